@@ -0,0 +1,97 @@
+//! Local SMPP SMSC for testing gateways offline.
+//!
+//! ```text
+//! smsc_simulator [--listen ADDR] [--system-id ID] [--password PW]
+//!                [--profile NAME] [--no-receipts] [--mo FROM,TO,DELAY_MS,TEXT]...
+//! ```
+//!
+//! Submitted messages are printed as they arrive; stop with Ctrl-C.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use gsm7_alt::smpp::{
+    DataCodingProfile, MoMessage, SmppAddress, SmscOptions, SmscSimulator, SubmitSm,
+};
+
+const USAGE: &str = "usage: smsc_simulator [--listen ADDR] [--system-id ID] [--password PW] \
+                     [--profile standard|gsm7-packed|latin1|ia5] [--no-receipts] \
+                     [--mo FROM,TO,DELAY_MS,TEXT]...";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut options = SmscOptions {
+        address: SocketAddr::from(([127, 0, 0, 1], 2775)),
+        ..SmscOptions::default()
+    };
+
+    let mut mo_specs = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{arg} needs a value\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--listen" => options.address = value()?.parse()?,
+            "--system-id" => options.system_id = value()?,
+            "--password" => options.password = Some(value()?),
+            "--profile" => {
+                let name = value()?;
+                options.profile = DataCodingProfile::named(&name)
+                    .ok_or_else(|| format!("unknown profile {name}\n{USAGE}"))?;
+            }
+            "--no-receipts" => options.delivery_receipts = false,
+            "--mo" => mo_specs.push(value()?),
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(format!("unknown argument {arg}\n{USAGE}").into()),
+        }
+    }
+
+    for spec in &mo_specs {
+        let mo = parse_mo(spec, &options.profile)?;
+        options.mo_script.push(mo);
+    }
+
+    let smsc = SmscSimulator::start(options)?;
+    println!("SMSC listening on {}", smsc.local_addr());
+
+    let mut seen = 0;
+    loop {
+        let messages = smsc.wait_for_messages(seen + 1, Duration::from_secs(1));
+        for message in &messages[seen..] {
+            println!(
+                "{} {} -> {} dcs=0x{:02X}: {}",
+                message.message_id,
+                message.submit.source.address,
+                message.submit.destination.address,
+                message.submit.data_coding,
+                message.text.as_deref().unwrap_or("<binary>")
+            );
+        }
+        seen = messages.len();
+    }
+}
+
+/// Parse `FROM,TO,DELAY_MS,TEXT`; numbers are international, other senders
+/// alphanumeric.
+fn parse_mo(
+    spec: &str,
+    profile: &DataCodingProfile,
+) -> Result<MoMessage, Box<dyn std::error::Error>> {
+    let [from, to, delay, text] = spec.splitn(4, ',').collect::<Vec<_>>()[..] else {
+        return Err(format!("--mo expects FROM,TO,DELAY_MS,TEXT, got {spec:?}").into());
+    };
+    let address = |value: &str| {
+        if value.chars().all(|ch| ch.is_ascii_digit()) {
+            SmppAddress::international(value)
+        } else {
+            SmppAddress::alphanumeric(value)
+        }
+    };
+    let delay = Duration::from_millis(delay.parse()?);
+    let deliver = SubmitSm::from_text_with_profile(address(from), address(to), text, profile)?;
+    Ok(MoMessage { delay, deliver })
+}
@@ -0,0 +1,1167 @@
+//! Cell Broadcast pages (3GPP TS 23.041 §9.4.1).
+//!
+//! A GSM cell broadcast message is sent as 1–15 pages of 88 octets: a 6-octet
+//! header (serial number, message identifier, DCS and page parameter) followed
+//! by 82 octets of content. GSM 7-bit content is 93 packed septets padded with
+//! `<CR>`, UCS-2 content 41 characters. The CBS data coding scheme
+//! (TS 23.038 §5) differs from the SMS one and is modelled separately.
+//!
+//! A page carries no text length, so `<CR>` characters at the end of a
+//! page's text cannot be told apart from the padding. When splitting, such
+//! characters are moved to the next page, so only those ending the message
+//! are lost when it is decoded.
+
+use std::ops::Range;
+
+use crate::dcs::charset_bits;
+use crate::segment::char_costs;
+use crate::{
+    CharacterSet, Gsm7Config, Gsm7Error, MessageClass, Result, SmsEncoding, decode_ucs2,
+    decode_with_config, encode_ucs2, encode_with_config, fill_bits_for_udh, hex, pack_septets,
+    unpack_septets, unpack_septets_with_fill,
+};
+
+/// Octets in a page: header and content.
+pub const PAGE_OCTETS: usize = 88;
+/// Octets of content in a page.
+pub const PAGE_CONTENT_OCTETS: usize = 82;
+/// Most pages a message can have.
+pub const MAX_PAGES: u8 = 15;
+
+/// Octets in the page header.
+const HEADER_OCTETS: usize = 6;
+/// GSM 7-bit septets that fit in the content of a page.
+const PAGE_SEPTETS: usize = 93;
+/// `<CR>`, the padding character.
+const CR: u8 = 0x0D;
+
+/// Area in which the serial number is unique (GS bits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GeographicalScope {
+    /// Cell wide, displayed immediately.
+    CellImmediate = 0,
+    /// PLMN wide.
+    Plmn = 1,
+    /// Location area, service area or tracking area wide.
+    LocationArea = 2,
+    /// Cell wide, normal display.
+    Cell = 3,
+}
+
+/// The Serial Number: scope, message code and update number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SerialNumber {
+    /// Geographical scope.
+    pub scope: GeographicalScope,
+    /// Message code, 0–1023. Tells messages with the same identifier apart.
+    pub message_code: u16,
+    /// Update number, 0–15. Changes when the content of a message changes.
+    pub update_number: u8,
+}
+
+impl SerialNumber {
+    /// Decode the 16-bit serial number.
+    pub fn from_u16(value: u16) -> Self {
+        Self {
+            scope: match value >> 14 {
+                0 => GeographicalScope::CellImmediate,
+                1 => GeographicalScope::Plmn,
+                2 => GeographicalScope::LocationArea,
+                _ => GeographicalScope::Cell,
+            },
+            message_code: (value >> 4) & 0x3FF,
+            update_number: (value & 0x0F) as u8,
+        }
+    }
+
+    /// Encode as the 16-bit serial number. Out-of-range fields are truncated.
+    pub fn to_u16(self) -> u16 {
+        (self.scope as u16) << 14
+            | (self.message_code & 0x3FF) << 4
+            | u16::from(self.update_number & 0x0F)
+    }
+}
+
+/// Earthquake and Tsunami Warning System message types (identifiers 4352–4359).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EtwsWarning {
+    /// Earthquake warning (4352).
+    Earthquake,
+    /// Tsunami warning (4353).
+    Tsunami,
+    /// Earthquake and tsunami warning (4354).
+    EarthquakeAndTsunami,
+    /// Test message (4355).
+    Test,
+    /// Other emergency types (4356).
+    OtherEmergency,
+    /// Reserved for future extension (4357–4359).
+    Reserved,
+}
+
+/// Public warning alert classes used by CMAS, EU-Alert and similar
+/// systems (identifiers 4370–4399).
+///
+/// Each class has one identifier for the primary language and one for an
+/// additional language; both map to the same class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CmasAlert {
+    /// Presidential alert (4370, 4383).
+    Presidential,
+    /// Extreme alert (4371–4372, 4384–4385).
+    Extreme,
+    /// Severe alert (4373–4378, 4386–4391).
+    Severe,
+    /// Child abduction emergency or AMBER alert (4379, 4392).
+    ChildAbduction,
+    /// Required monthly test (4380, 4393).
+    RequiredMonthlyTest,
+    /// Exercise (4381, 4394).
+    Exercise,
+    /// Operator defined use (4382, 4395).
+    OperatorDefined,
+    /// Public safety alert (4396, 4397).
+    PublicSafety,
+    /// State or local test (4398, 4399).
+    StateLocalTest,
+}
+
+/// The Message Identifier: source and type of the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageIdentifier(pub u16);
+
+impl MessageIdentifier {
+    /// The ETWS warning type, if the identifier is in the ETWS range.
+    pub fn etws(self) -> Option<EtwsWarning> {
+        Some(match self.0 {
+            4352 => EtwsWarning::Earthquake,
+            4353 => EtwsWarning::Tsunami,
+            4354 => EtwsWarning::EarthquakeAndTsunami,
+            4355 => EtwsWarning::Test,
+            4356 => EtwsWarning::OtherEmergency,
+            4357..=4359 => EtwsWarning::Reserved,
+            _ => return None,
+        })
+    }
+
+    /// The public warning alert class, if the identifier is in the CMAS range.
+    pub fn cmas(self) -> Option<CmasAlert> {
+        Some(match self.0 {
+            4370 | 4383 => CmasAlert::Presidential,
+            4371..=4372 | 4384..=4385 => CmasAlert::Extreme,
+            4373..=4378 | 4386..=4391 => CmasAlert::Severe,
+            4379 | 4392 => CmasAlert::ChildAbduction,
+            4380 | 4393 => CmasAlert::RequiredMonthlyTest,
+            4381 | 4394 => CmasAlert::Exercise,
+            4382 | 4395 => CmasAlert::OperatorDefined,
+            4396..=4397 => CmasAlert::PublicSafety,
+            4398..=4399 => CmasAlert::StateLocalTest,
+            _ => return None,
+        })
+    }
+}
+
+/// Language named by a CBS data coding scheme (groups 0000, 0010 and 0011).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CbsLanguage {
+    German,
+    English,
+    Italian,
+    French,
+    Spanish,
+    Dutch,
+    Swedish,
+    Danish,
+    Portuguese,
+    Finnish,
+    Norwegian,
+    Greek,
+    Turkish,
+    Hungarian,
+    Polish,
+    /// Language unspecified (0x0F).
+    Unspecified,
+    Czech,
+    Hebrew,
+    Arabic,
+    Russian,
+    Icelandic,
+    /// A reserved language code, with the raw DCS octet (0x25–0x3F).
+    Other(u8),
+}
+
+impl CbsLanguage {
+    /// Languages in order of their DCS octet, from 0x00 and from 0x20.
+    const GROUP_0000: [CbsLanguage; 16] = [
+        CbsLanguage::German,
+        CbsLanguage::English,
+        CbsLanguage::Italian,
+        CbsLanguage::French,
+        CbsLanguage::Spanish,
+        CbsLanguage::Dutch,
+        CbsLanguage::Swedish,
+        CbsLanguage::Danish,
+        CbsLanguage::Portuguese,
+        CbsLanguage::Finnish,
+        CbsLanguage::Norwegian,
+        CbsLanguage::Greek,
+        CbsLanguage::Turkish,
+        CbsLanguage::Hungarian,
+        CbsLanguage::Polish,
+        CbsLanguage::Unspecified,
+    ];
+    const GROUP_0010: [CbsLanguage; 5] = [
+        CbsLanguage::Czech,
+        CbsLanguage::Hebrew,
+        CbsLanguage::Arabic,
+        CbsLanguage::Russian,
+        CbsLanguage::Icelandic,
+    ];
+
+    fn from_octet(octet: u8) -> Self {
+        match octet {
+            0x00..=0x0F => Self::GROUP_0000[usize::from(octet)],
+            0x20..=0x24 => Self::GROUP_0010[usize::from(octet - 0x20)],
+            _ => CbsLanguage::Other(octet),
+        }
+    }
+
+    /// The DCS octet naming this language.
+    pub fn octet(self) -> u8 {
+        if let CbsLanguage::Other(octet) = self {
+            return octet;
+        }
+        let find = |group: &[CbsLanguage]| group.iter().position(|&language| language == self);
+        match find(&Self::GROUP_0000) {
+            Some(index) => index as u8,
+            None => 0x20 + find(&Self::GROUP_0010).unwrap_or_default() as u8,
+        }
+    }
+
+    /// The ISO 639 language code, or `None` for unspecified and reserved languages.
+    pub fn iso639(self) -> Option<&'static str> {
+        Some(match self {
+            CbsLanguage::German => "de",
+            CbsLanguage::English => "en",
+            CbsLanguage::Italian => "it",
+            CbsLanguage::French => "fr",
+            CbsLanguage::Spanish => "es",
+            CbsLanguage::Dutch => "nl",
+            CbsLanguage::Swedish => "sv",
+            CbsLanguage::Danish => "da",
+            CbsLanguage::Portuguese => "pt",
+            CbsLanguage::Finnish => "fi",
+            CbsLanguage::Norwegian => "no",
+            CbsLanguage::Greek => "el",
+            CbsLanguage::Turkish => "tr",
+            CbsLanguage::Hungarian => "hu",
+            CbsLanguage::Polish => "pl",
+            CbsLanguage::Czech => "cs",
+            CbsLanguage::Hebrew => "he",
+            CbsLanguage::Arabic => "ar",
+            CbsLanguage::Russian => "ru",
+            CbsLanguage::Icelandic => "is",
+            CbsLanguage::Unspecified | CbsLanguage::Other(_) => return None,
+        })
+    }
+}
+
+/// A decoded CBS data coding scheme octet (3GPP TS 23.038 §5).
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::CharacterSet;
+/// use gsm7_alt::cbs::{CbsDataCodingScheme, CbsLanguage};
+///
+/// let dcs = CbsDataCodingScheme::from_octet(0x01);
+/// assert_eq!(dcs, CbsDataCodingScheme::Language(CbsLanguage::English));
+/// assert_eq!(dcs.character_set(), Some(CharacterSet::Gsm7));
+/// assert_eq!(CbsDataCodingScheme::from_octet(0x11).to_octet(), 0x11);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CbsDataCodingScheme {
+    /// Groups 0000, 0010 and 0011: GSM 7-bit text in the language named here.
+    Language(CbsLanguage),
+    /// 0x10 (GSM 7-bit) and 0x11 (UCS-2): the text starts with its two-letter
+    /// ISO 639 language code.
+    LanguageInText(CharacterSet),
+    /// General data coding group (01xx).
+    General {
+        /// Text is compressed (TS 23.042).
+        compressed: bool,
+        /// Coding of the content. The reserved value 11 reads as GSM 7-bit.
+        character_set: CharacterSet,
+        /// Message class, if one is given.
+        class: Option<MessageClass>,
+    },
+    /// Message with a User Data Header (1001).
+    WithHeader {
+        /// Coding of the content after the header.
+        character_set: CharacterSet,
+        /// Message class.
+        class: MessageClass,
+    },
+    /// Data coding/message handling group (1111).
+    DataCodingClass {
+        /// GSM 7-bit or 8-bit data.
+        character_set: CharacterSet,
+        /// Message class 1, 2 or 3, if one is given.
+        class: Option<MessageClass>,
+    },
+    /// Reserved and WAP coding groups, with the raw octet.
+    Reserved(u8),
+}
+
+impl CbsDataCodingScheme {
+    /// Decode a CBS DCS octet.
+    pub fn from_octet(octet: u8) -> Self {
+        let character_set = match (octet >> 2) & 0b11 {
+            0b01 => CharacterSet::EightBit,
+            0b10 => CharacterSet::Ucs2,
+            // 0b11 is reserved and read as the default alphabet
+            _ => CharacterSet::Gsm7,
+        };
+        match octet >> 4 {
+            0x0 | 0x2 | 0x3 => CbsDataCodingScheme::Language(CbsLanguage::from_octet(octet)),
+            0x1 => match octet {
+                0x10 => CbsDataCodingScheme::LanguageInText(CharacterSet::Gsm7),
+                0x11 => CbsDataCodingScheme::LanguageInText(CharacterSet::Ucs2),
+                _ => CbsDataCodingScheme::Reserved(octet),
+            },
+            0x4..=0x7 => CbsDataCodingScheme::General {
+                compressed: octet & 0x20 != 0,
+                character_set,
+                class: (octet & 0x10 != 0).then(|| MessageClass::from_bits(octet)),
+            },
+            0x9 => CbsDataCodingScheme::WithHeader {
+                character_set,
+                class: MessageClass::from_bits(octet),
+            },
+            0xF => CbsDataCodingScheme::DataCodingClass {
+                character_set: if octet & 0x04 != 0 {
+                    CharacterSet::EightBit
+                } else {
+                    CharacterSet::Gsm7
+                },
+                class: (octet & 0b11 != 0).then(|| MessageClass::from_bits(octet)),
+            },
+            _ => CbsDataCodingScheme::Reserved(octet),
+        }
+    }
+
+    /// Encode as a CBS DCS octet.
+    ///
+    /// Combinations the coding group cannot express fall back to the nearest
+    /// valid octet: language-in-text with 8-bit data is written as GSM 7-bit
+    /// (0x10), and the data coding/message handling group writes UCS-2 as
+    /// 8-bit and class 0 as no class.
+    pub fn to_octet(self) -> u8 {
+        match self {
+            CbsDataCodingScheme::Language(language) => language.octet(),
+            CbsDataCodingScheme::LanguageInText(CharacterSet::Ucs2) => 0x11,
+            CbsDataCodingScheme::LanguageInText(_) => 0x10,
+            CbsDataCodingScheme::General {
+                compressed,
+                character_set,
+                class,
+            } => {
+                let mut octet = 0x40 | charset_bits(character_set) << 2;
+                if compressed {
+                    octet |= 0x20;
+                }
+                if let Some(class) = class {
+                    octet |= 0x10 | class as u8;
+                }
+                octet
+            }
+            CbsDataCodingScheme::WithHeader {
+                character_set,
+                class,
+            } => 0x90 | charset_bits(character_set) << 2 | class as u8,
+            CbsDataCodingScheme::DataCodingClass {
+                character_set,
+                class,
+            } => {
+                let data = u8::from(character_set != CharacterSet::Gsm7) << 2;
+                0xF0 | data | class.map_or(0, |class| class as u8)
+            }
+            CbsDataCodingScheme::Reserved(octet) => octet,
+        }
+    }
+
+    /// Coding of the content, or `None` for reserved coding groups.
+    pub fn character_set(self) -> Option<CharacterSet> {
+        match self {
+            CbsDataCodingScheme::Language(_) => Some(CharacterSet::Gsm7),
+            CbsDataCodingScheme::LanguageInText(character_set)
+            | CbsDataCodingScheme::General { character_set, .. }
+            | CbsDataCodingScheme::WithHeader { character_set, .. }
+            | CbsDataCodingScheme::DataCodingClass { character_set, .. } => Some(character_set),
+            CbsDataCodingScheme::Reserved(_) => None,
+        }
+    }
+
+    /// The language the DCS names, if any.
+    pub fn language(self) -> Option<CbsLanguage> {
+        match self {
+            CbsDataCodingScheme::Language(language) => Some(language),
+            _ => None,
+        }
+    }
+
+    /// The message class, if the DCS gives one.
+    pub fn message_class(self) -> Option<MessageClass> {
+        match self {
+            CbsDataCodingScheme::General { class, .. }
+            | CbsDataCodingScheme::DataCodingClass { class, .. } => class,
+            CbsDataCodingScheme::WithHeader { class, .. } => Some(class),
+            _ => None,
+        }
+    }
+
+    /// Whether the content is compressed.
+    pub fn is_compressed(self) -> bool {
+        matches!(
+            self,
+            CbsDataCodingScheme::General {
+                compressed: true,
+                ..
+            }
+        )
+    }
+}
+
+impl From<u8> for CbsDataCodingScheme {
+    fn from(octet: u8) -> Self {
+        Self::from_octet(octet)
+    }
+}
+
+impl From<CbsDataCodingScheme> for u8 {
+    fn from(dcs: CbsDataCodingScheme) -> Self {
+        dcs.to_octet()
+    }
+}
+
+/// One 88-octet page of a cell broadcast message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CbsPage {
+    /// Serial Number.
+    pub serial_number: SerialNumber,
+    /// Message Identifier.
+    pub message_identifier: MessageIdentifier,
+    /// Data Coding Scheme.
+    pub data_coding_scheme: CbsDataCodingScheme,
+    /// This page's number, from 1.
+    pub page: u8,
+    /// Number of pages in the message.
+    pub pages: u8,
+    /// The padded content.
+    pub content: [u8; PAGE_CONTENT_OCTETS],
+}
+
+impl CbsPage {
+    /// Parse an 88-octet page.
+    ///
+    /// A page parameter of 0x00 reads as page 1 of 1.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::TruncatedPdu` if `bytes` is shorter than a page,
+    /// and `Gsm7Error::InvalidPduField` if it is longer or the page parameter
+    /// names a page outside `1..=pages`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < PAGE_OCTETS {
+            return Err(Gsm7Error::TruncatedPdu {
+                field: if bytes.len() < HEADER_OCTETS {
+                    "CBS header"
+                } else {
+                    "CBS content"
+                },
+                offset: bytes.len(),
+            });
+        }
+        if bytes.len() > PAGE_OCTETS {
+            return Err(Gsm7Error::InvalidPduField {
+                field: "CBS content",
+                offset: PAGE_OCTETS,
+                reason: format!("{} trailing octets", bytes.len() - PAGE_OCTETS),
+            });
+        }
+
+        let (page, pages) = match bytes[5] {
+            0x00 => (1, 1),
+            parameter => (parameter >> 4, parameter & 0x0F),
+        };
+        if page == 0 || page > pages {
+            return Err(Gsm7Error::InvalidPduField {
+                field: "CBS page parameter",
+                offset: 5,
+                reason: format!("page {page} of {pages}"),
+            });
+        }
+
+        let mut content = [0; PAGE_CONTENT_OCTETS];
+        content.copy_from_slice(&bytes[HEADER_OCTETS..]);
+        Ok(Self {
+            serial_number: SerialNumber::from_u16(u16::from_be_bytes([bytes[0], bytes[1]])),
+            message_identifier: MessageIdentifier(u16::from_be_bytes([bytes[2], bytes[3]])),
+            data_coding_scheme: CbsDataCodingScheme::from_octet(bytes[4]),
+            page,
+            pages,
+            content,
+        })
+    }
+
+    /// Parse a page from a hex string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidHex` if `hex` is not a hex string, and
+    /// otherwise the errors of [`from_bytes`](Self::from_bytes).
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        Self::from_bytes(&hex::decode(hex)?)
+    }
+
+    /// Serialize the page to its 88 octets.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PAGE_OCTETS);
+        bytes.extend_from_slice(&self.serial_number.to_u16().to_be_bytes());
+        bytes.extend_from_slice(&self.message_identifier.0.to_be_bytes());
+        bytes.push(self.data_coding_scheme.to_octet());
+        bytes.push((self.page & 0x0F) << 4 | (self.pages & 0x0F));
+        bytes.extend_from_slice(&self.content);
+        bytes
+    }
+
+    /// Serialize the page as uppercase hex.
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.to_bytes())
+    }
+
+    /// Units of text in the page with the padding removed: septets for
+    /// GSM 7-bit, octets for UCS-2.
+    ///
+    /// Without a text length, every trailing `<CR>` counts as padding, and in
+    /// UCS-2 every trailing U+000D or U+0000, so real ones ending the text go
+    /// with it.
+    fn text_units(&self, character_set: CharacterSet) -> Result<Vec<u8>> {
+        let (content, header_len) = match self.data_coding_scheme {
+            CbsDataCodingScheme::WithHeader { .. } => {
+                let header_len = usize::from(self.content[0]) + 1;
+                if header_len >= PAGE_CONTENT_OCTETS {
+                    return Err(Gsm7Error::InvalidPduField {
+                        field: "CBS content",
+                        offset: HEADER_OCTETS,
+                        reason: "header longer than the page".to_string(),
+                    });
+                }
+                (&self.content[header_len..], header_len)
+            }
+            _ => (&self.content[..], 0),
+        };
+
+        match character_set {
+            CharacterSet::Gsm7 => {
+                let fill_bits = fill_bits_for_udh(header_len);
+                let header_septets = (header_len * 8 + usize::from(fill_bits)) / 7;
+                let mut septets =
+                    unpack_septets_with_fill(content, PAGE_SEPTETS - header_septets, fill_bits)?;
+                while septets.last() == Some(&CR) {
+                    septets.pop();
+                }
+                Ok(septets)
+            }
+            _ => {
+                let mut octets = content.to_vec();
+                while let [.., 0x00, CR | 0x00] = octets[..] {
+                    octets.truncate(octets.len() - 2);
+                }
+                Ok(octets)
+            }
+        }
+    }
+}
+
+/// A cell broadcast message: the text of all its pages.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::cbs::{CbsMessage, GeographicalScope, MessageIdentifier, SerialNumber};
+///
+/// let serial_number = SerialNumber {
+///     scope: GeographicalScope::Plmn,
+///     message_code: 1,
+///     update_number: 0,
+/// };
+/// let message = CbsMessage::new(serial_number, MessageIdentifier(50), &"Weather ".repeat(20));
+/// let pages = message.to_pages()?;
+/// assert_eq!(pages.len(), 2);
+/// assert_eq!(pages[0].to_bytes().len(), 88);
+/// assert_eq!(CbsMessage::from_pages(&pages)?, message);
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CbsMessage {
+    /// Serial Number shared by the pages.
+    pub serial_number: SerialNumber,
+    /// Message Identifier shared by the pages.
+    pub message_identifier: MessageIdentifier,
+    /// Data Coding Scheme shared by the pages.
+    pub data_coding_scheme: CbsDataCodingScheme,
+    /// ISO 639 language code. When decoding, it comes from the DCS or the
+    /// start of the text; when encoding, it is only used, and then required,
+    /// with [`CbsDataCodingScheme::LanguageInText`].
+    pub language: Option<String>,
+    /// The text, without the language code and padding.
+    pub text: String,
+}
+
+impl CbsMessage {
+    /// A GSM 7-bit message with the language unspecified.
+    pub fn new(
+        serial_number: SerialNumber,
+        message_identifier: MessageIdentifier,
+        text: &str,
+    ) -> Self {
+        Self {
+            serial_number,
+            message_identifier,
+            data_coding_scheme: CbsDataCodingScheme::Language(CbsLanguage::Unspecified),
+            language: None,
+            text: text.to_string(),
+        }
+    }
+
+    /// Split the message into pages.
+    ///
+    /// Pages never split a GSM 7-bit escape sequence or a UTF-16 surrogate
+    /// pair, and a page other than the last never ends in a character read as
+    /// padding (`<CR>`, or in UCS-2 U+000D or U+0000); it goes to the next
+    /// page instead. GSM 7-bit pages are padded with `<CR>`, UCS-2 pages with
+    /// U+000D.
+    ///
+    /// # Returns
+    ///
+    /// The pages in order, numbered from 1.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::UnsupportedDataCoding` if the DCS carries no text,
+    /// `Gsm7Error::UnsupportedCharacter` if the text cannot be encoded in the
+    /// DCS's alphabet, and `Gsm7Error::InvalidCbsMessage` if the DCS announces
+    /// a header, the language code is missing, or the text needs more than 15
+    /// pages.
+    pub fn to_pages(&self) -> Result<Vec<CbsPage>> {
+        let invalid = |reason: String| Gsm7Error::InvalidCbsMessage { reason };
+        let dcs = self.data_coding_scheme;
+        let encoding = match dcs.character_set() {
+            _ if matches!(dcs, CbsDataCodingScheme::WithHeader { .. }) => {
+                return Err(invalid(
+                    "pages with a User Data Header are not supported".into(),
+                ));
+            }
+            _ if dcs.is_compressed() => {
+                return Err(invalid("compressed text is not supported".into()));
+            }
+            Some(CharacterSet::Gsm7) => SmsEncoding::GSM7_DEFAULT,
+            Some(CharacterSet::Ucs2) => SmsEncoding::Ucs2,
+            _ => {
+                return Err(Gsm7Error::UnsupportedDataCoding {
+                    dcs: dcs.to_octet(),
+                });
+            }
+        };
+        let config = Gsm7Config::strict();
+
+        // Text to split, and octets in front of the text on the first page
+        let mut text = self.text.clone();
+        let mut prefix = Vec::new();
+        if let CbsDataCodingScheme::LanguageInText(_) = dcs {
+            let language = self
+                .language
+                .as_deref()
+                .filter(|language| language.chars().count() == 2)
+                .ok_or_else(|| invalid("a two-letter language code is required".into()))?;
+            let septets = encode_with_config(language, &config)?;
+            if septets.len() != 2 {
+                return Err(invalid(format!(
+                    "language code {language:?} is not two septets"
+                )));
+            }
+            match encoding {
+                SmsEncoding::Ucs2 => prefix = pack_septets(&septets),
+                SmsEncoding::Gsm7 { .. } => text = format!("{language}\r{text}"),
+            }
+        }
+
+        let capacity = |page: usize| match encoding {
+            SmsEncoding::Gsm7 { .. } => PAGE_SEPTETS,
+            SmsEncoding::Ucs2 if page == 0 => (PAGE_CONTENT_OCTETS - prefix.len()) / 2,
+            SmsEncoding::Ucs2 => PAGE_CONTENT_OCTETS / 2,
+        };
+        let pads = |ch: char| match encoding {
+            SmsEncoding::Gsm7 { .. } => ch == '\r',
+            SmsEncoding::Ucs2 => matches!(ch, '\r' | '\0'),
+        };
+        let ranges = page_ranges(&text, &char_costs(&text, encoding)?, capacity, pads);
+        if ranges.len() > usize::from(MAX_PAGES) {
+            return Err(invalid(format!(
+                "text needs {} pages, more than the maximum of {MAX_PAGES}",
+                ranges.len()
+            )));
+        }
+
+        let pages = ranges.len() as u8;
+        (1..)
+            .zip(ranges)
+            .map(|(page, range)| {
+                let page_text = &text[range];
+                let octets = match encoding {
+                    SmsEncoding::Gsm7 { .. } => {
+                        let mut septets = encode_with_config(page_text, &config)?;
+                        septets.resize(PAGE_SEPTETS, CR);
+                        pack_septets(&septets)
+                    }
+                    SmsEncoding::Ucs2 => {
+                        let mut octets = if page == 1 {
+                            prefix.clone()
+                        } else {
+                            Vec::new()
+                        };
+                        octets.extend(encode_ucs2(page_text)?);
+                        while octets.len() < PAGE_CONTENT_OCTETS {
+                            octets.extend_from_slice(&[0x00, CR]);
+                        }
+                        octets
+                    }
+                };
+                let mut content = [0; PAGE_CONTENT_OCTETS];
+                content.copy_from_slice(&octets);
+                Ok(CbsPage {
+                    serial_number: self.serial_number,
+                    message_identifier: self.message_identifier,
+                    data_coding_scheme: dcs,
+                    page,
+                    pages,
+                    content,
+                })
+            })
+            .collect()
+    }
+
+    /// Put a message together from its pages, in any order.
+    ///
+    /// GSM 7-bit text is decoded with the default alphabet in non-strict mode.
+    /// Trailing `<CR>` padding is removed from every page, together with any
+    /// `<CR>` (or, in UCS-2, U+0000) that really ended the page's text; pages
+    /// carry no text length to tell them apart. Pages made by
+    /// [`to_pages`](Self::to_pages) only lose such characters at the end of
+    /// the message. A repeated page is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidCbsMessage` if there are no pages, they
+    /// belong to different messages or a page is missing;
+    /// `Gsm7Error::UnsupportedDataCoding` if the DCS carries no text;
+    /// `Gsm7Error::InvalidPduField` for a malformed User Data Header; and
+    /// `Gsm7Error::InvalidUtf16` for malformed UCS-2 text.
+    pub fn from_pages(pages: &[CbsPage]) -> Result<Self> {
+        let invalid = |reason: String| Gsm7Error::InvalidCbsMessage { reason };
+        let first = pages.first().ok_or_else(|| invalid("no pages".into()))?;
+        let dcs = first.data_coding_scheme;
+
+        let mut ordered = vec![None; usize::from(first.pages)];
+        for page in pages {
+            if (
+                page.serial_number,
+                page.message_identifier,
+                page.data_coding_scheme,
+                page.pages,
+            ) != (
+                first.serial_number,
+                first.message_identifier,
+                dcs,
+                first.pages,
+            ) {
+                return Err(invalid("pages belong to different messages".into()));
+            }
+            let slot = page
+                .page
+                .checked_sub(1)
+                .and_then(|index| ordered.get_mut(usize::from(index)))
+                .ok_or_else(|| invalid(format!("page {} of {}", page.page, page.pages)))?;
+            slot.get_or_insert(page);
+        }
+        if let Some(index) = ordered.iter().position(Option::is_none) {
+            return Err(invalid(format!(
+                "page {} of {} is missing",
+                index + 1,
+                first.pages
+            )));
+        }
+        let ordered: Vec<&CbsPage> = ordered.into_iter().flatten().collect();
+
+        if dcs.is_compressed() {
+            return Err(invalid("compressed text is not supported".into()));
+        }
+        let character_set = match dcs.character_set() {
+            Some(character_set @ (CharacterSet::Gsm7 | CharacterSet::Ucs2)) => character_set,
+            _ => {
+                return Err(Gsm7Error::UnsupportedDataCoding {
+                    dcs: dcs.to_octet(),
+                });
+            }
+        };
+        let language_in_text = matches!(dcs, CbsDataCodingScheme::LanguageInText(_));
+        let config = Gsm7Config::default();
+
+        let mut units = Vec::new();
+        for page in &ordered {
+            units.extend(page.text_units(character_set)?);
+        }
+
+        let mut language = dcs
+            .language()
+            .and_then(CbsLanguage::iso639)
+            .map(String::from);
+        let text = match character_set {
+            CharacterSet::Ucs2 if language_in_text => {
+                let code = unpack_septets(&ordered[0].content[..2], 2);
+                language = Some(decode_with_config(&code, &config)?);
+                decode_ucs2(units.get(2..).unwrap_or_default())?
+            }
+            CharacterSet::Ucs2 => decode_ucs2(&units)?,
+            _ => {
+                let text = decode_with_config(&units, &config)?;
+                if language_in_text {
+                    let split = text.char_indices().nth(2).map_or(text.len(), |(i, _)| i);
+                    let (code, rest) = text.split_at(split);
+                    language = Some(code.to_string());
+                    rest.strip_prefix('\r').unwrap_or(rest).to_string()
+                } else {
+                    text
+                }
+            }
+        };
+
+        Ok(Self {
+            serial_number: first.serial_number,
+            message_identifier: first.message_identifier,
+            data_coding_scheme: dcs,
+            language,
+            text,
+        })
+    }
+}
+
+/// Split `text` into page ranges of at most `capacity(page index)` units,
+/// never inside one of the indivisible pieces in `costs`.
+///
+/// A page other than the last does not end in a character for which `pads`
+/// holds, unless the page holds nothing else; such characters start the
+/// next page instead.
+fn page_ranges(
+    text: &str,
+    costs: &[(usize, usize)],
+    capacity: impl Fn(usize) -> usize,
+    pads: impl Fn(char) -> bool,
+) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut first = 0;
+    let mut units = 0;
+    let mut index = 0;
+    while let Some(&(_, cost)) = costs.get(index) {
+        if units + cost > capacity(ranges.len()) && index > first {
+            let mut end = index;
+            while end - 1 > first && text[costs[end - 1].0..].starts_with(&pads) {
+                end -= 1;
+            }
+            ranges.push(costs[first].0..costs[end].0);
+            first = end;
+            index = end;
+            units = 0;
+            continue;
+        }
+        units += cost;
+        index += 1;
+    }
+    ranges.push(costs.get(first).map_or(0, |&(offset, _)| offset)..text.len());
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UserDataHeader;
+
+    fn serial_number() -> SerialNumber {
+        SerialNumber {
+            scope: GeographicalScope::Cell,
+            message_code: 0x123,
+            update_number: 4,
+        }
+    }
+
+    #[test]
+    fn test_page_layout() {
+        let mut message = CbsMessage::new(serial_number(), MessageIdentifier(0x1112), "Hello");
+        message.data_coding_scheme = CbsDataCodingScheme::Language(CbsLanguage::English);
+        let pages = message.to_pages().unwrap();
+        assert_eq!(pages.len(), 1);
+
+        let bytes = pages[0].to_bytes();
+        assert_eq!(bytes.len(), PAGE_OCTETS);
+        assert_eq!(bytes[..6], [0xD2, 0x34, 0x11, 0x12, 0x01, 0x11]);
+        let mut septets = b"Hello".to_vec();
+        septets.resize(PAGE_SEPTETS, CR);
+        assert_eq!(bytes[6..], pack_septets(&septets));
+
+        let parsed = CbsPage::from_hex(&pages[0].to_hex()).unwrap();
+        assert_eq!(parsed, pages[0]);
+        assert_eq!(parsed.serial_number, serial_number());
+        let decoded = CbsMessage::from_pages(&[parsed]).unwrap();
+        assert_eq!(decoded.text, "Hello");
+        assert_eq!(decoded.language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_trailing_cr_is_taken_for_padding() {
+        for dcs in [
+            CbsDataCodingScheme::Language(CbsLanguage::English),
+            CbsDataCodingScheme::General {
+                compressed: false,
+                character_set: CharacterSet::Ucs2,
+                class: None,
+            },
+        ] {
+            let mut message = CbsMessage::new(serial_number(), MessageIdentifier(50), "Line\r");
+            message.data_coding_scheme = dcs;
+            let decoded = CbsMessage::from_pages(&message.to_pages().unwrap()).unwrap();
+            assert_eq!(decoded.text, "Line");
+        }
+    }
+
+    #[test]
+    fn test_cr_ending_a_full_page_moves_to_the_next() {
+        let text = format!("{}\r\r{}", "a".repeat(91), "b".repeat(5));
+        let message = CbsMessage::new(serial_number(), MessageIdentifier(50), &text);
+        let pages = message.to_pages().unwrap();
+        assert_eq!(pages.len(), 2);
+        let first = unpack_septets(&pages[0].content, PAGE_SEPTETS);
+        assert_eq!(&first[90..], [b'a', CR, CR]);
+        assert_eq!(CbsMessage::from_pages(&pages).unwrap().text, text);
+
+        let text = format!("{}\r{}", "a".repeat(92), "b".repeat(5));
+        let message = CbsMessage::new(serial_number(), MessageIdentifier(50), &text);
+        assert_eq!(
+            CbsMessage::from_pages(&message.to_pages().unwrap())
+                .unwrap()
+                .text,
+            text
+        );
+
+        for end in ['\r', '\0'] {
+            let text = format!("{}{end}{}", "Ж".repeat(40), "b".repeat(5));
+            let mut message = CbsMessage::new(serial_number(), MessageIdentifier(50), &text);
+            message.data_coding_scheme = CbsDataCodingScheme::General {
+                compressed: false,
+                character_set: CharacterSet::Ucs2,
+                class: None,
+            };
+            let pages = message.to_pages().unwrap();
+            assert_eq!(pages.len(), 2);
+            assert_eq!(CbsMessage::from_pages(&pages).unwrap().text, text);
+        }
+    }
+
+    #[test]
+    fn test_multi_page_keeps_escapes_whole() {
+        let text = format!("{}€{}", "a".repeat(92), "b".repeat(100));
+        let message = CbsMessage::new(serial_number(), MessageIdentifier(50), &text);
+        let mut pages = message.to_pages().unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_eq!((pages[1].page, pages[1].pages), (2, 3));
+        assert_eq!(
+            CbsMessage::from_pages(&[pages[0].clone()]),
+            Err(Gsm7Error::InvalidCbsMessage {
+                reason: "page 2 of 3 is missing".to_string()
+            })
+        );
+
+        // The 93rd septet would be the escape; it moves to page 2
+        let first = unpack_septets(&pages[0].content, PAGE_SEPTETS);
+        assert_eq!(first[92], CR);
+
+        pages.swap(0, 2);
+        pages.push(pages[1].clone());
+        assert_eq!(CbsMessage::from_pages(&pages).unwrap().text, text);
+    }
+
+    #[test]
+    fn test_language_in_text() {
+        let mut message = CbsMessage::new(serial_number(), MessageIdentifier(4371), "Évacuez");
+        message.data_coding_scheme = CbsDataCodingScheme::LanguageInText(CharacterSet::Gsm7);
+        message.language = Some("fr".to_string());
+        let pages = message.to_pages().unwrap();
+        assert_eq!(pages[0].to_bytes()[4], 0x10);
+        assert_eq!(
+            unpack_septets(&pages[0].content, 3),
+            encode_with_config("fr\r", &Gsm7Config::strict()).unwrap()
+        );
+        assert_eq!(CbsMessage::from_pages(&pages).unwrap(), message);
+
+        let text = "Внимание! ".repeat(9);
+        message.data_coding_scheme = CbsDataCodingScheme::LanguageInText(CharacterSet::Ucs2);
+        message.language = Some("ru".to_string());
+        message.text = text.clone();
+        let pages = message.to_pages().unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].content[..2], pack_septets(b"ru"));
+        assert_eq!(
+            decode_ucs2(&pages[0].content[2..]).unwrap(),
+            text[..text.char_indices().nth(40).unwrap().0]
+        );
+        assert_eq!(CbsMessage::from_pages(&pages).unwrap(), message);
+
+        message.language = None;
+        assert!(matches!(
+            message.to_pages(),
+            Err(Gsm7Error::InvalidCbsMessage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_limits_and_unsupported_coding() {
+        let message = CbsMessage::new(serial_number(), MessageIdentifier(1), &"x".repeat(93 * 15));
+        assert_eq!(message.to_pages().unwrap().len(), 15);
+        let message = CbsMessage::new(
+            serial_number(),
+            MessageIdentifier(1),
+            &"x".repeat(93 * 15 + 1),
+        );
+        assert!(matches!(
+            message.to_pages(),
+            Err(Gsm7Error::InvalidCbsMessage { .. })
+        ));
+
+        let mut message = CbsMessage::new(serial_number(), MessageIdentifier(1), "data");
+        message.data_coding_scheme = CbsDataCodingScheme::from_octet(0xF4);
+        assert_eq!(
+            message.to_pages(),
+            Err(Gsm7Error::UnsupportedDataCoding { dcs: 0xF4 })
+        );
+        message.data_coding_scheme = CbsDataCodingScheme::Language(CbsLanguage::German);
+        message.text = "Grüße 🦀".to_string();
+        assert!(matches!(
+            message.to_pages(),
+            Err(Gsm7Error::UnsupportedCharacter {
+                character: '🦀',
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_page_with_header() {
+        let header = UserDataHeader::parse(&[0x06, 0x05, 0x04, 0x0B, 0x84, 0x23, 0xF0]).unwrap();
+        let mut septets = encode_with_config("Port message", &Gsm7Config::strict()).unwrap();
+        septets.resize(PAGE_SEPTETS - header.septets(), CR);
+        let mut content = header.to_bytes();
+        content.extend(crate::pack_septets_with_fill(&septets, header.fill_bits()).unwrap());
+        content.resize(PAGE_CONTENT_OCTETS, 0);
+
+        let mut bytes = vec![0x40, 0x10, 0x00, 0x32, 0x90, 0x11];
+        bytes.extend_from_slice(&content);
+        let page = CbsPage::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            page.data_coding_scheme,
+            CbsDataCodingScheme::WithHeader {
+                character_set: CharacterSet::Gsm7,
+                class: MessageClass::Class0,
+            }
+        );
+        assert_eq!(
+            CbsMessage::from_pages(&[page]).unwrap().text,
+            "Port message"
+        );
+    }
+
+    #[test]
+    fn test_page_errors() {
+        let mut bytes = vec![0; PAGE_OCTETS];
+        assert_eq!(CbsPage::from_bytes(&bytes).unwrap().pages, 1);
+        bytes[5] = 0x32;
+        assert!(matches!(
+            CbsPage::from_bytes(&bytes),
+            Err(Gsm7Error::InvalidPduField { offset: 5, .. })
+        ));
+        assert_eq!(
+            CbsPage::from_bytes(&bytes[..4]),
+            Err(Gsm7Error::TruncatedPdu {
+                field: "CBS header",
+                offset: 4,
+            })
+        );
+        bytes.push(0);
+        assert!(matches!(
+            CbsPage::from_bytes(&bytes),
+            Err(Gsm7Error::InvalidPduField { offset: 88, .. })
+        ));
+    }
+
+    #[test]
+    fn test_data_coding_scheme() {
+        for octet in [
+            0x00, 0x0F, 0x10, 0x11, 0x24, 0x31, 0x48, 0x59, 0x95, 0xF1, 0xF7,
+        ] {
+            assert_eq!(CbsDataCodingScheme::from_octet(octet).to_octet(), octet);
+        }
+        assert_eq!(
+            CbsDataCodingScheme::from_octet(0x23).language(),
+            Some(CbsLanguage::Russian)
+        );
+        assert_eq!(CbsLanguage::Other(0x31).iso639(), None);
+        assert_eq!(
+            CbsDataCodingScheme::from_octet(0x59).message_class(),
+            Some(MessageClass::Class1)
+        );
+        assert_eq!(
+            CbsDataCodingScheme::from_octet(0xF4).character_set(),
+            Some(CharacterSet::EightBit)
+        );
+        assert_eq!(
+            CbsDataCodingScheme::from_octet(0x12),
+            CbsDataCodingScheme::Reserved(0x12)
+        );
+    }
+
+    #[test]
+    fn test_warning_identifiers() {
+        assert_eq!(
+            MessageIdentifier(0x1100).etws(),
+            Some(EtwsWarning::Earthquake)
+        );
+        assert_eq!(
+            MessageIdentifier(0x1104).etws(),
+            Some(EtwsWarning::OtherEmergency)
+        );
+        assert_eq!(
+            MessageIdentifier(0x1112).cmas(),
+            Some(CmasAlert::Presidential)
+        );
+        assert_eq!(
+            MessageIdentifier(4392).cmas(),
+            Some(CmasAlert::ChildAbduction)
+        );
+        assert_eq!(MessageIdentifier(50).etws(), None);
+        assert_eq!(MessageIdentifier(50).cmas(), None);
+
+        let serial = SerialNumber::from_u16(0x7FF3);
+        assert_eq!(serial.scope, GeographicalScope::Plmn);
+        assert_eq!((serial.message_code, serial.update_number), (0x3FF, 3));
+        assert_eq!(serial.to_u16(), 0x7FF3);
+    }
+}
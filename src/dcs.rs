@@ -0,0 +1,402 @@
+//! TP-DCS data coding scheme (3GPP TS 23.038 §4).
+//!
+//! The DCS octet tells the receiver how the user data is coded and, depending
+//! on the coding group, carries a message class, a compression flag or a
+//! message waiting indication. Reserved bits within a group are written as zero.
+
+use crate::SmsEncoding;
+
+/// How the user data is coded, and so how to decode it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharacterSet {
+    /// GSM 7-bit default alphabet, packed: decode with
+    /// [`decode_packed`](crate::decode_packed) or
+    /// [`unpack_septets_with_fill`](crate::unpack_septets_with_fill).
+    Gsm7,
+    /// 8-bit data: the payload is binary and not decoded.
+    EightBit,
+    /// UCS-2: decode with [`decode_ucs2`](crate::decode_ucs2).
+    Ucs2,
+}
+
+/// Message class: where the receiving handset puts the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageClass {
+    /// Class 0: displayed immediately ("flash SMS"), not necessarily stored.
+    Class0,
+    /// Class 1: stored in the handset (ME specific).
+    Class1,
+    /// Class 2: stored on the SIM.
+    Class2,
+    /// Class 3: passed to terminal equipment (TE specific).
+    Class3,
+}
+
+impl MessageClass {
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0 => MessageClass::Class0,
+            1 => MessageClass::Class1,
+            2 => MessageClass::Class2,
+            _ => MessageClass::Class3,
+        }
+    }
+}
+
+/// Kind of message waiting indication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndicationType {
+    /// Voicemail message waiting.
+    Voicemail,
+    /// Fax message waiting.
+    Fax,
+    /// Electronic mail message waiting.
+    Email,
+    /// Other message waiting.
+    Other,
+}
+
+impl IndicationType {
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0 => IndicationType::Voicemail,
+            1 => IndicationType::Fax,
+            2 => IndicationType::Email,
+            _ => IndicationType::Other,
+        }
+    }
+}
+
+/// A decoded TP-DCS octet.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{CharacterSet, DataCodingScheme, MessageClass};
+///
+/// let dcs = DataCodingScheme::from_octet(0x18);
+/// assert_eq!(dcs.character_set(), Some(CharacterSet::Ucs2));
+/// assert_eq!(dcs.message_class(), Some(MessageClass::Class0));
+/// assert_eq!(dcs.to_octet(), 0x18);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataCodingScheme {
+    /// General data coding groups (00xx and, with automatic deletion, 01xx).
+    General {
+        /// Text is compressed (TS 23.042).
+        compressed: bool,
+        /// Coding of the user data. The reserved value 11 reads as GSM 7-bit.
+        character_set: CharacterSet,
+        /// Message class, if one is given.
+        class: Option<MessageClass>,
+        /// The message is deleted once read (group 01xx).
+        auto_delete: bool,
+    },
+    /// Message waiting indication groups (1100, 1101 and 1110).
+    MessageWaiting {
+        /// Store the message (1101, 1110) rather than discard it (1100).
+        store: bool,
+        /// GSM 7-bit for groups 1100 and 1101, UCS-2 for 1110.
+        character_set: CharacterSet,
+        /// Set the indication (`true`) or clear it.
+        active: bool,
+        /// Which indication.
+        indication: IndicationType,
+    },
+    /// Data coding/message class group (1111).
+    DataCodingClass {
+        /// GSM 7-bit or 8-bit data.
+        character_set: CharacterSet,
+        /// Message class.
+        class: MessageClass,
+    },
+    /// Reserved coding groups (1000–1011), with the raw octet.
+    Reserved(u8),
+}
+
+impl DataCodingScheme {
+    /// GSM 7-bit default alphabet, no class (0x00).
+    pub const GSM7: DataCodingScheme = DataCodingScheme::general(CharacterSet::Gsm7);
+    /// 8-bit data, no class (0x04).
+    pub const EIGHT_BIT: DataCodingScheme = DataCodingScheme::general(CharacterSet::EightBit);
+    /// UCS-2, no class (0x08).
+    pub const UCS2: DataCodingScheme = DataCodingScheme::general(CharacterSet::Ucs2);
+
+    /// General data coding: uncompressed, no class, no automatic deletion.
+    pub const fn general(character_set: CharacterSet) -> Self {
+        DataCodingScheme::General {
+            compressed: false,
+            character_set,
+            class: None,
+            auto_delete: false,
+        }
+    }
+
+    /// The DCS announcing `encoding`. National language tables are announced
+    /// in the User Data Header, not in the DCS.
+    pub fn for_encoding(encoding: SmsEncoding) -> Self {
+        match encoding {
+            SmsEncoding::Gsm7 { .. } => Self::GSM7,
+            SmsEncoding::Ucs2 => Self::UCS2,
+        }
+    }
+
+    /// Decode a DCS octet.
+    pub fn from_octet(octet: u8) -> Self {
+        match octet >> 4 {
+            0x0..=0x7 => DataCodingScheme::General {
+                compressed: octet & 0x20 != 0,
+                character_set: match (octet >> 2) & 0b11 {
+                    0b01 => CharacterSet::EightBit,
+                    0b10 => CharacterSet::Ucs2,
+                    // 0b11 is reserved and read as the default alphabet
+                    _ => CharacterSet::Gsm7,
+                },
+                class: (octet & 0x10 != 0).then(|| MessageClass::from_bits(octet)),
+                auto_delete: octet & 0x40 != 0,
+            },
+            0x8..=0xB => DataCodingScheme::Reserved(octet),
+            group @ 0xC..=0xE => DataCodingScheme::MessageWaiting {
+                store: group != 0xC,
+                character_set: if group == 0xE {
+                    CharacterSet::Ucs2
+                } else {
+                    CharacterSet::Gsm7
+                },
+                active: octet & 0x08 != 0,
+                indication: IndicationType::from_bits(octet),
+            },
+            _ => DataCodingScheme::DataCodingClass {
+                character_set: if octet & 0x04 != 0 {
+                    CharacterSet::EightBit
+                } else {
+                    CharacterSet::Gsm7
+                },
+                class: MessageClass::from_bits(octet),
+            },
+        }
+    }
+
+    /// Encode as a DCS octet.
+    ///
+    /// Combinations the coding group cannot express fall back to the nearest
+    /// valid octet: a message waiting group with 8-bit data is written as
+    /// GSM 7-bit, a data coding/message class group with UCS-2 as 8-bit.
+    pub fn to_octet(self) -> u8 {
+        match self {
+            DataCodingScheme::General {
+                compressed,
+                character_set,
+                class,
+                auto_delete,
+            } => {
+                let mut octet = charset_bits(character_set) << 2;
+                if compressed {
+                    octet |= 0x20;
+                }
+                if let Some(class) = class {
+                    octet |= 0x10 | class as u8;
+                }
+                if auto_delete {
+                    octet |= 0x40;
+                }
+                octet
+            }
+            DataCodingScheme::MessageWaiting {
+                store,
+                character_set,
+                active,
+                indication,
+            } => {
+                let group = match (store, character_set) {
+                    (_, CharacterSet::Ucs2) => 0xE0,
+                    (true, _) => 0xD0,
+                    (false, _) => 0xC0,
+                };
+                group | u8::from(active) << 3 | indication as u8
+            }
+            DataCodingScheme::DataCodingClass {
+                character_set,
+                class,
+            } => {
+                let data = u8::from(character_set != CharacterSet::Gsm7) << 2;
+                0xF0 | data | class as u8
+            }
+            DataCodingScheme::Reserved(octet) => octet,
+        }
+    }
+
+    /// Coding of the user data, or `None` for reserved coding groups.
+    pub fn character_set(self) -> Option<CharacterSet> {
+        match self {
+            DataCodingScheme::General { character_set, .. }
+            | DataCodingScheme::MessageWaiting { character_set, .. }
+            | DataCodingScheme::DataCodingClass { character_set, .. } => Some(character_set),
+            DataCodingScheme::Reserved(_) => None,
+        }
+    }
+
+    /// The message class, if the DCS gives one.
+    pub fn message_class(self) -> Option<MessageClass> {
+        match self {
+            DataCodingScheme::General { class, .. } => class,
+            DataCodingScheme::DataCodingClass { class, .. } => Some(class),
+            DataCodingScheme::MessageWaiting { .. } | DataCodingScheme::Reserved(_) => None,
+        }
+    }
+
+    /// Whether the user data is compressed.
+    pub fn is_compressed(self) -> bool {
+        matches!(
+            self,
+            DataCodingScheme::General {
+                compressed: true,
+                ..
+            }
+        )
+    }
+
+    /// The text encoding to decode the user data with, or `None` for 8-bit
+    /// data, compressed text and reserved coding groups.
+    ///
+    /// GSM 7-bit maps to the default alphabet; check the User Data Header for
+    /// national language tables.
+    pub fn sms_encoding(self) -> Option<SmsEncoding> {
+        if self.is_compressed() {
+            return None;
+        }
+        match self.character_set()? {
+            CharacterSet::Gsm7 => Some(SmsEncoding::GSM7_DEFAULT),
+            CharacterSet::Ucs2 => Some(SmsEncoding::Ucs2),
+            CharacterSet::EightBit => None,
+        }
+    }
+}
+
+impl From<u8> for DataCodingScheme {
+    fn from(octet: u8) -> Self {
+        DataCodingScheme::from_octet(octet)
+    }
+}
+
+impl From<DataCodingScheme> for u8 {
+    fn from(dcs: DataCodingScheme) -> Self {
+        dcs.to_octet()
+    }
+}
+
+pub(crate) fn charset_bits(character_set: CharacterSet) -> u8 {
+    match character_set {
+        CharacterSet::Gsm7 => 0b00,
+        CharacterSet::EightBit => 0b01,
+        CharacterSet::Ucs2 => 0b10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_general_group() {
+        assert_eq!(DataCodingScheme::from_octet(0x00), DataCodingScheme::GSM7);
+        assert_eq!(
+            DataCodingScheme::from_octet(0x04),
+            DataCodingScheme::EIGHT_BIT
+        );
+        assert_eq!(DataCodingScheme::from_octet(0x08), DataCodingScheme::UCS2);
+        assert_eq!(
+            DataCodingScheme::from_octet(0x0C).character_set(),
+            Some(CharacterSet::Gsm7)
+        );
+
+        let dcs = DataCodingScheme::from_octet(0x76);
+        assert_eq!(
+            dcs,
+            DataCodingScheme::General {
+                compressed: true,
+                character_set: CharacterSet::EightBit,
+                class: Some(MessageClass::Class2),
+                auto_delete: true,
+            }
+        );
+        assert!(dcs.is_compressed());
+        assert_eq!(dcs.sms_encoding(), None);
+    }
+
+    #[test]
+    fn test_message_waiting_groups() {
+        assert_eq!(
+            DataCodingScheme::from_octet(0xC8),
+            DataCodingScheme::MessageWaiting {
+                store: false,
+                character_set: CharacterSet::Gsm7,
+                active: true,
+                indication: IndicationType::Voicemail,
+            }
+        );
+        let dcs = DataCodingScheme::from_octet(0xE2);
+        assert_eq!(dcs.character_set(), Some(CharacterSet::Ucs2));
+        assert_eq!(dcs.message_class(), None);
+        assert_eq!(dcs.sms_encoding(), Some(SmsEncoding::Ucs2));
+        assert_eq!(
+            DataCodingScheme::from_octet(0xD3),
+            DataCodingScheme::MessageWaiting {
+                store: true,
+                character_set: CharacterSet::Gsm7,
+                active: false,
+                indication: IndicationType::Other,
+            }
+        );
+    }
+
+    #[test]
+    fn test_data_coding_class_group() {
+        let dcs = DataCodingScheme::from_octet(0xF5);
+        assert_eq!(dcs.character_set(), Some(CharacterSet::EightBit));
+        assert_eq!(dcs.message_class(), Some(MessageClass::Class1));
+        assert_eq!(
+            DataCodingScheme::from_octet(0xF0).sms_encoding(),
+            Some(SmsEncoding::GSM7_DEFAULT)
+        );
+    }
+
+    #[test]
+    fn test_reserved_groups() {
+        let dcs = DataCodingScheme::from_octet(0x9A);
+        assert_eq!(dcs, DataCodingScheme::Reserved(0x9A));
+        assert_eq!(dcs.character_set(), None);
+        assert_eq!(dcs.to_octet(), 0x9A);
+    }
+
+    #[test]
+    fn test_octet_roundtrip() {
+        // Every octet without reserved bits set survives a round trip
+        let canonical = |octet: u8| match octet >> 4 {
+            0x0..=0x7 => (octet & 0x0C) != 0x0C && (octet & 0x10 != 0 || octet & 0x03 == 0),
+            0xC..=0xE => octet & 0x04 == 0,
+            0xF => octet & 0x08 == 0,
+            _ => true,
+        };
+        for octet in (0..=0xFF).filter(|&octet| canonical(octet)) {
+            assert_eq!(
+                DataCodingScheme::from(octet).to_octet(),
+                octet,
+                "0x{octet:02X}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_for_encoding() {
+        let turkish = SmsEncoding::Gsm7 {
+            locking_shift: Some(crate::NationalLanguage::Turkish),
+            single_shift: None,
+        };
+        assert_eq!(u8::from(DataCodingScheme::for_encoding(turkish)), 0x00);
+        assert_eq!(
+            u8::from(DataCodingScheme::for_encoding(SmsEncoding::Ucs2)),
+            0x08
+        );
+    }
+}
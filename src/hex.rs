@@ -0,0 +1,51 @@
+//! Hex string helpers for PDUs exchanged as text (AT commands, logs).
+
+use crate::{Gsm7Error, Result};
+
+/// Format bytes as an uppercase hex string without separators.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Parse a hex string (either case, no separators) into bytes.
+pub(crate) fn decode(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.trim();
+    if !hex.len().is_multiple_of(2) {
+        return Err(Gsm7Error::InvalidHex {
+            reason: format!("odd number of digits ({})", hex.len()),
+        });
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .filter(|pair| pair.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| Gsm7Error::InvalidHex {
+                    reason: format!("invalid digits at position {}", i),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        assert_eq!(encode(&[0x00, 0xAB, 0x7F]), "00AB7F");
+        assert_eq!(decode("00ab7F").unwrap(), [0x00, 0xAB, 0x7F]);
+        assert_eq!(encode(&[]), "");
+        assert!(decode("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(decode("ABC").is_err());
+        assert!(decode("0G").is_err());
+        assert!(decode("+1").is_err());
+        assert!(decode("é0").is_err());
+    }
+}
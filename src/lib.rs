@@ -19,6 +19,42 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use thiserror::Error;
 
+pub mod cbs;
+mod dcs;
+mod hex;
+mod national;
+mod network_name;
+mod packing;
+pub mod pdu;
+mod reader;
+mod segment;
+mod selection;
+mod sim;
+pub mod smpp;
+mod split;
+pub mod ucp;
+mod ucs2;
+mod udh;
+mod ussd;
+
+pub use dcs::{CharacterSet, DataCodingScheme, IndicationType, MessageClass};
+pub use national::NationalLanguage;
+pub use network_name::NetworkName;
+pub use packing::{
+    PackedSeptets, decode_packed, decode_packed_cr_padded, encode_packed, encode_packed_cr_padded,
+    fill_bits_for_udh, pack_septets, pack_septets_cr_padded, pack_septets_with_fill,
+    unpack_septets, unpack_septets_cr_padded, unpack_septets_with_fill,
+};
+pub use segment::{SegmentBoundary, SegmentInfo, segment_info, segment_info_with_encoding};
+pub use selection::{EncodingChoice, SmsEncoding, choose_encoding, choose_encoding_with_languages};
+pub use sim::{decode_alpha_identifier, encode_alpha_identifier};
+pub use split::{ConcatReference, SeptetFormat, SmsPart, SplitOptions, split_into_segments};
+pub use ucs2::{Ucs2Mode, decode_ucs2, decode_ucs2_with_mode, encode_ucs2, encode_ucs2_with_mode};
+pub use udh::{InformationElement, UserDataHeader};
+pub use ussd::{
+    MAX_USSD_OCTETS, USSD_DCS, decode_ussd, decode_ussd_lenient, encode_ussd, encode_ussd_with_dcs,
+};
+
 /// Errors that can occur during GSM 7-bit encoding/decoding operations.
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum Gsm7Error {
@@ -37,6 +73,93 @@ pub enum Gsm7Error {
     /// Input data is malformed.
     #[error("Malformed GSM 7-bit data: {reason}")]
     MalformedData { reason: String },
+
+    /// Character cannot be represented in strict UCS-2.
+    #[error("Character outside the UCS-2 range: '{character}' (U+{code:04X})")]
+    UnsupportedUcs2Character { character: char, code: u32 },
+
+    /// Unpaired or disallowed UTF-16 surrogate encountered during UCS-2 decoding.
+    #[error("Invalid UTF-16 code unit 0x{unit:04X} at byte offset {offset}")]
+    InvalidUtf16 { offset: usize, unit: u16 },
+
+    /// Text needs more parts than a concatenated message can have.
+    #[error("Message needs {count} segments, more than the maximum of 255")]
+    TooManySegments { count: usize },
+
+    /// Fill-bit count outside the range 0–6.
+    #[error("Invalid fill bit count: {fill_bits} (must be 0-6)")]
+    InvalidFillBits { fill_bits: u8 },
+
+    /// Address cannot be encoded in an address field.
+    #[error("Invalid address: {reason}")]
+    InvalidAddress { reason: String },
+
+    /// User data does not fit in one TPDU.
+    #[error("User data is {octets} octets, more than the maximum of 140")]
+    UserDataTooLong { octets: usize },
+
+    /// Hex string could not be parsed.
+    #[error("Invalid hex string: {reason}")]
+    InvalidHex { reason: String },
+
+    /// PDU ended before a field was complete.
+    #[error("PDU truncated in {field} at byte offset {offset}")]
+    TruncatedPdu { field: &'static str, offset: usize },
+
+    /// PDU field holds a value that cannot be interpreted.
+    #[error("Invalid {field} at byte offset {offset}: {reason}")]
+    InvalidPduField {
+        field: &'static str,
+        offset: usize,
+        reason: String,
+    },
+
+    /// Timestamp field is not valid BCD or out of range.
+    #[error("Invalid timestamp: {reason}")]
+    InvalidTimestamp { reason: String },
+
+    /// Concatenation IE names a part outside `1..=total`.
+    #[error("Invalid concatenated part {sequence} of {total}")]
+    InvalidConcatenation { sequence: u8, total: u8 },
+
+    /// Cell broadcast message cannot be split into or put together from pages.
+    #[error("Invalid cell broadcast message: {reason}")]
+    InvalidCbsMessage { reason: String },
+
+    /// Data coding scheme does not carry text this crate can decode.
+    #[error("Data coding scheme 0x{dcs:02X} does not carry supported text")]
+    UnsupportedDataCoding { dcs: u8 },
+
+    /// USSD string does not fit in 160 octets.
+    #[error("USSD string is {octets} octets, more than the maximum of 160")]
+    UssdTooLong { octets: usize },
+
+    /// Name does not fit in a SIM alpha identifier in any coding.
+    #[error("Alpha identifier needs {octets} octets, more than the {limit} available")]
+    AlphaIdentifierTooLong { octets: usize, limit: usize },
+
+    /// Character has no code in a single-octet character set.
+    #[error("Character not supported in {charset}: '{character}' (U+{code:04X})")]
+    UnsupportedCharsetCharacter {
+        character: char,
+        code: u32,
+        charset: &'static str,
+    },
+
+    /// SMPP field value cannot be encoded.
+    #[error("Invalid SMPP {field}: {reason}")]
+    InvalidSmppField { field: &'static str, reason: String },
+
+    /// UCP/EMI message or field is malformed or cannot be encoded.
+    #[error("Invalid UCP {field}: {reason}")]
+    InvalidUcpField { field: &'static str, reason: String },
+    /// Concatenated message is complete but its text cannot be decoded; the
+    /// parts are handed back in sequence order.
+    #[error("Concatenated message of {} parts cannot be decoded: {error}", parts.len())]
+    UndecodableMessage {
+        parts: Vec<pdu::SmsDeliver>,
+        error: Box<Gsm7Error>,
+    },
 }
 
 /// Result type for GSM 7-bit operations.
@@ -49,6 +172,10 @@ pub struct Gsm7Config {
     pub strict: bool,
     /// Replacement character for unsupported characters in non-strict mode.
     pub replacement_char: char,
+    /// National language locking-shift table replacing the default alphabet, if any.
+    pub locking_shift: Option<NationalLanguage>,
+    /// National language single-shift table replacing the extension table, if any.
+    pub single_shift: Option<NationalLanguage>,
 }
 
 impl Default for Gsm7Config {
@@ -56,6 +183,8 @@ impl Default for Gsm7Config {
         Self {
             strict: false,
             replacement_char: '�',
+            locking_shift: None,
+            single_shift: None,
         }
     }
 }
@@ -65,7 +194,34 @@ impl Gsm7Config {
     pub fn strict() -> Self {
         Self {
             strict: true,
-            replacement_char: '�',
+            ..Self::default()
+        }
+    }
+
+    /// Create a config using the given national language shift tables.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use gsm7_alt::{Gsm7Config, NationalLanguage, decode_with_config, encode_with_config};
+    ///
+    /// let config = Gsm7Config::national(
+    ///     Some(NationalLanguage::Turkish),
+    ///     Some(NationalLanguage::Turkish),
+    /// );
+    /// let encoded = encode_with_config("Günaydın İstanbul", &config)?;
+    /// assert_eq!(encoded.len(), 17);
+    /// assert_eq!(decode_with_config(&encoded, &config)?, "Günaydın İstanbul");
+    /// # Ok::<(), gsm7_alt::Gsm7Error>(())
+    /// ```
+    pub fn national(
+        locking_shift: Option<NationalLanguage>,
+        single_shift: Option<NationalLanguage>,
+    ) -> Self {
+        Self {
+            locking_shift,
+            single_shift,
+            ..Self::default()
         }
     }
 }
@@ -79,30 +235,118 @@ enum Code {
     Escape(u8),
 }
 
-/// GSM 7-bit character mappings (lazy-initialized static data).
-static GSM_MAPS: Lazy<(HashMap<char, Code>, [Option<char>; 128], HashMap<u8, char>)> =
-    Lazy::new(|| {
-        let gsm_to_char = build_gsm_table();
-        let gsm_ext = build_gsm_ext_table();
+/// Lookup tables for a locking-shift table (the single-byte codes).
+struct LockingShift {
+    char_to_gsm: HashMap<char, u8>,
+    gsm_array: [Option<char>; 128],
+}
 
-        let mut char_to_gsm = HashMap::new();
+impl LockingShift {
+    fn new(gsm_to_char: HashMap<u8, Option<char>>) -> Self {
         let mut gsm_array = [None; 128];
+        for (code, ch) in gsm_to_char {
+            gsm_array[code as usize] = ch;
+        }
 
-        // Build character to GSM mapping and array for fast lookup
-        for (&code, &ch) in &gsm_to_char {
+        // Iterate in code order so a character listed twice maps to its lowest code
+        let mut char_to_gsm = HashMap::new();
+        for (code, ch) in gsm_array.iter().enumerate() {
             if let Some(character) = ch {
-                char_to_gsm.insert(character, Code::Single(code));
-                gsm_array[code as usize] = Some(character);
+                char_to_gsm.entry(*character).or_insert(code as u8);
             }
         }
 
-        // Add extension characters
-        for (&code, &ch) in &gsm_ext {
-            char_to_gsm.insert(ch, Code::Escape(code));
+        Self {
+            char_to_gsm,
+            gsm_array,
         }
+    }
+}
+
+/// Lookup tables for a single-shift table (the codes following 0x1B).
+struct SingleShift {
+    char_to_gsm: HashMap<char, u8>,
+    gsm_ext: HashMap<u8, char>,
+}
+
+impl SingleShift {
+    fn new(gsm_ext: HashMap<u8, char>) -> Self {
+        let mut codes: Vec<_> = gsm_ext.iter().map(|(&code, &ch)| (code, ch)).collect();
+        codes.sort_unstable();
+
+        let mut char_to_gsm = HashMap::new();
+        for (code, ch) in codes {
+            char_to_gsm.entry(ch).or_insert(code);
+        }
+
+        Self {
+            char_to_gsm,
+            gsm_ext,
+        }
+    }
+}
+
+/// Default alphabet and extension table (lazy-initialized static data).
+static GSM_MAPS: Lazy<(LockingShift, SingleShift)> = Lazy::new(|| {
+    (
+        LockingShift::new(build_gsm_table()),
+        SingleShift::new(build_gsm_ext_table()),
+    )
+});
+
+/// National language locking-shift tables (lazy-initialized static data).
+static NATIONAL_LOCKING: Lazy<HashMap<NationalLanguage, LockingShift>> = Lazy::new(|| {
+    NationalLanguage::ALL
+        .into_iter()
+        .filter_map(|language| {
+            national::build_locking_shift_table(language)
+                .map(|table| (language, LockingShift::new(table)))
+        })
+        .collect()
+});
+
+/// National language single-shift tables (lazy-initialized static data).
+static NATIONAL_SINGLE: Lazy<HashMap<NationalLanguage, SingleShift>> = Lazy::new(|| {
+    NationalLanguage::ALL
+        .into_iter()
+        .map(|language| {
+            let table = national::build_single_shift_table(language);
+            (language, SingleShift::new(table))
+        })
+        .collect()
+});
+
+/// The pair of tables selected by a [`Gsm7Config`].
+#[derive(Clone, Copy)]
+struct Alphabet {
+    locking: &'static LockingShift,
+    single: &'static SingleShift,
+}
+
+impl Alphabet {
+    fn for_config(config: &Gsm7Config) -> Self {
+        let (default_locking, default_single) = &*GSM_MAPS;
+        Self {
+            locking: config
+                .locking_shift
+                .and_then(|language| NATIONAL_LOCKING.get(&language))
+                .unwrap_or(default_locking),
+            single: config
+                .single_shift
+                .and_then(|language| NATIONAL_SINGLE.get(&language))
+                .unwrap_or(default_single),
+        }
+    }
 
-        (char_to_gsm, gsm_array, gsm_ext)
-    });
+    /// Look up a character, preferring a single-byte code over an escape sequence.
+    fn code(&self, ch: char) -> Option<Code> {
+        if let Some(&b) = self.locking.char_to_gsm.get(&ch) {
+            Some(Code::Single(b))
+        } else {
+            self.single.char_to_gsm.get(&ch).map(|&b| Code::Escape(b))
+        }
+    }
+}
 
 /// Encode a string using GSM 7-bit encoding.
 ///
@@ -143,15 +387,15 @@ pub fn encode(content: &str) -> Result<Vec<u8>> {
 ///
 /// A `Vec<u8>` containing the GSM 7-bit encoded bytes.
 pub fn encode_with_config(content: &str, config: &Gsm7Config) -> Result<Vec<u8>> {
-    let (char_to_gsm, _, _) = &*GSM_MAPS;
+    let alphabet = Alphabet::for_config(config);
     let mut bytes = Vec::with_capacity(content.len());
 
     for ch in content.chars() {
-        match char_to_gsm.get(&ch) {
-            Some(Code::Single(b)) => bytes.push(*b),
+        match alphabet.code(ch) {
+            Some(Code::Single(b)) => bytes.push(b),
             Some(Code::Escape(b)) => {
                 bytes.push(0x1B);
-                bytes.push(*b);
+                bytes.push(b);
             }
             None => {
                 if config.strict {
@@ -161,8 +405,8 @@ pub fn encode_with_config(content: &str, config: &Gsm7Config) -> Result<Vec<u8>>
                     });
                 } else {
                     // Use replacement character
-                    if let Some(Code::Single(b)) = char_to_gsm.get(&config.replacement_char) {
-                        bytes.push(*b);
+                    if let Some(Code::Single(b)) = alphabet.code(config.replacement_char) {
+                        bytes.push(b);
                     } else {
                         bytes.push(0x20); // space as fallback
                     }
@@ -220,7 +464,9 @@ pub fn decode(data: &[u8]) -> Result<String> {
 ///
 /// A `String` containing the decoded text.
 pub fn decode_with_config(data: &[u8], config: &Gsm7Config) -> Result<String> {
-    let (_, gsm_array, gsm_ext) = &*GSM_MAPS;
+    let alphabet = Alphabet::for_config(config);
+    let gsm_array = &alphabet.locking.gsm_array;
+    let gsm_ext = &alphabet.single.gsm_ext;
     let mut result = String::with_capacity(data.len());
 
     let mut i = 0;
@@ -292,11 +538,29 @@ pub fn decode_with_config(data: &[u8], config: &Gsm7Config) -> Result<String> {
 /// The number of bytes required, or an error if the string contains
 /// unsupported characters.
 pub fn encoded_len(content: &str) -> Result<usize> {
-    let (char_to_gsm, _, _) = &*GSM_MAPS;
+    encoded_len_with_config(content, &Gsm7Config::default())
+}
+
+/// Calculate the number of bytes required to encode a string in GSM 7-bit
+/// using the shift tables selected by `config`.
+///
+/// Unsupported characters are always reported as errors, regardless of `config.strict`.
+///
+/// # Arguments
+///
+/// * `content` - The string to measure
+/// * `config` - Configuration options
+///
+/// # Returns
+///
+/// The number of bytes required, or an error if the string contains
+/// characters missing from the selected tables.
+pub fn encoded_len_with_config(content: &str, config: &Gsm7Config) -> Result<usize> {
+    let alphabet = Alphabet::for_config(config);
     let mut len = 0;
 
     for ch in content.chars() {
-        match char_to_gsm.get(&ch) {
+        match alphabet.code(ch) {
             Some(Code::Single(_)) => len += 1,
             Some(Code::Escape(_)) => len += 2,
             None => {
@@ -549,6 +813,7 @@ mod tests {
         let config = Gsm7Config {
             strict: false,
             replacement_char: '?',
+            ..Default::default()
         };
 
         let encoded = encode_with_config("Hello 🦀 World", &config).unwrap();
@@ -556,6 +821,58 @@ mod tests {
         assert_eq!(decoded, "Hello ? World");
     }
 
+    #[test]
+    fn test_national_locking_and_single_shift() {
+        let config =
+            Gsm7Config::national(Some(NationalLanguage::Hindi), Some(NationalLanguage::Hindi));
+        let text = "नमस्ते ABC १२३";
+        let encoded = encode_with_config(text, &config).unwrap();
+        // Devanagari letters come from the locking table, A-Z and digits from the single shift
+        assert_eq!(
+            encoded_len_with_config(text, &config).unwrap(),
+            encoded.len()
+        );
+        assert_eq!(encoded.len(), 6 + 1 + 3 * 2 + 1 + 3 * 2);
+        assert_eq!(decode_with_config(&encoded, &config).unwrap(), text);
+
+        assert!(encoded_len(text).is_err());
+    }
+
+    #[test]
+    fn test_national_single_shift_with_default_alphabet() {
+        let config = Gsm7Config::national(None, Some(NationalLanguage::Spanish));
+        let text = "Canción ¿qué? {ok}";
+        let encoded = encode_with_config(text, &config).unwrap();
+        assert_eq!(encoded[5], 0x1B);
+        assert_eq!(encoded[6], 0x6F); // ó
+        assert_eq!(decode_with_config(&encoded, &config).unwrap(), text);
+    }
+
+    #[test]
+    fn test_spanish_locking_shift_uses_default_alphabet() {
+        let config = Gsm7Config::national(Some(NationalLanguage::Spanish), None);
+        let text = "Hola España";
+        assert_eq!(
+            encode_with_config(text, &config).unwrap(),
+            encode(text).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_national_strict_mode_rejects_missing_characters() {
+        let config = Gsm7Config {
+            strict: true,
+            ..Gsm7Config::national(Some(NationalLanguage::Turkish), None)
+        };
+        assert_eq!(encode_with_config("Å", &config), Ok(vec![0x0E]));
+        assert!(matches!(
+            encode_with_config("Ø", &config),
+            Err(Gsm7Error::UnsupportedCharacter {
+                character: 'Ø', ..
+            })
+        ));
+    }
+
     #[test]
     fn test_invalid_byte_replaced() {
         // Test byte 0x81 (outside valid range)
@@ -18,6 +18,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = Gsm7Config {
         strict: false,
         replacement_char: '?',
+        ..Default::default()
     };
 
     let encoded_with_replacement = gsm7_alt::encode_with_config(emoji_text, &config)?;
@@ -0,0 +1,257 @@
+//! Network name IE (3GPP TS 24.008 §10.5.3.5a).
+//!
+//! MM and GMM INFORMATION messages (NITZ) and EF_PNN on the SIM carry the
+//! full and short operator names in this format: a coding octet with the
+//! coding scheme, the add-CI flag and the number of spare bits in the last
+//! octet, followed by packed GSM 7-bit or UCS-2 text.
+
+use crate::{
+    CharacterSet, Gsm7Config, Gsm7Error, Result, decode_ucs2, decode_with_config, encode_ucs2,
+    encode_with_config, pack_septets, unpack_septets, unpack_septets_cr_padded,
+};
+
+/// Coding scheme bits for the GSM 7-bit default alphabet.
+const CODING_GSM7: u8 = 0b000;
+/// Coding scheme bits for UCS-2.
+const CODING_UCS2: u8 = 0b001;
+
+/// A full or short network name.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::NetworkName;
+///
+/// let ie = [0x43, 0x0B, 0x83, 0xD4, 0x56, 0xF3, 0x2D, 0x4E, 0xB3, 0xCB, 0xA0, 0xEA, 0x12];
+/// let name = NetworkName::from_ie(&ie)?;
+/// assert_eq!(name.text, "T-Mobile UK");
+/// assert!(!name.add_country_initials);
+/// assert_eq!(name.to_ie(NetworkName::FULL_NAME_IEI)?, ie);
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkName {
+    /// The operator name.
+    pub text: String,
+    /// GSM 7-bit or UCS-2.
+    pub character_set: CharacterSet,
+    /// The handset should put the country's initials and a separator in front
+    /// of the name.
+    pub add_country_initials: bool,
+}
+
+impl NetworkName {
+    /// IEI of the full name for network in MM/GMM INFORMATION, and the EF_PNN tag.
+    pub const FULL_NAME_IEI: u8 = 0x43;
+    /// IEI of the short name for network in MM/GMM INFORMATION, and the EF_PNN tag.
+    pub const SHORT_NAME_IEI: u8 = 0x45;
+
+    /// A name in the GSM 7-bit default alphabet if it fits, UCS-2 otherwise.
+    pub fn new(text: &str) -> Self {
+        let character_set = if encode_with_config(text, &Gsm7Config::strict()).is_ok() {
+            CharacterSet::Gsm7
+        } else {
+            CharacterSet::Ucs2
+        };
+        Self {
+            text: text.to_string(),
+            character_set,
+            add_country_initials: false,
+        }
+    }
+
+    /// Parse the value of the IE: the coding octet and the text.
+    ///
+    /// A spare bit count of 0 gives no information; the text then runs to
+    /// the end of the last full septet, with `<CR>` padding removed. A name
+    /// of a multiple of eight septets that really ends in `<CR>` is encoded
+    /// with a count of 0 too, so that `<CR>` is lost.
+    /// Unknown GSM 7-bit codes become the replacement character.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The IE contents after the length octet
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::TruncatedPdu` if `value` is empty,
+    /// `Gsm7Error::InvalidPduField` for a reserved coding scheme or a spare
+    /// bit count larger than the text, and `Gsm7Error::InvalidUtf16` for
+    /// malformed UCS-2 text.
+    pub fn from_bytes(value: &[u8]) -> Result<Self> {
+        let (&coding, text) = value.split_first().ok_or(Gsm7Error::TruncatedPdu {
+            field: "network name coding",
+            offset: 0,
+        })?;
+        let invalid = |reason: String| Gsm7Error::InvalidPduField {
+            field: "network name coding",
+            offset: 0,
+            reason,
+        };
+        let spare_bits = usize::from(coding & 0b111);
+
+        let (character_set, text) = match (coding >> 4) & 0b111 {
+            CODING_GSM7 => {
+                let septets = if spare_bits == 0 {
+                    unpack_septets_cr_padded(text)
+                } else {
+                    let bits = (text.len() * 8)
+                        .checked_sub(spare_bits)
+                        .ok_or_else(|| invalid(format!("{spare_bits} spare bits without text")))?;
+                    unpack_septets(text, bits / 7)
+                };
+                (
+                    CharacterSet::Gsm7,
+                    decode_with_config(&septets, &Gsm7Config::default())?,
+                )
+            }
+            CODING_UCS2 => (CharacterSet::Ucs2, decode_ucs2(text)?),
+            scheme => return Err(invalid(format!("reserved coding scheme 0b{scheme:03b}"))),
+        };
+
+        Ok(Self {
+            text,
+            character_set,
+            add_country_initials: coding & 0x08 != 0,
+        })
+    }
+
+    /// Parse a whole IE: IEI, length and value.
+    ///
+    /// The IEI is not checked, so both names and EF_PNN records parse.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::TruncatedPdu` if the IE is shorter than its length
+    /// octet says, `Gsm7Error::InvalidPduField` if it is longer, and otherwise
+    /// the errors of [`from_bytes`](Self::from_bytes).
+    pub fn from_ie(ie: &[u8]) -> Result<Self> {
+        let length = usize::from(*ie.get(1).ok_or(Gsm7Error::TruncatedPdu {
+            field: "network name length",
+            offset: ie.len(),
+        })?);
+        let value = ie.get(2..2 + length).ok_or(Gsm7Error::TruncatedPdu {
+            field: "network name",
+            offset: ie.len(),
+        })?;
+        if ie.len() > 2 + length {
+            return Err(Gsm7Error::InvalidPduField {
+                field: "network name",
+                offset: 2 + length,
+                reason: format!("{} trailing octets", ie.len() - 2 - length),
+            });
+        }
+        Self::from_bytes(value)
+    }
+
+    /// Serialize the value of the IE: the coding octet and the text.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::UnsupportedCharacter` if the text cannot be encoded
+    /// in its character set, and `Gsm7Error::UnsupportedDataCoding` if the
+    /// character set is 8-bit.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let flags = 0x80 | u8::from(self.add_country_initials) << 3;
+        let mut bytes = Vec::new();
+        match self.character_set {
+            CharacterSet::Gsm7 => {
+                let septets = encode_with_config(&self.text, &Gsm7Config::strict())?;
+                let spare_bits = ((8 - (septets.len() * 7) % 8) % 8) as u8;
+                bytes.push(flags | CODING_GSM7 << 4 | spare_bits);
+                bytes.extend(pack_septets(&septets));
+            }
+            CharacterSet::Ucs2 => {
+                bytes.push(flags | CODING_UCS2 << 4);
+                bytes.extend(encode_ucs2(&self.text)?);
+            }
+            CharacterSet::EightBit => {
+                return Err(Gsm7Error::UnsupportedDataCoding { dcs: 0x04 });
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Serialize a whole IE: `iei`, length and value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::MalformedData` if the value is longer than 255
+    /// octets, and otherwise the errors of [`to_bytes`](Self::to_bytes).
+    pub fn to_ie(&self, iei: u8) -> Result<Vec<u8>> {
+        let value = self.to_bytes()?;
+        let length = u8::try_from(value.len()).map_err(|_| Gsm7Error::MalformedData {
+            reason: format!("network name IE value is {} octets", value.len()),
+        })?;
+        let mut ie = vec![iei, length];
+        ie.extend(value);
+        Ok(ie)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spare_bits() {
+        // 8 septets fill 7 octets exactly
+        let name = NetworkName::new("Vodafone");
+        let bytes = name.to_bytes().unwrap();
+        assert_eq!(bytes[0], 0x80);
+        assert_eq!(bytes[1..], [0xD6, 0x37, 0x39, 0x6C, 0x7E, 0xBB, 0xCB]);
+        assert_eq!(NetworkName::from_bytes(&bytes).unwrap(), name);
+
+        // 7 septets leave 7 spare bits, which must not read as '@'
+        let name = NetworkName::new("Telekom");
+        let bytes = name.to_bytes().unwrap();
+        assert_eq!(bytes[0], 0x87);
+        assert_eq!(bytes.len(), 8);
+        assert_eq!(NetworkName::from_bytes(&bytes).unwrap().text, "Telekom");
+    }
+
+    #[test]
+    fn test_unspecified_spare_bits() {
+        let mut bytes = NetworkName::new("Orange").to_bytes().unwrap();
+        bytes[0] &= !0b111;
+        assert_eq!(NetworkName::from_bytes(&bytes).unwrap().text, "Orange");
+
+        // Eight septets ending in <CR> cannot be told from seven and padding
+        let bytes = NetworkName::new("Network\r").to_bytes().unwrap();
+        assert_eq!(bytes[0] & 0b111, 0);
+        assert_eq!(NetworkName::from_bytes(&bytes).unwrap().text, "Network");
+    }
+
+    #[test]
+    fn test_ucs2_and_add_ci() {
+        let mut name = NetworkName::new("МТС");
+        assert_eq!(name.character_set, CharacterSet::Ucs2);
+        name.add_country_initials = true;
+        let ie = name.to_ie(NetworkName::SHORT_NAME_IEI).unwrap();
+        assert_eq!(ie[..3], [0x45, 0x07, 0x98]);
+        assert_eq!(NetworkName::from_ie(&ie).unwrap(), name);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            NetworkName::from_ie(&[0x43, 0x03, 0x80, 0x41]),
+            Err(Gsm7Error::TruncatedPdu {
+                field: "network name",
+                offset: 4,
+            })
+        );
+        assert!(matches!(
+            NetworkName::from_bytes(&[0xA0, 0x41]),
+            Err(Gsm7Error::InvalidPduField { .. })
+        ));
+        assert!(matches!(
+            NetworkName::from_bytes(&[0x87]),
+            Err(Gsm7Error::InvalidPduField { .. })
+        ));
+        assert!(matches!(
+            NetworkName::from_bytes(&[]),
+            Err(Gsm7Error::TruncatedPdu { .. })
+        ));
+    }
+}
@@ -0,0 +1,513 @@
+//! Septet packing and unpacking.
+//!
+//! GSM 7-bit codes produced by [`encode`](crate::encode) occupy one byte each, with
+//! the top bit unused. SMS user data, USSD strings and cell broadcast pages carry
+//! them packed, eight septets to every seven octets, least significant bit first
+//! (3GPP TS 23.038 §6.1.2.1).
+
+use crate::{Gsm7Config, Gsm7Error, Result, decode_with_config, encode_with_config};
+
+/// GSM 7-bit carriage return, used as padding for the trailing spare bits.
+const CR: u8 = 0x0D;
+
+/// GSM 7-bit text packed into octets, together with the number of septets it holds.
+///
+/// The septet count is needed to unpack the data again, because a packed stream
+/// whose length is a multiple of seven octets may end either with seven spare
+/// bits or with a final septet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedSeptets {
+    /// The packed octets.
+    pub bytes: Vec<u8>,
+    /// Number of septets stored in `bytes`.
+    pub septet_count: usize,
+}
+
+/// Pack 7-bit codes into octets.
+///
+/// # Arguments
+///
+/// * `septets` - Unpacked GSM 7-bit codes, one per byte. The top bit of each byte is ignored.
+///
+/// # Returns
+///
+/// A `Vec<u8>` of `ceil(septets.len() * 7 / 8)` packed octets. Spare bits in the
+/// last octet are set to zero.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::pack_septets;
+///
+/// // "hellohello" in the GSM default alphabet
+/// let septets = b"hellohello";
+/// assert_eq!(
+///     pack_septets(septets),
+///     vec![0xE8, 0x32, 0x9B, 0xFD, 0x46, 0x97, 0xD9, 0xEC, 0x37]
+/// );
+/// ```
+pub fn pack_septets(septets: &[u8]) -> Vec<u8> {
+    pack_at(septets, 0)
+}
+
+/// Unpack octets into 7-bit codes.
+///
+/// # Arguments
+///
+/// * `data` - Packed octets
+/// * `septet_count` - Number of septets to extract
+///
+/// # Returns
+///
+/// A `Vec<u8>` of unpacked GSM 7-bit codes. If `data` is too short to hold
+/// `septet_count` septets, only the septets that are fully present are returned.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::unpack_septets;
+///
+/// let packed = [0xE8, 0x32, 0x9B, 0xFD, 0x46, 0x97, 0xD9, 0xEC, 0x37];
+/// assert_eq!(unpack_septets(&packed, 10), b"hellohello".to_vec());
+/// ```
+pub fn unpack_septets(data: &[u8], septet_count: usize) -> Vec<u8> {
+    unpack_at(data, septet_count, 0)
+}
+
+/// Pack 7-bit codes into octets after a number of leading fill bits.
+///
+/// When a User Data Header precedes GSM 7-bit text, the text must start on a
+/// septet boundary counted from the start of the user data. The 0–6 zero bits
+/// between the end of the header and the first septet are the fill bits; see
+/// [`fill_bits_for_udh`].
+///
+/// # Arguments
+///
+/// * `septets` - Unpacked GSM 7-bit codes, one per byte
+/// * `fill_bits` - Number of zero bits to place before the first septet (0–6)
+///
+/// # Returns
+///
+/// The packed octets, starting with the octet that holds the fill bits.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::InvalidFillBits` if `fill_bits` is greater than 6.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{fill_bits_for_udh, pack_septets_with_fill, unpack_septets_with_fill};
+///
+/// // 8-bit reference concatenation header: UDHL + 5 octets
+/// let udh = [0x05, 0x00, 0x03, 0x2A, 0x02, 0x01];
+/// let fill = fill_bits_for_udh(udh.len());
+/// assert_eq!(fill, 1);
+///
+/// let packed = pack_septets_with_fill(b"hello", fill)?;
+/// let mut user_data = udh.to_vec();
+/// user_data.extend_from_slice(&packed);
+///
+/// let septets = unpack_septets_with_fill(&user_data[udh.len()..], 5, fill)?;
+/// assert_eq!(septets, b"hello");
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+pub fn pack_septets_with_fill(septets: &[u8], fill_bits: u8) -> Result<Vec<u8>> {
+    check_fill_bits(fill_bits)?;
+    Ok(pack_at(septets, usize::from(fill_bits)))
+}
+
+/// Unpack octets into 7-bit codes, skipping a number of leading fill bits.
+///
+/// This is the inverse of [`pack_septets_with_fill`].
+///
+/// # Arguments
+///
+/// * `data` - Packed octets, starting with the octet that holds the fill bits
+/// * `septet_count` - Number of septets to extract
+/// * `fill_bits` - Number of bits to skip before the first septet (0–6)
+///
+/// # Returns
+///
+/// A `Vec<u8>` of unpacked GSM 7-bit codes, clamped to the septets fully present in `data`.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::InvalidFillBits` if `fill_bits` is greater than 6.
+pub fn unpack_septets_with_fill(
+    data: &[u8],
+    septet_count: usize,
+    fill_bits: u8,
+) -> Result<Vec<u8>> {
+    check_fill_bits(fill_bits)?;
+    Ok(unpack_at(data, septet_count, usize::from(fill_bits)))
+}
+
+/// Pack 7-bit codes into octets, applying the `<CR>` padding rule.
+///
+/// When the number of septets is `8n - 1`, the last octet would end with seven
+/// zero bits that a receiver cannot tell apart from a trailing `@` (0x00). In
+/// that case a `<CR>` (0x0D) is packed into the spare bits instead. If the text
+/// already ends with `<CR>` on an octet boundary, a second `<CR>` is added so the
+/// receiver does not strip the wanted one (3GPP TS 23.038 §6.1.2.3.1).
+///
+/// # Arguments
+///
+/// * `septets` - Unpacked GSM 7-bit codes, one per byte
+///
+/// # Returns
+///
+/// The packed octets. The septet count is implied by their length; use
+/// [`unpack_septets_cr_padded`] to unpack them.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{pack_septets, pack_septets_cr_padded, unpack_septets_cr_padded};
+///
+/// // Seven septets leave seven spare bits: they carry <CR> instead of zeros
+/// let packed = pack_septets_cr_padded(b"1234567");
+/// assert_eq!(packed, pack_septets(b"1234567\r"));
+/// assert_eq!(unpack_septets_cr_padded(&packed), b"1234567");
+/// ```
+pub fn pack_septets_cr_padded(septets: &[u8]) -> Vec<u8> {
+    let needs_padding = match septets.len() % 8 {
+        7 => true,
+        0 => septets.last() == Some(&CR),
+        _ => false,
+    };
+
+    if needs_padding {
+        let mut padded = Vec::with_capacity(septets.len() + 1);
+        padded.extend_from_slice(septets);
+        padded.push(CR);
+        pack_at(&padded, 0)
+    } else {
+        pack_at(septets, 0)
+    }
+}
+
+/// Unpack octets whose septet count is implied by their length, stripping `<CR>` padding.
+///
+/// Every complete septet in `data` is extracted. When `data` ends exactly on a
+/// septet boundary (its length is a multiple of seven octets) and the final
+/// septet is `<CR>`, that `<CR>` is padding and is removed.
+///
+/// # Arguments
+///
+/// * `data` - Octets produced by [`pack_septets_cr_padded`] or an equivalent sender
+///
+/// # Returns
+///
+/// A `Vec<u8>` of unpacked GSM 7-bit codes without the padding `<CR>`.
+pub fn unpack_septets_cr_padded(data: &[u8]) -> Vec<u8> {
+    let mut septets = unpack_at(data, usize::MAX, 0);
+    if data.len().is_multiple_of(7) && septets.last() == Some(&CR) {
+        septets.pop();
+    }
+    septets
+}
+
+/// Number of fill bits needed after a User Data Header so that GSM 7-bit text
+/// starts on a septet boundary.
+///
+/// # Arguments
+///
+/// * `udh_len` - Total header length in octets, including the UDHL octet itself
+///
+/// # Returns
+///
+/// A value in the range 0–6. A zero-length header needs no fill bits.
+pub fn fill_bits_for_udh(udh_len: usize) -> u8 {
+    ((7 - (udh_len * 8) % 7) % 7) as u8
+}
+
+fn check_fill_bits(fill_bits: u8) -> Result<()> {
+    if fill_bits > 6 {
+        return Err(Gsm7Error::InvalidFillBits { fill_bits });
+    }
+    Ok(())
+}
+
+/// Pack septets starting at `bit_offset` bits into the output.
+fn pack_at(septets: &[u8], bit_offset: usize) -> Vec<u8> {
+    let mut packed = vec![0u8; (bit_offset + septets.len() * 7).div_ceil(8)];
+
+    for (i, &septet) in septets.iter().enumerate() {
+        let bit = bit_offset + i * 7;
+        let byte = bit / 8;
+        let shift = bit % 8;
+        let value = u16::from(septet & 0x7F) << shift;
+
+        packed[byte] |= value as u8;
+        if shift > 1 {
+            packed[byte + 1] |= (value >> 8) as u8;
+        }
+    }
+
+    packed
+}
+
+/// Unpack up to `septet_count` septets starting `bit_offset` bits into `data`.
+fn unpack_at(data: &[u8], septet_count: usize, bit_offset: usize) -> Vec<u8> {
+    let available = (data.len() * 8).saturating_sub(bit_offset) / 7;
+    let count = septet_count.min(available);
+    let mut septets = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let bit = bit_offset + i * 7;
+        let byte = bit / 8;
+        let shift = bit % 8;
+
+        let mut value = u16::from(data[byte]);
+        if shift > 1 {
+            value |= u16::from(data[byte + 1]) << 8;
+        }
+        septets.push(((value >> shift) & 0x7F) as u8);
+    }
+
+    septets
+}
+
+/// Encode a string to packed GSM 7-bit with custom configuration.
+///
+/// This is [`encode_with_config`] followed by [`pack_septets`].
+///
+/// # Arguments
+///
+/// * `content` - The string to encode
+/// * `config` - Configuration options
+///
+/// # Returns
+///
+/// The packed octets and the number of septets they contain. Extension
+/// characters count as two septets.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{Gsm7Config, decode_packed, encode_packed};
+///
+/// let config = Gsm7Config::default();
+/// let packed = encode_packed("Hello {world} €!", &config)?;
+/// assert_eq!(packed.septet_count, 19);
+/// assert_eq!(packed.bytes.len(), 17);
+///
+/// let decoded = decode_packed(&packed.bytes, packed.septet_count, &config)?;
+/// assert_eq!(decoded, "Hello {world} €!");
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+pub fn encode_packed(content: &str, config: &Gsm7Config) -> Result<PackedSeptets> {
+    let septets = encode_with_config(content, config)?;
+    Ok(PackedSeptets {
+        bytes: pack_septets(&septets),
+        septet_count: septets.len(),
+    })
+}
+
+/// Decode packed GSM 7-bit octets to a string with custom configuration.
+///
+/// This is [`unpack_septets`] followed by [`decode_with_config`].
+///
+/// # Arguments
+///
+/// * `data` - Packed octets
+/// * `septet_count` - Number of septets stored in `data`
+/// * `config` - Configuration options
+///
+/// # Returns
+///
+/// A `String` containing the decoded text.
+pub fn decode_packed(data: &[u8], septet_count: usize, config: &Gsm7Config) -> Result<String> {
+    decode_with_config(&unpack_septets(data, septet_count), config)
+}
+
+/// Encode a string to packed GSM 7-bit, applying the `<CR>` padding rule.
+///
+/// This is [`encode_with_config`] followed by [`pack_septets_cr_padded`]. It is
+/// intended for USSD and cell broadcast, where no septet count travels with the data.
+///
+/// # Arguments
+///
+/// * `content` - The string to encode
+/// * `config` - Configuration options
+///
+/// # Returns
+///
+/// The packed octets.
+pub fn encode_packed_cr_padded(content: &str, config: &Gsm7Config) -> Result<Vec<u8>> {
+    let septets = encode_with_config(content, config)?;
+    Ok(pack_septets_cr_padded(&septets))
+}
+
+/// Decode packed GSM 7-bit octets with `<CR>` padding to a string.
+///
+/// This is [`unpack_septets_cr_padded`] followed by [`decode_with_config`], so a
+/// padded message does not gain a stray `@` or `<CR>` at the end.
+///
+/// # Arguments
+///
+/// * `data` - Packed octets
+/// * `config` - Configuration options
+///
+/// # Returns
+///
+/// A `String` containing the decoded text.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{Gsm7Config, decode_packed_cr_padded, encode_packed_cr_padded};
+///
+/// let config = Gsm7Config::default();
+/// let packed = encode_packed_cr_padded("*100#12", &config)?;
+/// assert_eq!(packed.len(), 7);
+/// assert_eq!(decode_packed_cr_padded(&packed, &config)?, "*100#12");
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+pub fn decode_packed_cr_padded(data: &[u8], config: &Gsm7Config) -> Result<String> {
+    decode_with_config(&unpack_septets_cr_padded(data), config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_known_vector() {
+        // "hellohello" from 3GPP TS 23.038 worked examples
+        let packed = pack_septets(b"hellohello");
+        assert_eq!(
+            packed,
+            [0xE8, 0x32, 0x9B, 0xFD, 0x46, 0x97, 0xD9, 0xEC, 0x37]
+        );
+        assert_eq!(unpack_septets(&packed, 10), b"hellohello");
+    }
+
+    #[test]
+    fn test_pack_unpack_all_lengths() {
+        let source: Vec<u8> = (0..=0x7F).collect();
+        for len in 0..source.len() {
+            let packed = pack_septets(&source[..len]);
+            assert_eq!(packed.len(), (len * 7).div_ceil(8));
+            assert_eq!(
+                unpack_septets(&packed, len),
+                &source[..len],
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_pack_ignores_high_bit() {
+        assert_eq!(pack_septets(&[0xC1]), pack_septets(&[0x41]));
+    }
+
+    #[test]
+    fn test_unpack_clamps_to_available_data() {
+        let packed = pack_septets(b"abc");
+        assert_eq!(unpack_septets(&packed, 100), b"abc");
+    }
+
+    #[test]
+    fn test_fill_bits_for_udh() {
+        assert_eq!(fill_bits_for_udh(0), 0);
+        assert_eq!(fill_bits_for_udh(6), 1); // 8-bit concatenation
+        assert_eq!(fill_bits_for_udh(7), 0); // 16-bit concatenation
+        assert_eq!(fill_bits_for_udh(12), 2); // concatenation + 16-bit ports
+        assert_eq!(fill_bits_for_udh(1), 6);
+    }
+
+    #[test]
+    fn test_pack_with_fill_matches_udh_vector() {
+        // SMS-DELIVER user data after a 6-octet concatenation header: one fill bit
+        let packed = pack_septets_with_fill(b"hellohello", 1).unwrap();
+        assert_eq!(
+            packed,
+            [0xD0, 0x65, 0x36, 0xFB, 0x8D, 0x2E, 0xB3, 0xD9, 0x6F]
+        );
+        assert_eq!(
+            unpack_septets_with_fill(&packed, 10, 1).unwrap(),
+            b"hellohello"
+        );
+    }
+
+    #[test]
+    fn test_pack_unpack_with_every_fill() {
+        let source: Vec<u8> = (0..40).map(|i| (i * 3) as u8 & 0x7F).collect();
+        for fill in 0..=6u8 {
+            for len in 0..source.len() {
+                let packed = pack_septets_with_fill(&source[..len], fill).unwrap();
+                assert_eq!(packed.len(), (usize::from(fill) + len * 7).div_ceil(8));
+                assert_eq!(packed.first().map_or(0, |b| b & ((1 << fill) - 1)), 0);
+                let unpacked = unpack_septets_with_fill(&packed, len, fill).unwrap();
+                assert_eq!(unpacked, &source[..len], "fill {} length {}", fill, len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_fill_bits() {
+        assert_eq!(
+            pack_septets_with_fill(b"a", 7),
+            Err(Gsm7Error::InvalidFillBits { fill_bits: 7 })
+        );
+        assert_eq!(
+            unpack_septets_with_fill(&[0x00], 1, 9),
+            Err(Gsm7Error::InvalidFillBits { fill_bits: 9 })
+        );
+    }
+
+    #[test]
+    fn test_cr_padding_applied_for_seven_spare_bits() {
+        for n in 1..4 {
+            let septets = vec![0x41; 8 * n - 1];
+            let packed = pack_septets_cr_padded(&septets);
+            assert_eq!(packed.len(), 7 * n);
+            assert_eq!(packed.last().unwrap() >> 1, CR);
+            assert_eq!(unpack_septets_cr_padded(&packed), septets);
+        }
+    }
+
+    #[test]
+    fn test_cr_padding_not_applied_otherwise() {
+        for len in (0..24).filter(|len| len % 8 != 7) {
+            let septets = vec![0x41; len];
+            assert_eq!(pack_septets_cr_padded(&septets), pack_septets(&septets));
+            assert_eq!(unpack_septets_cr_padded(&pack_septets(&septets)), septets);
+        }
+    }
+
+    #[test]
+    fn test_cr_padding_keeps_wanted_trailing_cr() {
+        // 7 characters + wanted <CR>: ends on an octet boundary, so another <CR> is added
+        let septets = b"ABCDEFG\r";
+        let packed = pack_septets_cr_padded(septets);
+        assert_eq!(packed.len(), 8);
+        assert_eq!(unpack_septets_cr_padded(&packed), b"ABCDEFG\r\r");
+
+        // 6 characters + wanted <CR>: the padding <CR> is stripped, the wanted one kept
+        let septets = b"ABCDEF\r";
+        let packed = pack_septets_cr_padded(septets);
+        assert_eq!(packed.len(), 7);
+        assert_eq!(unpack_septets_cr_padded(&packed), septets);
+    }
+
+    #[test]
+    fn test_trailing_at_sign_survives_cr_unpacking() {
+        let septets = b"ABCDEFG@";
+        let packed = pack_septets_cr_padded(septets);
+        assert_eq!(unpack_septets_cr_padded(&packed), septets);
+    }
+
+    #[test]
+    fn test_encode_decode_packed_roundtrip() {
+        let config = Gsm7Config::default();
+        let text = "Extension chars: {[]}\\~€|^";
+        let packed = encode_packed(text, &config).unwrap();
+        assert_eq!(packed.septet_count, crate::encoded_len(text).unwrap());
+        let decoded = decode_packed(&packed.bytes, packed.septet_count, &config).unwrap();
+        assert_eq!(decoded, text);
+    }
+}
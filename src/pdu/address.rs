@@ -0,0 +1,413 @@
+//! TP-DA / TP-OA address fields (3GPP TS 23.040 §9.1.2.5).
+
+use super::Reader;
+use crate::{
+    Gsm7Config, Gsm7Error, Result, decode, encode_with_config, pack_septets, unpack_septets,
+};
+
+/// Maximum digits in an address field.
+const MAX_DIGITS: usize = 20;
+
+/// Maximum characters in an alphanumeric address: 11 septets fill the
+/// 10-octet address value.
+const MAX_ALPHANUMERIC_CHARACTERS: usize = 11;
+
+/// Characters for semi-octet values 0x0–0xE; 0xF is the filler.
+const DIGITS: [char; 15] = [
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '*', '#', 'a', 'b', 'c',
+];
+
+/// Type of number, bits 6–4 of the type-of-address octet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypeOfNumber {
+    /// Unknown; the number is sent as dialled.
+    Unknown = 0,
+    /// International number, without the leading `+` or international prefix.
+    International = 1,
+    /// National number.
+    National = 2,
+    /// Network specific number.
+    NetworkSpecific = 3,
+    /// Subscriber number.
+    Subscriber = 4,
+    /// Alphanumeric, GSM 7-bit packed.
+    Alphanumeric = 5,
+    /// Abbreviated number.
+    Abbreviated = 6,
+    /// Reserved for extension.
+    Reserved = 7,
+}
+
+/// Numbering plan identification, bits 3–0 of the type-of-address octet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NumberingPlan {
+    /// Unknown.
+    Unknown,
+    /// ISDN/telephone numbering plan (E.164/E.163).
+    Isdn,
+    /// Data numbering plan (X.121).
+    Data,
+    /// Telex numbering plan.
+    Telex,
+    /// Service centre specific plan 1.
+    ServiceCentreSpecific1,
+    /// Service centre specific plan 2.
+    ServiceCentreSpecific2,
+    /// National numbering plan.
+    National,
+    /// Private numbering plan.
+    Private,
+    /// ERMES numbering plan (ETSI DE/PS 3 01-3).
+    Ermes,
+    /// Any other, reserved value.
+    Reserved(u8),
+}
+
+impl TypeOfNumber {
+    /// The type of number for the three-bit identifier (upper bits ignored).
+    pub fn from_id(id: u8) -> Self {
+        match id & 0b111 {
+            0 => TypeOfNumber::Unknown,
+            1 => TypeOfNumber::International,
+            2 => TypeOfNumber::National,
+            3 => TypeOfNumber::NetworkSpecific,
+            4 => TypeOfNumber::Subscriber,
+            5 => TypeOfNumber::Alphanumeric,
+            6 => TypeOfNumber::Abbreviated,
+            _ => TypeOfNumber::Reserved,
+        }
+    }
+}
+
+impl NumberingPlan {
+    /// The numbering plan for the four-bit identifier (upper bits ignored).
+    pub fn from_id(id: u8) -> Self {
+        match id & 0x0F {
+            0b0000 => NumberingPlan::Unknown,
+            0b0001 => NumberingPlan::Isdn,
+            0b0011 => NumberingPlan::Data,
+            0b0100 => NumberingPlan::Telex,
+            0b0101 => NumberingPlan::ServiceCentreSpecific1,
+            0b0110 => NumberingPlan::ServiceCentreSpecific2,
+            0b1000 => NumberingPlan::National,
+            0b1001 => NumberingPlan::Private,
+            0b1010 => NumberingPlan::Ermes,
+            id => NumberingPlan::Reserved(id),
+        }
+    }
+
+    /// The four-bit plan identifier.
+    pub fn id(self) -> u8 {
+        match self {
+            NumberingPlan::Unknown => 0b0000,
+            NumberingPlan::Isdn => 0b0001,
+            NumberingPlan::Data => 0b0011,
+            NumberingPlan::Telex => 0b0100,
+            NumberingPlan::ServiceCentreSpecific1 => 0b0101,
+            NumberingPlan::ServiceCentreSpecific2 => 0b0110,
+            NumberingPlan::National => 0b1000,
+            NumberingPlan::Private => 0b1001,
+            NumberingPlan::Ermes => 0b1010,
+            NumberingPlan::Reserved(id) => id & 0x0F,
+        }
+    }
+}
+
+/// An SMS address: a phone number or an alphanumeric sender ID, with its type
+/// of address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SmsAddress {
+    /// Type of number.
+    pub type_of_number: TypeOfNumber,
+    /// Numbering plan.
+    pub numbering_plan: NumberingPlan,
+    /// The address: digits (`0`–`9`, `*`, `#` and `a`–`c`), or the sender
+    /// name when the type of number is alphanumeric.
+    pub value: String,
+}
+
+impl SmsAddress {
+    /// Create an address from a dialled number.
+    ///
+    /// A leading `+` makes it an international number; anything else is sent
+    /// with an unknown type of number. Both use the ISDN numbering plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidAddress` if the number is empty, longer than
+    /// 20 digits, or contains characters other than `0`–`9`, `*`, `#` and `a`–`c`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use gsm7_alt::pdu::{SmsAddress, TypeOfNumber};
+    ///
+    /// let address = SmsAddress::new("+46708251358")?;
+    /// assert_eq!(address.type_of_number, TypeOfNumber::International);
+    /// assert_eq!(address.value, "46708251358");
+    /// # Ok::<(), gsm7_alt::Gsm7Error>(())
+    /// ```
+    pub fn new(number: &str) -> Result<Self> {
+        let (type_of_number, digits) = match number.strip_prefix('+') {
+            Some(digits) => (TypeOfNumber::International, digits),
+            None => (TypeOfNumber::Unknown, number),
+        };
+        let address = Self {
+            type_of_number,
+            numbering_plan: NumberingPlan::Isdn,
+            value: digits.to_string(),
+        };
+        address.semi_octets()?;
+        Ok(address)
+    }
+
+    /// Create an alphanumeric sender ID.
+    ///
+    /// The name is sent as packed GSM 7-bit text and may use at most 11
+    /// characters, and at most 11 septets when it contains extension characters.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidAddress` if the name is empty, too long, or
+    /// contains characters outside the GSM 7-bit default alphabet.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use gsm7_alt::pdu::{SmsAddress, TypeOfNumber};
+    ///
+    /// let sender = SmsAddress::alphanumeric("MyBank")?;
+    /// assert_eq!(sender.type_of_number, TypeOfNumber::Alphanumeric);
+    /// assert!(SmsAddress::alphanumeric("MyBankAlerts").is_err());
+    /// # Ok::<(), gsm7_alt::Gsm7Error>(())
+    /// ```
+    pub fn alphanumeric(name: &str) -> Result<Self> {
+        let address = Self {
+            type_of_number: TypeOfNumber::Alphanumeric,
+            numbering_plan: NumberingPlan::Unknown,
+            value: name.to_string(),
+        };
+        address.alphanumeric_septets()?;
+        Ok(address)
+    }
+
+    /// Whether this is an alphanumeric sender ID.
+    pub fn is_alphanumeric(&self) -> bool {
+        self.type_of_number == TypeOfNumber::Alphanumeric
+    }
+
+    /// Encode the address field: the length in semi-octets, the type of
+    /// address, then the digits as swapped semi-octets padded with `0xF`, or
+    /// the packed septets of an alphanumeric address.
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>> {
+        let (length, body) = if self.is_alphanumeric() {
+            let septets = self.alphanumeric_septets()?;
+            ((septets.len() * 7).div_ceil(4), pack_septets(&septets))
+        } else {
+            let nibbles = self.semi_octets()?;
+            let body = nibbles
+                .chunks(2)
+                .map(|pair| pair[0] | pair.get(1).copied().unwrap_or(0x0F) << 4)
+                .collect();
+            (nibbles.len(), body)
+        };
+
+        let mut bytes = Vec::with_capacity(2 + body.len());
+        bytes.push(length as u8);
+        bytes.push(self.type_of_address());
+        bytes.extend(body);
+        Ok(bytes)
+    }
+
+    /// Read an address field written by [`to_bytes`](Self::to_bytes).
+    pub(super) fn read(reader: &mut Reader, field: &'static str) -> Result<Self> {
+        let length = usize::from(reader.u8(field)?);
+        let type_of_address = reader.u8(field)?;
+        let type_of_number = TypeOfNumber::from_id(type_of_address >> 4);
+        let value_offset = reader.offset;
+        let octets = reader.take(length.div_ceil(2), field)?;
+
+        let value = if type_of_number == TypeOfNumber::Alphanumeric {
+            // The length counts the semi-octets holding septets
+            let septets = unpack_septets(octets, length * 4 / 7);
+            decode(&septets)?
+        } else {
+            octets
+                .iter()
+                .flat_map(|&octet| [octet & 0x0F, octet >> 4])
+                .take(length)
+                .enumerate()
+                .map(|(index, nibble)| {
+                    DIGITS.get(usize::from(nibble)).copied().ok_or_else(|| {
+                        Gsm7Error::InvalidPduField {
+                            field,
+                            offset: value_offset + index / 2,
+                            reason: format!("filler nibble 0x{:X} within the digits", nibble),
+                        }
+                    })
+                })
+                .collect::<Result<String>>()?
+        };
+
+        Ok(Self {
+            type_of_number,
+            numbering_plan: NumberingPlan::from_id(type_of_address),
+            value,
+        })
+    }
+
+    fn type_of_address(&self) -> u8 {
+        0x80 | (self.type_of_number as u8) << 4 | self.numbering_plan.id()
+    }
+
+    fn semi_octets(&self) -> Result<Vec<u8>> {
+        let invalid = |reason: String| Gsm7Error::InvalidAddress { reason };
+
+        if self.value.is_empty() || self.value.chars().count() > MAX_DIGITS {
+            return Err(invalid(format!(
+                "'{}' must have 1 to {} digits",
+                self.value, MAX_DIGITS
+            )));
+        }
+        self.value
+            .chars()
+            .map(|ch| {
+                DIGITS
+                    .iter()
+                    .position(|&digit| digit == ch)
+                    .map(|nibble| nibble as u8)
+                    .ok_or_else(|| {
+                        invalid(format!("'{}' contains invalid digit '{}'", self.value, ch))
+                    })
+            })
+            .collect()
+    }
+
+    fn alphanumeric_septets(&self) -> Result<Vec<u8>> {
+        let invalid = |reason: String| Gsm7Error::InvalidAddress { reason };
+
+        let characters = self.value.chars().count();
+        if characters == 0 || characters > MAX_ALPHANUMERIC_CHARACTERS {
+            return Err(invalid(format!(
+                "'{}' must have 1 to {} characters",
+                self.value, MAX_ALPHANUMERIC_CHARACTERS
+            )));
+        }
+        let septets = encode_with_config(&self.value, &Gsm7Config::strict())
+            .map_err(|err| invalid(format!("'{}': {}", self.value, err)))?;
+        if septets.len() > MAX_ALPHANUMERIC_CHARACTERS {
+            return Err(invalid(format!(
+                "'{}' needs {} septets, more than the maximum of {}",
+                self.value,
+                septets.len(),
+                MAX_ALPHANUMERIC_CHARACTERS
+            )));
+        }
+        Ok(septets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_international_odd_length() {
+        let address = SmsAddress::new("+46708251358").unwrap();
+        assert_eq!(
+            address.to_bytes().unwrap(),
+            [0x0B, 0x91, 0x64, 0x07, 0x28, 0x15, 0x53, 0xF8]
+        );
+    }
+
+    #[test]
+    fn test_unknown_even_length() {
+        let address = SmsAddress::new("0123*#").unwrap();
+        assert_eq!(address.type_of_number, TypeOfNumber::Unknown);
+        assert_eq!(address.to_bytes().unwrap(), [0x06, 0x81, 0x10, 0x32, 0xBA]);
+    }
+
+    #[test]
+    fn test_read_roundtrip() {
+        for number in ["+46708251358", "0123*#", "12345abc"] {
+            let address = SmsAddress::new(number).unwrap();
+            let bytes = address.to_bytes().unwrap();
+            let mut reader = Reader::new(&bytes);
+            assert_eq!(SmsAddress::read(&mut reader, "TP-OA").unwrap(), address);
+            assert_eq!(reader.offset, bytes.len());
+        }
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let mut reader = Reader::new(&[0x0B, 0x91, 0x64, 0x07]);
+        assert_eq!(
+            SmsAddress::read(&mut reader, "TP-OA"),
+            Err(Gsm7Error::TruncatedPdu {
+                field: "TP-OA",
+                offset: 4,
+            })
+        );
+
+        let mut reader = Reader::new(&[0x04, 0x81, 0x21, 0xF3]);
+        assert!(matches!(
+            SmsAddress::read(&mut reader, "TP-OA"),
+            Err(Gsm7Error::InvalidPduField { offset: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_alphanumeric() {
+        let sender = SmsAddress::alphanumeric("Google").unwrap();
+        let bytes = sender.to_bytes().unwrap();
+        // 6 septets = 42 bits = 11 semi-octets
+        assert_eq!(bytes, [0x0B, 0xD0, 0xC7, 0xF7, 0xFB, 0xCC, 0x2E, 0x03]);
+
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(SmsAddress::read(&mut reader, "TP-OA").unwrap(), sender);
+    }
+
+    #[test]
+    fn test_alphanumeric_lengths() {
+        // Seven septets leave a spare nibble that must not decode as '@'
+        for name in ["ABCDEFG", "A", "Eleven Char", "{Promo}"] {
+            let sender = SmsAddress::alphanumeric(name).unwrap();
+            let bytes = sender.to_bytes().unwrap();
+            let mut reader = Reader::new(&bytes);
+            assert_eq!(SmsAddress::read(&mut reader, "TP-OA").unwrap().value, name);
+            assert_eq!(reader.offset, bytes.len());
+        }
+        assert_eq!(
+            SmsAddress::alphanumeric("Eleven Char")
+                .unwrap()
+                .to_bytes()
+                .unwrap()[0],
+            20
+        );
+    }
+
+    #[test]
+    fn test_alphanumeric_limits() {
+        assert!(matches!(
+            SmsAddress::alphanumeric("Twelve Chars"),
+            Err(Gsm7Error::InvalidAddress { .. })
+        ));
+        // Ten characters, but the braces are escaped: 14 septets
+        assert!(SmsAddress::alphanumeric("{{Promo}}!").is_err());
+        assert!(SmsAddress::alphanumeric("Привет").is_err());
+        assert!(SmsAddress::alphanumeric("").is_err());
+    }
+
+    #[test]
+    fn test_invalid_numbers() {
+        assert!(matches!(
+            SmsAddress::new("+"),
+            Err(Gsm7Error::InvalidAddress { .. })
+        ));
+        assert!(matches!(
+            SmsAddress::new("12-34"),
+            Err(Gsm7Error::InvalidAddress { .. })
+        ));
+        assert!(SmsAddress::new(&"1".repeat(21)).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! SMS-COMMAND (3GPP TS 23.040 §9.2.2.4).
+
+use super::{Reader, SmsAddress, expect_mti};
+use crate::{Gsm7Error, Result, hex};
+
+/// TP-MTI for SMS-COMMAND.
+const MTI_COMMAND: u8 = 0b10;
+
+/// Maximum TP-CD length in octets.
+const MAX_COMMAND_DATA_OCTETS: usize = 156;
+
+/// TP-CT: the operation requested (3GPP TS 23.040 §9.2.3.19).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandType {
+    /// Enquiry relating to a previously submitted message.
+    Enquiry,
+    /// Cancel the status report request of a previously submitted message.
+    CancelStatusReportRequest,
+    /// Delete a previously submitted message.
+    DeleteMessage,
+    /// Enable a status report request for a previously submitted message.
+    EnableStatusReportRequest,
+    /// Reserved or service centre specific value.
+    Other(u8),
+}
+
+impl CommandType {
+    /// The TP-CT octet.
+    pub fn code(self) -> u8 {
+        match self {
+            CommandType::Enquiry => 0x00,
+            CommandType::CancelStatusReportRequest => 0x01,
+            CommandType::DeleteMessage => 0x02,
+            CommandType::EnableStatusReportRequest => 0x03,
+            CommandType::Other(code) => code,
+        }
+    }
+
+    /// The command type for a TP-CT octet.
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0x00 => CommandType::Enquiry,
+            0x01 => CommandType::CancelStatusReportRequest,
+            0x02 => CommandType::DeleteMessage,
+            0x03 => CommandType::EnableStatusReportRequest,
+            code => CommandType::Other(code),
+        }
+    }
+}
+
+/// An SMS-COMMAND TPDU, sent from the handset to the service centre to act
+/// on a previously submitted message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmsCommand {
+    /// TP-SRR: request a status report for the command.
+    pub status_report_request: bool,
+    /// TP-MR: reference of this command.
+    pub message_reference: u8,
+    /// TP-PID: protocol identifier.
+    pub protocol_identifier: u8,
+    /// TP-CT: the operation.
+    pub command_type: CommandType,
+    /// TP-MN: TP-MR of the message the command applies to.
+    pub message_number: u8,
+    /// TP-DA: destination address of that message.
+    pub destination: SmsAddress,
+    /// TP-UDHI: `command_data` starts with a User Data Header.
+    pub command_data_has_header: bool,
+    /// TP-CD: command data, at most 156 octets.
+    pub command_data: Vec<u8>,
+}
+
+impl SmsCommand {
+    /// Create a command without command data, PID 0 and no status report request.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use gsm7_alt::pdu::{CommandType, SmsAddress, SmsCommand};
+    ///
+    /// let destination = SmsAddress::new("+46708251358")?;
+    /// let command = SmsCommand::new(CommandType::DeleteMessage, 0x42, destination);
+    /// assert_eq!(command.to_hex()?, "02000002420B916407281553F800");
+    /// # Ok::<(), gsm7_alt::Gsm7Error>(())
+    /// ```
+    pub fn new(command_type: CommandType, message_number: u8, destination: SmsAddress) -> Self {
+        Self {
+            status_report_request: false,
+            message_reference: 0,
+            protocol_identifier: 0,
+            command_type,
+            message_number,
+            destination,
+            command_data_has_header: false,
+            command_data: Vec::new(),
+        }
+    }
+
+    /// Parse an SMS-COMMAND TPDU.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The TPDU, without a service centre address
+    ///
+    /// # Returns
+    ///
+    /// The parsed command.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::TruncatedPdu` if the PDU ends early and
+    /// `Gsm7Error::InvalidPduField` if a field cannot be interpreted, both with
+    /// the byte offset of the problem.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+
+        let first = reader.u8("first octet")?;
+        expect_mti(first, MTI_COMMAND, "SMS-COMMAND")?;
+        let message_reference = reader.u8("TP-MR")?;
+        let protocol_identifier = reader.u8("TP-PID")?;
+        let command_type = CommandType::from_code(reader.u8("TP-CT")?);
+        let message_number = reader.u8("TP-MN")?;
+        let destination = SmsAddress::read(&mut reader, "TP-DA")?;
+        let length_offset = reader.offset;
+        let length = usize::from(reader.u8("TP-CDL")?);
+        if length > MAX_COMMAND_DATA_OCTETS {
+            return Err(Gsm7Error::InvalidPduField {
+                field: "TP-CDL",
+                offset: length_offset,
+                reason: format!("{} octets, more than the maximum of 156", length),
+            });
+        }
+        let command_data = reader.take(length, "TP-CD")?.to_vec();
+        reader.finish("TP-CD")?;
+
+        Ok(Self {
+            status_report_request: first & 0x20 != 0,
+            message_reference,
+            protocol_identifier,
+            command_type,
+            message_number,
+            destination,
+            command_data_has_header: first & 0x40 != 0,
+            command_data,
+        })
+    }
+
+    /// Parse an SMS-COMMAND TPDU from a hex string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidHex` if `hex` is not a hex string, and
+    /// otherwise the errors of [`from_bytes`](Self::from_bytes).
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        Self::from_bytes(&hex::decode(hex)?)
+    }
+
+    /// Serialize the TPDU.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidAddress` if the destination cannot be
+    /// encoded, and `Gsm7Error::UserDataTooLong` if the command data exceeds
+    /// 156 octets.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        if self.command_data.len() > MAX_COMMAND_DATA_OCTETS {
+            return Err(Gsm7Error::UserDataTooLong {
+                octets: self.command_data.len(),
+            });
+        }
+
+        let mut first = MTI_COMMAND;
+        if self.status_report_request {
+            first |= 0x20;
+        }
+        if self.command_data_has_header {
+            first |= 0x40;
+        }
+
+        let mut bytes = vec![
+            first,
+            self.message_reference,
+            self.protocol_identifier,
+            self.command_type.code(),
+            self.message_number,
+        ];
+        bytes.extend(self.destination.to_bytes()?);
+        bytes.push(self.command_data.len() as u8);
+        bytes.extend_from_slice(&self.command_data);
+        Ok(bytes)
+    }
+
+    /// Serialize the TPDU as an uppercase hex string.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`to_bytes`](Self::to_bytes).
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(hex::encode(&self.to_bytes()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut command = SmsCommand::new(
+            CommandType::Enquiry,
+            0x17,
+            SmsAddress::new("0123456").unwrap(),
+        );
+        command.status_report_request = true;
+        command.message_reference = 0x05;
+        command.command_data = vec![0xDE, 0xAD];
+
+        let bytes = command.to_bytes().unwrap();
+        assert_eq!(
+            bytes,
+            [
+                0x22, 0x05, 0x00, 0x00, 0x17, 0x07, 0x81, 0x10, 0x32, 0x54, 0xF6, 0x02, 0xDE, 0xAD
+            ]
+        );
+        assert_eq!(SmsCommand::from_bytes(&bytes).unwrap(), command);
+    }
+
+    #[test]
+    fn test_command_types() {
+        for code in 0..=0xFF {
+            assert_eq!(CommandType::from_code(code).code(), code);
+        }
+        assert_eq!(CommandType::from_code(0x02), CommandType::DeleteMessage);
+    }
+
+    #[test]
+    fn test_command_data_limits() {
+        let mut command = SmsCommand::new(CommandType::Enquiry, 0, SmsAddress::new("1").unwrap());
+        command.command_data = vec![0; 157];
+        assert_eq!(
+            command.to_bytes(),
+            Err(Gsm7Error::UserDataTooLong { octets: 157 })
+        );
+
+        assert!(matches!(
+            SmsCommand::from_hex("02000000000181F19D"),
+            Err(Gsm7Error::InvalidPduField {
+                field: "TP-CDL",
+                offset: 8,
+                ..
+            })
+        ));
+        assert_eq!(
+            SmsCommand::from_hex("02000000000181F102AA"),
+            Err(Gsm7Error::TruncatedPdu {
+                field: "TP-CD",
+                offset: 10,
+            })
+        );
+    }
+}
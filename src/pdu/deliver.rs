@@ -0,0 +1,236 @@
+//! SMS-DELIVER (3GPP TS 23.040 §9.2.2.1).
+
+use super::{Reader, SmsAddress, SmsTimestamp, UserData, expect_mti, user_data_coding};
+use crate::{DataCodingScheme, Result, hex};
+
+/// TP-MTI for SMS-DELIVER.
+const MTI_DELIVER: u8 = 0b00;
+
+/// An SMS-DELIVER TPDU, sent from the service centre to the handset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmsDeliver {
+    /// More messages are waiting in the service centre (TP-MMS clear).
+    pub more_messages_to_send: bool,
+    /// TP-LP: the message was forwarded or is a spawned message.
+    pub loop_prevention: bool,
+    /// TP-SRI: a status report will be returned to the originator.
+    pub status_report_indication: bool,
+    /// TP-RP: a reply path is set.
+    pub reply_path: bool,
+    /// TP-OA: originating address.
+    pub originator: SmsAddress,
+    /// TP-PID: protocol identifier.
+    pub protocol_identifier: u8,
+    /// TP-DCS: data coding scheme.
+    pub data_coding_scheme: DataCodingScheme,
+    /// TP-SCTS: when the service centre received the message.
+    pub service_centre_timestamp: SmsTimestamp,
+    /// TP-UD, with TP-UDL.
+    pub user_data: UserData,
+    /// The decoded text, or `None` for 8-bit data.
+    pub text: Option<String>,
+}
+
+impl SmsDeliver {
+    /// Parse an SMS-DELIVER TPDU.
+    ///
+    /// `bytes` must start at the first octet of the TPDU; use
+    /// [`skip_smsc_address`](super::skip_smsc_address) first for PDUs read
+    /// from a modem.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The TPDU
+    ///
+    /// # Returns
+    ///
+    /// The parsed message with its text decoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::TruncatedPdu` if the PDU ends early and
+    /// `Gsm7Error::InvalidPduField` if a field cannot be interpreted, both with
+    /// the byte offset of the problem. Malformed UCS-2 text is reported as by
+    /// [`decode_ucs2`](crate::decode_ucs2).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use gsm7_alt::pdu::{SmsDeliver, skip_smsc_address};
+    ///
+    /// let pdu = [
+    ///     0x07, 0x91, 0x72, 0x83, 0x01, 0x00, 0x10, 0xF5, // SMSC address
+    ///     0x04, 0x0B, 0xC8, 0x72, 0x38, 0x88, 0x09, 0x00, 0xF1, 0x00, 0x00,
+    ///     0x99, 0x30, 0x92, 0x51, 0x61, 0x95, 0x80, 0x0A,
+    ///     0xE8, 0x32, 0x9B, 0xFD, 0x46, 0x97, 0xD9, 0xEC, 0x37,
+    /// ];
+    /// let deliver = SmsDeliver::from_bytes(skip_smsc_address(&pdu)?)?;
+    /// assert_eq!(deliver.originator.value, "27838890001");
+    /// assert_eq!(deliver.text.as_deref(), Some("hellohello"));
+    /// # Ok::<(), gsm7_alt::Gsm7Error>(())
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+
+        let first = reader.u8("first octet")?;
+        expect_mti(first, MTI_DELIVER, "SMS-DELIVER")?;
+
+        let originator = SmsAddress::read(&mut reader, "TP-OA")?;
+        let protocol_identifier = reader.u8("TP-PID")?;
+        let dcs_offset = reader.offset;
+        let data_coding_scheme = DataCodingScheme::from_octet(reader.u8("TP-DCS")?);
+        let coding = user_data_coding(data_coding_scheme, dcs_offset)?;
+        let service_centre_timestamp = SmsTimestamp::read(&mut reader, "TP-SCTS")?;
+        let user_data = UserData::read(&mut reader, coding, first & 0x40 != 0)?;
+        reader.finish("TP-UD")?;
+
+        let text = user_data.text()?;
+        Ok(Self {
+            more_messages_to_send: first & 0x04 == 0,
+            loop_prevention: first & 0x08 != 0,
+            status_report_indication: first & 0x20 != 0,
+            reply_path: first & 0x80 != 0,
+            originator,
+            protocol_identifier,
+            data_coding_scheme,
+            service_centre_timestamp,
+            user_data,
+            text,
+        })
+    }
+
+    /// Parse an SMS-DELIVER TPDU from a hex string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidHex` if `hex` is not a hex string, and
+    /// otherwise the errors of [`from_bytes`](Self::from_bytes).
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        Self::from_bytes(&hex::decode(hex)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CharacterSet;
+    use crate::Gsm7Error;
+    use crate::pdu::{SmsSubmit, TypeOfNumber};
+    use crate::{NationalLanguage, SmsEncoding, SplitOptions};
+
+    /// Build a DELIVER from the user data of a SUBMIT to the same address.
+    fn deliver_hex(text: &str, options: &SplitOptions) -> Vec<String> {
+        let address = crate::pdu::SmsAddress::new("+4912345").unwrap();
+        SmsSubmit::from_text(address, text, options)
+            .unwrap()
+            .iter()
+            .map(|submit| {
+                let bytes = submit.to_bytes().unwrap();
+                // Replace first octet, drop TP-MR, insert an SCTS after TP-DCS
+                let mut deliver = vec![(bytes[0] & 0x40) | 0x04];
+                deliver.extend_from_slice(&bytes[2..10]);
+                deliver.extend_from_slice(&[0x52, 0x10, 0x61, 0x21, 0x43, 0x00, 0x40]);
+                deliver.extend_from_slice(&bytes[10..]);
+                hex::encode(&deliver)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_gsm7() {
+        let deliver =
+            SmsDeliver::from_hex("040BC87238880900F10000993092516195800AE8329BFD4697D9EC37")
+                .unwrap();
+        assert!(!deliver.more_messages_to_send);
+        assert!(!deliver.status_report_indication);
+        assert_eq!(deliver.originator.type_of_number, TypeOfNumber::Subscriber);
+        assert_eq!(deliver.protocol_identifier, 0);
+        assert_eq!(
+            deliver.service_centre_timestamp.to_string(),
+            "1999-03-29T15:16:59+02:00"
+        );
+        assert_eq!(deliver.user_data.length, 10);
+        assert!(deliver.user_data.header.is_empty());
+        assert_eq!(deliver.text.as_deref(), Some("hellohello"));
+    }
+
+    #[test]
+    fn test_parse_concatenated_with_shift_tables() {
+        let options = SplitOptions {
+            encoding: Some(SmsEncoding::Gsm7 {
+                locking_shift: Some(NationalLanguage::Turkish),
+                single_shift: Some(NationalLanguage::Turkish),
+            }),
+            ..Default::default()
+        };
+        let text = "Çok güzel bir gün, İstanbul'da hava çok sıcak! ".repeat(5);
+        let parts = deliver_hex(&text, &options);
+        assert_eq!(parts.len(), 2);
+
+        let decoded: String = parts
+            .iter()
+            .map(|part| SmsDeliver::from_hex(part).unwrap().text.unwrap())
+            .collect();
+        assert_eq!(decoded, text);
+
+        let first = SmsDeliver::from_hex(&parts[0]).unwrap();
+        assert_eq!(first.user_data.header[..2], [0x0B, 0x00]);
+    }
+
+    #[test]
+    fn test_parse_ucs2() {
+        let parts = deliver_hex("Привет 🦀", &SplitOptions::default());
+        let deliver = SmsDeliver::from_hex(&parts[0]).unwrap();
+        assert_eq!(deliver.user_data.coding, CharacterSet::Ucs2);
+        assert_eq!(deliver.data_coding_scheme, DataCodingScheme::UCS2);
+        assert_eq!(deliver.text.as_deref(), Some("Привет 🦀"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            SmsDeliver::from_hex("040BC87238880900F10000993092"),
+            Err(Gsm7Error::TruncatedPdu {
+                field: "TP-SCTS",
+                offset: 14,
+            })
+        );
+        assert!(matches!(
+            SmsDeliver::from_hex("010BC87238880900F10000993092516195800AE8329BFD4697D9EC37"),
+            Err(Gsm7Error::InvalidPduField {
+                field: "TP-MTI",
+                offset: 0,
+                ..
+            })
+        ));
+        assert!(matches!(
+            SmsDeliver::from_hex("040BC87238880900F10020993092516195800AE8329BFD4697D9EC37"),
+            Err(Gsm7Error::InvalidPduField {
+                field: "TP-DCS",
+                offset: 10,
+                ..
+            })
+        ));
+        // TP-UDL claims 11 septets: one octet too few
+        assert_eq!(
+            SmsDeliver::from_hex("040BC87238880900F10000993092516195800BE8329BFD4697D9EC37"),
+            Err(Gsm7Error::TruncatedPdu {
+                field: "TP-UD",
+                offset: 28,
+            })
+        );
+        // UDHI set, but the header length exceeds the user data
+        assert!(matches!(
+            SmsDeliver::from_hex("440BC87238880900F100049930925161958002FF00"),
+            Err(Gsm7Error::InvalidPduField {
+                field: "TP-UDHL",
+                offset: 19,
+                ..
+            })
+        ));
+        assert!(matches!(
+            SmsDeliver::from_hex("04ZZ"),
+            Err(Gsm7Error::InvalidHex { .. })
+        ));
+    }
+}
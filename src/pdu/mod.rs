@@ -0,0 +1,232 @@
+//! SMS transfer protocol data units (3GPP TS 23.040).
+//!
+//! TPDUs are what a modem takes in PDU mode and what an SMSC exchanges with the
+//! handset. The user data is built with the crate's own encoding, packing and
+//! splitting, so headers, fill bits and TP-UDL stay consistent.
+
+mod address;
+mod command;
+mod deliver;
+mod reassembly;
+mod status_report;
+mod submit;
+mod time;
+mod validity;
+
+pub use address::{NumberingPlan, SmsAddress, TypeOfNumber};
+pub use command::{CommandType, SmsCommand};
+pub use deliver::SmsDeliver;
+pub use reassembly::{IncompleteMessage, ReassembledMessage, Reassembler};
+pub use status_report::{SmsStatusReport, StatusCategory, StatusReportQualifier, TransferStatus};
+pub use submit::SmsSubmit;
+pub use time::{SmsTimestamp, UtcDateTime};
+pub use validity::{EnhancedPeriod, EnhancedValidityPeriod, ValidityPeriod};
+
+use crate::reader::Reader;
+use crate::{
+    CharacterSet, DataCodingScheme, Gsm7Config, Gsm7Error, Result, SmsEncoding, SmsPart,
+    UserDataHeader, decode_ucs2, decode_with_config, unpack_septets_with_fill,
+};
+
+/// Maximum TP-UD length in octets.
+const MAX_USER_DATA_OCTETS: usize = 140;
+
+/// TP-UD: an optional User Data Header and the encoded payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserData {
+    /// Coding of the payload.
+    pub coding: CharacterSet,
+    /// User Data Header including the UDHL octet, or empty. Its presence sets TP-UDHI.
+    pub header: Vec<u8>,
+    /// Payload following the header; for GSM 7-bit, packed septets starting
+    /// after the fill bits.
+    pub payload: Vec<u8>,
+    /// TP-UDL: septets for GSM 7-bit (header and fill bits included), octets otherwise.
+    pub length: usize,
+}
+
+impl UserData {
+    /// User data carrying one part produced by
+    /// [`split_into_segments`](crate::split_into_segments) with packed septets.
+    pub fn from_part(part: &SmsPart) -> Self {
+        Self {
+            coding: match part.encoding {
+                SmsEncoding::Gsm7 { .. } => CharacterSet::Gsm7,
+                SmsEncoding::Ucs2 => CharacterSet::Ucs2,
+            },
+            header: part.udh.clone(),
+            payload: part.payload.clone(),
+            length: part.user_data_length,
+        }
+    }
+
+    /// 8-bit user data with an optional header (including its UDHL octet).
+    pub fn binary(header: Vec<u8>, payload: Vec<u8>) -> Self {
+        let length = header.len() + payload.len();
+        Self {
+            coding: CharacterSet::EightBit,
+            header,
+            payload,
+            length,
+        }
+    }
+
+    /// Whether TP-UDHI must be set.
+    pub fn has_header(&self) -> bool {
+        !self.header.is_empty()
+    }
+
+    /// The parsed User Data Header; empty if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidPduField` if the header is malformed.
+    pub fn udh(&self) -> Result<UserDataHeader> {
+        if self.header.is_empty() {
+            return Ok(UserDataHeader::default());
+        }
+        UserDataHeader::parse(&self.header)
+    }
+
+    /// Decode the payload as text.
+    ///
+    /// GSM 7-bit payloads are decoded with the national language tables
+    /// announced in the header; unknown codes become the replacement character.
+    ///
+    /// # Returns
+    ///
+    /// The text, or `None` for 8-bit data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidPduField` if the header is malformed, and
+    /// `Gsm7Error::InvalidUtf16` or `Gsm7Error::MalformedData` for malformed
+    /// UCS-2 payloads.
+    pub fn text(&self) -> Result<Option<String>> {
+        match self.coding {
+            CharacterSet::Gsm7 => {
+                let udh = self.udh()?;
+                let septets = unpack_septets_with_fill(
+                    &self.payload,
+                    self.length.saturating_sub(udh.septets()),
+                    udh.fill_bits(),
+                )?;
+                let config = Gsm7Config::national(udh.locking_shift(), udh.single_shift());
+                decode_with_config(&septets, &config).map(Some)
+            }
+            CharacterSet::Ucs2 => decode_ucs2(&self.payload).map(Some),
+            CharacterSet::EightBit => Ok(None),
+        }
+    }
+
+    /// Read TP-UDL and TP-UD, which run to the end of the PDU.
+    fn read(reader: &mut Reader, coding: CharacterSet, has_header: bool) -> Result<Self> {
+        let length = usize::from(reader.u8("TP-UDL")?);
+        let octets = match coding {
+            CharacterSet::Gsm7 => (length * 7).div_ceil(8),
+            CharacterSet::EightBit | CharacterSet::Ucs2 => length,
+        };
+        let start = reader.offset;
+        let user_data = reader.take(octets, "TP-UD")?;
+
+        let header_len = if has_header {
+            match user_data.first() {
+                Some(&udhl) if usize::from(udhl) < user_data.len() => usize::from(udhl) + 1,
+                _ => {
+                    return Err(Gsm7Error::InvalidPduField {
+                        field: "TP-UDHL",
+                        offset: start,
+                        reason: "header longer than the user data".to_string(),
+                    });
+                }
+            }
+        } else {
+            0
+        };
+        let (header, payload) = user_data.split_at(header_len);
+        if has_header {
+            UserDataHeader::parse(header).map_err(|err| match err {
+                Gsm7Error::InvalidPduField {
+                    field,
+                    offset,
+                    reason,
+                } => Gsm7Error::InvalidPduField {
+                    field,
+                    offset: start + offset,
+                    reason,
+                },
+                err => err,
+            })?;
+        }
+
+        Ok(Self {
+            coding,
+            header: header.to_vec(),
+            payload: payload.to_vec(),
+            length,
+        })
+    }
+
+    /// Append TP-UDL and TP-UD to `bytes`.
+    fn write(&self, bytes: &mut Vec<u8>) -> Result<()> {
+        let octets = self.header.len() + self.payload.len();
+        if octets > MAX_USER_DATA_OCTETS || self.length > usize::from(u8::MAX) {
+            return Err(Gsm7Error::UserDataTooLong { octets });
+        }
+        bytes.push(self.length as u8);
+        bytes.extend_from_slice(&self.header);
+        bytes.extend_from_slice(&self.payload);
+        Ok(())
+    }
+}
+
+/// Skip the service centre address that modems put in front of a TPDU.
+///
+/// The address is a length octet counting the octets that follow (type of
+/// address and digits), so `00` stands for "no address".
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::TruncatedPdu` if the address runs past the end of `bytes`.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::pdu::skip_smsc_address;
+///
+/// let pdu = [0x07, 0x91, 0x72, 0x83, 0x01, 0x00, 0x10, 0xF5, 0x04];
+/// assert_eq!(skip_smsc_address(&pdu)?, [0x04]);
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+pub fn skip_smsc_address(bytes: &[u8]) -> Result<&[u8]> {
+    let mut reader = Reader::new(bytes);
+    let len = reader.u8("SMSC address")?;
+    reader.take(usize::from(len), "SMSC address")?;
+    Ok(&bytes[reader.offset..])
+}
+
+/// Coding of the user data announced by a TP-DCS read at `offset`.
+fn user_data_coding(dcs: DataCodingScheme, offset: usize) -> Result<CharacterSet> {
+    let invalid = |reason: &str| Gsm7Error::InvalidPduField {
+        field: "TP-DCS",
+        offset,
+        reason: reason.to_string(),
+    };
+    if dcs.is_compressed() {
+        return Err(invalid("compressed user data is not supported"));
+    }
+    dcs.character_set()
+        .ok_or_else(|| invalid("reserved coding group"))
+}
+
+/// Check the TP-MTI bits of a first octet.
+fn expect_mti(first: u8, mti: u8, name: &str) -> Result<()> {
+    if first & 0b11 != mti {
+        return Err(Gsm7Error::InvalidPduField {
+            field: "TP-MTI",
+            offset: 0,
+            reason: format!("0b{:02b} is not {}", first & 0b11, name),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,497 @@
+//! Reassembly of concatenated SMS-DELIVER parts (3GPP TS 23.040 §9.2.3.24.1).
+//!
+//! Parts of one message share an originator, a concatenation reference and a
+//! part count. They may arrive in any order, more than once, or not at all,
+//! and each part announces its own national language tables.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::{SmsAddress, SmsDeliver};
+use crate::{CharacterSet, ConcatReference, Gsm7Error, Result, decode_ucs2};
+
+/// Identifies the parts of one concatenated message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MessageKey {
+    originator: SmsAddress,
+    reference: ConcatReference,
+    total: u8,
+}
+
+/// Parts received so far for one message.
+#[derive(Debug)]
+struct PendingMessage {
+    parts: Vec<Option<SmsDeliver>>,
+    updated: Instant,
+}
+
+impl PendingMessage {
+    fn missing(&self) -> Vec<u8> {
+        (1..)
+            .zip(&self.parts)
+            .filter(|(_, part)| part.is_none())
+            .map(|(sequence, _)| sequence)
+            .collect()
+    }
+}
+
+/// A complete message put together from its parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReassembledMessage {
+    /// TP-OA shared by the parts.
+    pub originator: SmsAddress,
+    /// Concatenation reference, or `None` for a single-part message.
+    pub reference: Option<ConcatReference>,
+    /// The parts in sequence order.
+    pub parts: Vec<SmsDeliver>,
+    /// The decoded text, or `None` if any part carries 8-bit data.
+    pub text: Option<String>,
+}
+
+impl ReassembledMessage {
+    /// The payloads of all parts joined, without their headers.
+    pub fn payload(&self) -> Vec<u8> {
+        self.parts
+            .iter()
+            .flat_map(|part| part.user_data.payload.iter().copied())
+            .collect()
+    }
+}
+
+/// A message still waiting for parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncompleteMessage {
+    /// TP-OA shared by the parts.
+    pub originator: SmsAddress,
+    /// Concatenation reference.
+    pub reference: ConcatReference,
+    /// Number of parts announced.
+    pub total: u8,
+    /// Part numbers not received yet, from 1, in ascending order.
+    pub missing: Vec<u8>,
+}
+
+/// Collects concatenated SMS-DELIVER parts until their message is complete.
+///
+/// Parts are grouped by originator, reference and part count. A duplicate
+/// part is ignored. A partial message that has not received a part within
+/// the timeout is dropped by [`evict_expired`](Self::evict_expired), or
+/// replaced when a new part reuses its reference.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use gsm7_alt::pdu::{Reassembler, SmsDeliver};
+///
+/// let mut reassembler = Reassembler::new(Duration::from_secs(300));
+/// let deliver =
+///     SmsDeliver::from_hex("040BC87238880900F10000993092516195800AE8329BFD4697D9EC37")?;
+///
+/// // A message without a concatenation IE is complete on its own
+/// let message = reassembler.push(deliver)?.unwrap();
+/// assert_eq!(message.text.as_deref(), Some("hellohello"));
+/// assert!(reassembler.is_empty());
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Reassembler {
+    timeout: Duration,
+    pending: HashMap<MessageKey, PendingMessage>,
+}
+
+impl Reassembler {
+    /// Create a reassembler that gives up on a message `timeout` after its
+    /// last part arrived.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Add a part received now.
+    ///
+    /// See [`push_at`](Self::push_at).
+    pub fn push(&mut self, part: SmsDeliver) -> Result<Option<ReassembledMessage>> {
+        self.push_at(part, Instant::now())
+    }
+
+    /// Add a part received at `now`.
+    ///
+    /// # Arguments
+    ///
+    /// * `part` - A parsed SMS-DELIVER, with or without a concatenation IE
+    /// * `now` - When the part was received
+    ///
+    /// # Returns
+    ///
+    /// The message if `part` completed it, `None` if parts are still missing
+    /// or `part` is a duplicate.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidConcatenation` if the part number is 0 or
+    /// larger than the part count, `Gsm7Error::InvalidPduField` if the header
+    /// is malformed, and `Gsm7Error::UndecodableMessage` with the parts and
+    /// the error of [`UserData::text`](super::UserData::text) if the completed
+    /// message cannot be decoded. Either way a completed message is no longer
+    /// pending, so a new message may reuse its reference.
+    pub fn push_at(
+        &mut self,
+        part: SmsDeliver,
+        now: Instant,
+    ) -> Result<Option<ReassembledMessage>> {
+        let Some((reference, total, sequence)) = part.user_data.udh()?.concatenation() else {
+            return reassembled(part.originator.clone(), None, vec![part]).map(Some);
+        };
+        if sequence == 0 || sequence > total {
+            return Err(Gsm7Error::InvalidConcatenation { sequence, total });
+        }
+
+        let key = MessageKey {
+            originator: part.originator.clone(),
+            reference,
+            total,
+        };
+        if self
+            .pending
+            .get(&key)
+            .is_some_and(|pending| self.is_expired(pending, now))
+        {
+            self.pending.remove(&key);
+        }
+
+        let pending = self
+            .pending
+            .entry(key.clone())
+            .or_insert_with(|| PendingMessage {
+                parts: vec![None; usize::from(total)],
+                updated: now,
+            });
+        let slot = &mut pending.parts[usize::from(sequence - 1)];
+        if slot.is_some() {
+            return Ok(None);
+        }
+        *slot = Some(part);
+        pending.updated = now;
+        if pending.parts.iter().any(Option::is_none) {
+            return Ok(None);
+        }
+
+        let parts = self
+            .pending
+            .remove(&key)
+            .map(|pending| pending.parts.into_iter().flatten().collect())
+            .unwrap_or_default();
+        reassembled(key.originator, Some(reference), parts).map(Some)
+    }
+
+    /// Drop the messages whose last part arrived more than the timeout ago.
+    ///
+    /// See [`evict_expired_at`](Self::evict_expired_at).
+    pub fn evict_expired(&mut self) -> Vec<IncompleteMessage> {
+        self.evict_expired_at(Instant::now())
+    }
+
+    /// Drop the messages whose last part arrived more than the timeout
+    /// before `now`.
+    ///
+    /// # Returns
+    ///
+    /// The dropped messages with their missing part numbers.
+    pub fn evict_expired_at(&mut self, now: Instant) -> Vec<IncompleteMessage> {
+        let expired: Vec<MessageKey> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| self.is_expired(pending, now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|key| {
+                let pending = self.pending.remove(&key)?;
+                Some(incomplete(key, &pending))
+            })
+            .collect()
+    }
+
+    /// The messages still waiting for parts, with their missing part numbers.
+    pub fn incomplete(&self) -> Vec<IncompleteMessage> {
+        self.pending
+            .iter()
+            .map(|(key, pending)| incomplete(key.clone(), pending))
+            .collect()
+    }
+
+    /// Number of messages waiting for parts.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether no message is waiting for parts.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    fn is_expired(&self, pending: &PendingMessage, now: Instant) -> bool {
+        now.saturating_duration_since(pending.updated) > self.timeout
+    }
+}
+
+fn incomplete(key: MessageKey, pending: &PendingMessage) -> IncompleteMessage {
+    IncompleteMessage {
+        originator: key.originator,
+        reference: key.reference,
+        total: key.total,
+        missing: pending.missing(),
+    }
+}
+
+/// The message made of `parts`, or `Gsm7Error::UndecodableMessage` holding
+/// them if their text cannot be decoded.
+fn reassembled(
+    originator: SmsAddress,
+    reference: Option<ConcatReference>,
+    parts: Vec<SmsDeliver>,
+) -> Result<ReassembledMessage> {
+    match join_text(&parts) {
+        Ok(text) => Ok(ReassembledMessage {
+            originator,
+            reference,
+            parts,
+            text,
+        }),
+        Err(error) => Err(Gsm7Error::UndecodableMessage {
+            parts,
+            error: Box::new(error),
+        }),
+    }
+}
+
+/// Decode and join the text of `parts`.
+///
+/// GSM 7-bit parts are decoded one by one, each with its own shift tables.
+/// Consecutive UCS-2 payloads are joined before decoding, so a surrogate pair
+/// split across parts survives.
+fn join_text(parts: &[SmsDeliver]) -> Result<Option<String>> {
+    let mut text = String::new();
+    let mut ucs2 = Vec::new();
+    for part in parts {
+        match part.user_data.coding {
+            CharacterSet::Ucs2 => {
+                ucs2.extend_from_slice(&part.user_data.payload);
+                continue;
+            }
+            CharacterSet::EightBit => return Ok(None),
+            CharacterSet::Gsm7 => {}
+        }
+        if !ucs2.is_empty() {
+            text.push_str(&decode_ucs2(&ucs2)?);
+            ucs2.clear();
+        }
+        text.push_str(&part.user_data.text()?.unwrap_or_default());
+    }
+    if !ucs2.is_empty() {
+        text.push_str(&decode_ucs2(&ucs2)?);
+    }
+    Ok(Some(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdu::{SmsTimestamp, UserData};
+    use crate::{
+        DataCodingScheme, NationalLanguage, SmsEncoding, SplitOptions, split_into_segments,
+    };
+
+    fn deliver_parts(originator: &str, text: &str, options: &SplitOptions) -> Vec<SmsDeliver> {
+        split_into_segments(text, options)
+            .unwrap()
+            .iter()
+            .map(|part| {
+                let user_data = UserData::from_part(part);
+                SmsDeliver {
+                    more_messages_to_send: false,
+                    loop_prevention: false,
+                    status_report_indication: false,
+                    reply_path: false,
+                    originator: SmsAddress::new(originator).unwrap(),
+                    protocol_identifier: 0,
+                    data_coding_scheme: DataCodingScheme::for_encoding(part.encoding),
+                    service_centre_timestamp: SmsTimestamp::from_octets([
+                        0x52, 0x10, 0x61, 0x21, 0x43, 0x00, 0x40,
+                    ])
+                    .unwrap(),
+                    text: user_data.text().unwrap(),
+                    user_data,
+                }
+            })
+            .collect()
+    }
+
+    fn options(reference: ConcatReference) -> SplitOptions {
+        SplitOptions {
+            reference,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_out_of_order_and_duplicates() {
+        let text = "All work and no play makes Jack a dull boy. ".repeat(10);
+        let parts = deliver_parts("+491701234", &text, &options(ConcatReference::EightBit(7)));
+        assert_eq!(parts.len(), 3);
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(60));
+        assert_eq!(reassembler.push(parts[2].clone()), Ok(None));
+        assert_eq!(reassembler.push(parts[0].clone()), Ok(None));
+        assert_eq!(reassembler.push(parts[0].clone()), Ok(None));
+        assert_eq!(reassembler.incomplete()[0].missing, [2]);
+
+        let message = reassembler.push(parts[1].clone()).unwrap().unwrap();
+        assert_eq!(message.text.as_deref(), Some(text.as_str()));
+        assert_eq!(message.reference, Some(ConcatReference::EightBit(7)));
+        assert_eq!(message.parts, parts);
+        assert!(reassembler.is_empty());
+    }
+
+    #[test]
+    fn test_separates_originators_and_references() {
+        let text = "x".repeat(200);
+        let first = deliver_parts("+111", &text, &options(ConcatReference::EightBit(1)));
+        let other_sender = deliver_parts("+222", &text, &options(ConcatReference::EightBit(1)));
+        let other_reference =
+            deliver_parts("+111", &text, &options(ConcatReference::SixteenBit(1)));
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(60));
+        assert_eq!(reassembler.push(first[0].clone()), Ok(None));
+        assert_eq!(reassembler.push(other_sender[1].clone()), Ok(None));
+        assert_eq!(reassembler.push(other_reference[1].clone()), Ok(None));
+        assert_eq!(reassembler.len(), 3);
+
+        let message = reassembler.push(first[1].clone()).unwrap().unwrap();
+        assert_eq!(message.originator.value, "111");
+        assert_eq!(reassembler.len(), 2);
+    }
+
+    #[test]
+    fn test_parts_with_different_shift_tables() {
+        let turkish = SplitOptions {
+            encoding: Some(SmsEncoding::Gsm7 {
+                locking_shift: Some(NationalLanguage::Turkish),
+                single_shift: None,
+            }),
+            ..Default::default()
+        };
+        let first = "İstanbul'da hava çok güzel. ".repeat(6);
+        let second = "Plain default alphabet text {with} escapes. ".repeat(4);
+        let turkish_parts = deliver_parts("+90555", &first, &turkish);
+        let default_parts = deliver_parts("+90555", &second, &SplitOptions::default());
+        assert_eq!((turkish_parts.len(), default_parts.len()), (2, 2));
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(60));
+        assert_eq!(reassembler.push(turkish_parts[0].clone()), Ok(None));
+        let message = reassembler.push(default_parts[1].clone()).unwrap().unwrap();
+        let expected = [
+            turkish_parts[0].text.clone().unwrap(),
+            default_parts[1].text.clone().unwrap(),
+        ]
+        .concat();
+        assert!(expected.starts_with("İstanbul"));
+        assert!(expected.contains('{'));
+        assert_eq!(message.text, Some(expected));
+    }
+
+    #[test]
+    fn test_ucs2_and_binary() {
+        let text = "Привет, мир! 🦀 ".repeat(6);
+        let parts = deliver_parts("+7900", &text, &SplitOptions::default());
+        let mut reassembler = Reassembler::new(Duration::from_secs(60));
+        let completed: Vec<_> = parts
+            .into_iter()
+            .filter_map(|part| reassembler.push(part).unwrap())
+            .collect();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].text.as_deref(), Some(text.as_str()));
+
+        let mut binary = deliver_parts("+7900", "hi", &SplitOptions::default()).remove(0);
+        binary.user_data = UserData::binary(Vec::new(), vec![0xCA, 0xFE]);
+        let message = reassembler.push(binary).unwrap().unwrap();
+        assert_eq!(message.text, None);
+        assert_eq!(message.payload(), [0xCA, 0xFE]);
+    }
+
+    #[test]
+    fn test_decode_error_returns_parts() {
+        let text = "Привет, мир! ".repeat(8);
+        let options = options(ConcatReference::EightBit(3));
+        let mut parts = deliver_parts("+7900", &text, &options);
+        assert_eq!(parts.len(), 2);
+        parts[1].user_data.payload.pop();
+
+        let mut reassembler = Reassembler::new(Duration::from_secs(60));
+        assert_eq!(reassembler.push(parts[0].clone()), Ok(None));
+        let Err(Gsm7Error::UndecodableMessage {
+            parts: returned,
+            error,
+        }) = reassembler.push(parts[1].clone())
+        else {
+            panic!("expected an undecodable message");
+        };
+        assert_eq!(returned, parts);
+        assert!(matches!(*error, Gsm7Error::MalformedData { .. }));
+        assert!(reassembler.is_empty());
+
+        // A new message reusing the reference is not taken for duplicates
+        let fresh = deliver_parts("+7900", &text, &options);
+        assert_eq!(reassembler.push(fresh[1].clone()), Ok(None));
+        let message = reassembler.push(fresh[0].clone()).unwrap().unwrap();
+        assert_eq!(message.text.as_deref(), Some(text.as_str()));
+    }
+
+    #[test]
+    fn test_timeout_eviction() {
+        let text = "y".repeat(400);
+        let parts = deliver_parts("+123", &text, &options(ConcatReference::EightBit(9)));
+        assert_eq!(parts.len(), 3);
+
+        let start = Instant::now();
+        let timeout = Duration::from_secs(30);
+        let mut reassembler = Reassembler::new(timeout);
+        reassembler.push_at(parts[1].clone(), start).unwrap();
+        assert!(reassembler.evict_expired_at(start + timeout).is_empty());
+
+        let evicted = reassembler.evict_expired_at(start + timeout + Duration::from_secs(1));
+        assert_eq!(
+            evicted,
+            [IncompleteMessage {
+                originator: SmsAddress::new("+123").unwrap(),
+                reference: ConcatReference::EightBit(9),
+                total: 3,
+                missing: vec![1, 3],
+            }]
+        );
+        assert!(reassembler.is_empty());
+
+        // A stale partial message is replaced by a new one reusing its reference
+        let later = start + Duration::from_secs(100);
+        reassembler.push_at(parts[0].clone(), start).unwrap();
+        reassembler.push_at(parts[1].clone(), later).unwrap();
+        assert_eq!(reassembler.incomplete()[0].missing, [1, 3]);
+    }
+
+    #[test]
+    fn test_invalid_sequence() {
+        let mut part = deliver_parts("+123", &"z".repeat(200), &SplitOptions::default()).remove(0);
+        part.user_data.header[5] = 3;
+        assert_eq!(
+            Reassembler::new(Duration::from_secs(1)).push(part),
+            Err(Gsm7Error::InvalidConcatenation {
+                sequence: 3,
+                total: 2,
+            })
+        );
+    }
+}
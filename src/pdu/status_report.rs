@@ -0,0 +1,395 @@
+//! SMS-STATUS-REPORT (3GPP TS 23.040 §9.2.2.3).
+
+use super::{Reader, SmsAddress, SmsTimestamp, UserData, expect_mti, user_data_coding};
+use crate::{DataCodingScheme, Result, hex};
+
+/// TP-MTI for SMS-STATUS-REPORT.
+const MTI_STATUS_REPORT: u8 = 0b10;
+
+/// TP-PI bits.
+const PI_PID: u8 = 0x01;
+const PI_DCS: u8 = 0x02;
+const PI_UDL: u8 = 0x04;
+const PI_EXTENSION: u8 = 0x80;
+
+/// What the status report is about (TP-SRQ).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatusReportQualifier {
+    /// The result of an SMS-SUBMIT.
+    #[default]
+    Submit,
+    /// The result of an SMS-COMMAND.
+    Command,
+}
+
+/// Broad outcome of a TP-ST value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusCategory {
+    /// The transaction is complete: delivered, or handled by the service centre.
+    Completed,
+    /// Temporary error; the service centre keeps trying.
+    TemporaryRetrying,
+    /// Permanent error; the service centre has given up.
+    Permanent,
+    /// Temporary error, but the service centre is not making more attempts.
+    TemporaryNotRetrying,
+    /// Reserved value.
+    Reserved,
+}
+
+/// TP-ST: status of a previously submitted message (3GPP TS 23.040 §9.2.3.15).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransferStatus(pub u8);
+
+impl TransferStatus {
+    /// Short message received by the SME.
+    pub const DELIVERED: TransferStatus = TransferStatus(0x00);
+
+    /// The category of the status.
+    pub fn category(self) -> StatusCategory {
+        match self.0 {
+            0x00..=0x1F => StatusCategory::Completed,
+            0x20..=0x3F => StatusCategory::TemporaryRetrying,
+            0x40..=0x5F => StatusCategory::Permanent,
+            0x60..=0x7F => StatusCategory::TemporaryNotRetrying,
+            _ => StatusCategory::Reserved,
+        }
+    }
+
+    /// Whether the service centre will send no further status for the message.
+    pub fn is_final(self) -> bool {
+        !matches!(self.category(), StatusCategory::TemporaryRetrying)
+    }
+
+    /// Whether the message reached the recipient.
+    pub fn is_delivered(self) -> bool {
+        self.0 == 0x00
+    }
+
+    /// Meaning of the status as given in the specification.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use gsm7_alt::pdu::{StatusCategory, TransferStatus};
+    ///
+    /// let status = TransferStatus(0x46);
+    /// assert_eq!(status.category(), StatusCategory::Permanent);
+    /// assert_eq!(status.description(), "SM validity period expired");
+    /// ```
+    pub fn description(self) -> &'static str {
+        match self.0 {
+            0x00 => "Short message received by the SME",
+            0x01 => {
+                "Short message forwarded by the SC to the SME but the SC is unable to confirm delivery"
+            }
+            0x02 => "Short message replaced by the SC",
+            0x10..=0x1F | 0x30..=0x3F | 0x50..=0x5F | 0x70..=0x7F => "SC specific",
+            0x20 | 0x60 => "Congestion",
+            0x21 | 0x61 => "SME busy",
+            0x22 | 0x62 => "No response from SME",
+            0x23 | 0x63 => "Service rejected",
+            0x24 | 0x44 | 0x64 => "Quality of service not available",
+            0x25 | 0x65 => "Error in SME",
+            0x40 => "Remote procedure error",
+            0x41 => "Incompatible destination",
+            0x42 => "Connection rejected by SME",
+            0x43 => "Not obtainable",
+            0x45 => "No interworking available",
+            0x46 => "SM validity period expired",
+            0x47 => "SM deleted by originating SME",
+            0x48 => "SM deleted by SC administration",
+            0x49 => "SM does not exist",
+            _ => "Reserved",
+        }
+    }
+}
+
+/// An SMS-STATUS-REPORT TPDU, sent from the service centre to the handset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmsStatusReport {
+    /// More messages are waiting in the service centre (TP-MMS clear).
+    pub more_messages_to_send: bool,
+    /// TP-LP: the report was forwarded or is a spawned message.
+    pub loop_prevention: bool,
+    /// TP-SRQ: whether this reports on a submit or a command.
+    pub qualifier: StatusReportQualifier,
+    /// TP-MR of the message the report is about.
+    pub message_reference: u8,
+    /// TP-RA: recipient address of that message.
+    pub recipient: SmsAddress,
+    /// TP-SCTS: when the service centre received the message.
+    pub service_centre_timestamp: SmsTimestamp,
+    /// TP-DT: when the status was reached, e.g. the delivery time.
+    pub discharge_time: SmsTimestamp,
+    /// TP-ST: the status.
+    pub status: TransferStatus,
+    /// TP-PID, if present.
+    pub protocol_identifier: Option<u8>,
+    /// TP-DCS, if present.
+    pub data_coding_scheme: Option<DataCodingScheme>,
+    /// TP-UD, if present; decode it with [`UserData::text`].
+    pub user_data: Option<UserData>,
+}
+
+impl SmsStatusReport {
+    /// Parse an SMS-STATUS-REPORT TPDU.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The TPDU, without a service centre address
+    ///
+    /// # Returns
+    ///
+    /// The parsed report.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::TruncatedPdu` if the PDU ends early and
+    /// `Gsm7Error::InvalidPduField` if a field cannot be interpreted, both with
+    /// the byte offset of the problem.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use gsm7_alt::pdu::{SmsStatusReport, StatusCategory};
+    ///
+    /// let report = SmsStatusReport::from_hex("06420B916407281553F8521061214300405210612153124000")?;
+    /// assert_eq!(report.message_reference, 0x42);
+    /// assert_eq!(report.recipient.value, "46708251358");
+    /// assert!(report.status.is_delivered());
+    /// assert_eq!(report.discharge_time.to_string(), "2025-01-16T12:35:21+01:00");
+    /// assert_eq!(report.status.category(), StatusCategory::Completed);
+    /// # Ok::<(), gsm7_alt::Gsm7Error>(())
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+
+        let first = reader.u8("first octet")?;
+        expect_mti(first, MTI_STATUS_REPORT, "SMS-STATUS-REPORT")?;
+        let message_reference = reader.u8("TP-MR")?;
+        let recipient = SmsAddress::read(&mut reader, "TP-RA")?;
+        let service_centre_timestamp = SmsTimestamp::read(&mut reader, "TP-SCTS")?;
+        let discharge_time = SmsTimestamp::read(&mut reader, "TP-DT")?;
+        let status = TransferStatus(reader.u8("TP-ST")?);
+
+        let mut report = Self {
+            more_messages_to_send: first & 0x04 == 0,
+            loop_prevention: first & 0x08 != 0,
+            qualifier: if first & 0x20 == 0 {
+                StatusReportQualifier::Submit
+            } else {
+                StatusReportQualifier::Command
+            },
+            message_reference,
+            recipient,
+            service_centre_timestamp,
+            discharge_time,
+            status,
+            protocol_identifier: None,
+            data_coding_scheme: None,
+            user_data: None,
+        };
+
+        // TP-PI and the fields it announces are optional
+        if reader.is_empty() {
+            return Ok(report);
+        }
+        let parameters = reader.u8("TP-PI")?;
+        let mut extension = parameters;
+        while extension & PI_EXTENSION != 0 {
+            extension = reader.u8("TP-PI")?;
+        }
+        if parameters & PI_PID != 0 {
+            report.protocol_identifier = Some(reader.u8("TP-PID")?);
+        }
+        let dcs_offset = reader.offset;
+        if parameters & PI_DCS != 0 {
+            report.data_coding_scheme = Some(DataCodingScheme::from_octet(reader.u8("TP-DCS")?));
+        }
+        if parameters & PI_UDL != 0 {
+            let dcs = report.data_coding_scheme.unwrap_or(DataCodingScheme::GSM7);
+            let coding = user_data_coding(dcs, dcs_offset)?;
+            report.user_data = Some(UserData::read(&mut reader, coding, first & 0x40 != 0)?);
+        }
+        reader.finish("TP-UD")?;
+
+        Ok(report)
+    }
+
+    /// Parse an SMS-STATUS-REPORT TPDU from a hex string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidHex` if `hex` is not a hex string, and
+    /// otherwise the errors of [`from_bytes`](Self::from_bytes).
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        Self::from_bytes(&hex::decode(hex)?)
+    }
+
+    /// Serialize the TPDU.
+    ///
+    /// TP-PI is written only when a PID, DCS or user data is present. The DCS
+    /// must agree with the user data coding; without one, receivers assume
+    /// the GSM 7-bit default alphabet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidAddress` if the recipient cannot be encoded,
+    /// and `Gsm7Error::UserDataTooLong` if the user data is too long.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut first = MTI_STATUS_REPORT;
+        if !self.more_messages_to_send {
+            first |= 0x04;
+        }
+        if self.loop_prevention {
+            first |= 0x08;
+        }
+        if self.qualifier == StatusReportQualifier::Command {
+            first |= 0x20;
+        }
+        if self.user_data.as_ref().is_some_and(UserData::has_header) {
+            first |= 0x40;
+        }
+
+        let mut bytes = vec![first, self.message_reference];
+        bytes.extend(self.recipient.to_bytes()?);
+        bytes.extend_from_slice(&self.service_centre_timestamp.to_octets());
+        bytes.extend_from_slice(&self.discharge_time.to_octets());
+        bytes.push(self.status.0);
+
+        let mut parameters = 0;
+        if self.protocol_identifier.is_some() {
+            parameters |= PI_PID;
+        }
+        if self.data_coding_scheme.is_some() {
+            parameters |= PI_DCS;
+        }
+        if self.user_data.is_some() {
+            parameters |= PI_UDL;
+        }
+        if parameters != 0 {
+            bytes.push(parameters);
+            bytes.extend(self.protocol_identifier);
+            bytes.extend(self.data_coding_scheme.map(DataCodingScheme::to_octet));
+            if let Some(user_data) = &self.user_data {
+                user_data.write(&mut bytes)?;
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Serialize the TPDU as an uppercase hex string.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`to_bytes`](Self::to_bytes).
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(hex::encode(&self.to_bytes()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Gsm7Error;
+
+    fn report() -> SmsStatusReport {
+        SmsStatusReport {
+            more_messages_to_send: false,
+            loop_prevention: false,
+            qualifier: StatusReportQualifier::Submit,
+            message_reference: 0x42,
+            recipient: SmsAddress::new("+46708251358").unwrap(),
+            service_centre_timestamp: SmsTimestamp::from_octets([
+                0x52, 0x10, 0x61, 0x21, 0x43, 0x00, 0x40,
+            ])
+            .unwrap(),
+            discharge_time: SmsTimestamp::from_octets([0x52, 0x10, 0x61, 0x21, 0x53, 0x12, 0x40])
+                .unwrap(),
+            status: TransferStatus::DELIVERED,
+            protocol_identifier: None,
+            data_coding_scheme: None,
+            user_data: None,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_without_parameters() {
+        let report = report();
+        let hex = report.to_hex().unwrap();
+        assert_eq!(hex, "06420B916407281553F8521061214300405210612153124000");
+        assert_eq!(SmsStatusReport::from_hex(&hex).unwrap(), report);
+    }
+
+    #[test]
+    fn test_roundtrip_with_parameters() {
+        let mut report = report();
+        report.qualifier = StatusReportQualifier::Command;
+        report.status = TransferStatus(0x46);
+        report.protocol_identifier = Some(0x00);
+        report.data_coding_scheme = Some(DataCodingScheme::UCS2);
+        report.user_data = Some(UserData {
+            coding: crate::CharacterSet::Ucs2,
+            header: Vec::new(),
+            payload: vec![0x04, 0x1F],
+            length: 2,
+        });
+
+        let bytes = report.to_bytes().unwrap();
+        assert_eq!(bytes[0], 0x26);
+        assert_eq!(&bytes[25..], [0x07, 0x00, 0x08, 0x02, 0x04, 0x1F]);
+
+        let parsed = SmsStatusReport::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, report);
+        assert_eq!(
+            parsed.user_data.unwrap().text().unwrap().as_deref(),
+            Some("П")
+        );
+        assert!(parsed.status.is_final());
+        assert!(!parsed.status.is_delivered());
+    }
+
+    #[test]
+    fn test_extended_parameter_indicator() {
+        let mut bytes = report().to_bytes().unwrap();
+        bytes.extend_from_slice(&[0x81, 0x00, 0x7F]);
+        let parsed = SmsStatusReport::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.protocol_identifier, Some(0x7F));
+    }
+
+    #[test]
+    fn test_status_categories() {
+        assert_eq!(
+            TransferStatus(0x21).category(),
+            StatusCategory::TemporaryRetrying
+        );
+        assert!(!TransferStatus(0x21).is_final());
+        assert_eq!(TransferStatus(0x21).description(), "SME busy");
+        assert_eq!(
+            TransferStatus(0x62).category(),
+            StatusCategory::TemporaryNotRetrying
+        );
+        assert_eq!(TransferStatus(0x15).description(), "SC specific");
+        assert_eq!(TransferStatus(0x80).category(), StatusCategory::Reserved);
+        assert_eq!(TransferStatus(0x4A).description(), "Reserved");
+    }
+
+    #[test]
+    fn test_wrong_message_type() {
+        assert!(matches!(
+            SmsStatusReport::from_hex("040BC87238880900F10000"),
+            Err(Gsm7Error::InvalidPduField {
+                field: "TP-MTI",
+                ..
+            })
+        ));
+        assert_eq!(
+            SmsStatusReport::from_hex("06420B916407281553F852106121430040"),
+            Err(Gsm7Error::TruncatedPdu {
+                field: "TP-DT",
+                offset: 17,
+            })
+        );
+    }
+}
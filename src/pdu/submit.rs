@@ -0,0 +1,331 @@
+//! SMS-SUBMIT (3GPP TS 23.040 §9.2.2.2).
+
+use super::{Reader, SmsAddress, UserData, ValidityPeriod, expect_mti, user_data_coding};
+use crate::{DataCodingScheme, Result, SeptetFormat, SplitOptions, hex, split_into_segments};
+
+/// TP-MTI for SMS-SUBMIT.
+const MTI_SUBMIT: u8 = 0b01;
+
+/// An SMS-SUBMIT TPDU, sent from the handset to the service centre.
+///
+/// TP-MTI is always SMS-SUBMIT and TP-UDHI follows from the user data, so
+/// neither is a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmsSubmit {
+    /// TP-RD: ask the service centre to reject a duplicate of a pending message.
+    pub reject_duplicates: bool,
+    /// TP-VP, or `None` to leave it out (TP-VPF 00).
+    pub validity_period: Option<ValidityPeriod>,
+    /// TP-SRR: request a status report.
+    pub status_report_request: bool,
+    /// TP-RP: a reply path is set.
+    pub reply_path: bool,
+    /// TP-MR: message reference.
+    pub message_reference: u8,
+    /// TP-DA: destination address.
+    pub destination: SmsAddress,
+    /// TP-PID: protocol identifier.
+    pub protocol_identifier: u8,
+    /// TP-DCS: data coding scheme; must agree with the user data coding.
+    pub data_coding_scheme: DataCodingScheme,
+    /// TP-UD, with TP-UDL.
+    pub user_data: UserData,
+}
+
+impl SmsSubmit {
+    /// Create an SMS-SUBMIT with default flags, PID 0 and a general data
+    /// coding DCS for the user data coding.
+    pub fn new(destination: SmsAddress, user_data: UserData) -> Self {
+        Self {
+            reject_duplicates: false,
+            validity_period: None,
+            status_report_request: false,
+            reply_path: false,
+            message_reference: 0,
+            destination,
+            protocol_identifier: 0,
+            data_coding_scheme: DataCodingScheme::general(user_data.coding),
+            user_data,
+        }
+    }
+
+    /// Create one SMS-SUBMIT per part of `text`, split with
+    /// [`split_into_segments`].
+    ///
+    /// GSM 7-bit payloads are always packed, whatever `options.septet_format`
+    /// says. All parts get message reference 0; give each part its own TP-MR
+    /// before sending.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The recipient
+    /// * `text` - The message text
+    /// * `options` - Encoding and concatenation reference
+    ///
+    /// # Returns
+    ///
+    /// One TPDU per part, in sending order.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`split_into_segments`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use gsm7_alt::SplitOptions;
+    /// use gsm7_alt::pdu::{SmsAddress, SmsSubmit, ValidityPeriod};
+    ///
+    /// let destination = SmsAddress::new("+46708251358")?;
+    /// let mut parts = SmsSubmit::from_text(destination, "hellohello", &SplitOptions::default())?;
+    /// let mut submit = parts.remove(0);
+    /// submit.validity_period = Some(ValidityPeriod::Relative(0xAA));
+    /// assert_eq!(submit.to_hex()?, "11000B916407281553F80000AA0AE8329BFD4697D9EC37");
+    /// # Ok::<(), gsm7_alt::Gsm7Error>(())
+    /// ```
+    pub fn from_text(
+        destination: SmsAddress,
+        text: &str,
+        options: &SplitOptions,
+    ) -> Result<Vec<Self>> {
+        let options = SplitOptions {
+            septet_format: SeptetFormat::Packed,
+            ..options.clone()
+        };
+        Ok(split_into_segments(text, &options)?
+            .iter()
+            .map(|part| Self::new(destination.clone(), UserData::from_part(part)))
+            .collect())
+    }
+
+    /// Parse an SMS-SUBMIT TPDU, as a service centre receives it.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The TPDU, without a service centre address
+    ///
+    /// # Returns
+    ///
+    /// The parsed message; decode its text with [`UserData::text`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::TruncatedPdu` if the PDU ends early and
+    /// `Gsm7Error::InvalidPduField` if a field cannot be interpreted, both with
+    /// the byte offset of the problem.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+
+        let first = reader.u8("first octet")?;
+        expect_mti(first, MTI_SUBMIT, "SMS-SUBMIT")?;
+        let message_reference = reader.u8("TP-MR")?;
+        let destination = SmsAddress::read(&mut reader, "TP-DA")?;
+        let protocol_identifier = reader.u8("TP-PID")?;
+        let dcs_offset = reader.offset;
+        let data_coding_scheme = DataCodingScheme::from_octet(reader.u8("TP-DCS")?);
+        let coding = user_data_coding(data_coding_scheme, dcs_offset)?;
+        let validity_period = ValidityPeriod::read(&mut reader, first)?;
+        let user_data = UserData::read(&mut reader, coding, first & 0x40 != 0)?;
+        reader.finish("TP-UD")?;
+
+        Ok(Self {
+            reject_duplicates: first & 0x04 != 0,
+            validity_period,
+            status_report_request: first & 0x20 != 0,
+            reply_path: first & 0x80 != 0,
+            message_reference,
+            destination,
+            protocol_identifier,
+            data_coding_scheme,
+            user_data,
+        })
+    }
+
+    /// Parse an SMS-SUBMIT TPDU from a hex string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidHex` if `hex` is not a hex string, and
+    /// otherwise the errors of [`from_bytes`](Self::from_bytes).
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        Self::from_bytes(&hex::decode(hex)?)
+    }
+
+    /// Serialize the TPDU.
+    ///
+    /// The result has no service centre address in front; AT+CMGS in PDU mode
+    /// usually expects one, `00` for the modem's default.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidAddress` if the destination cannot be
+    /// encoded, and `Gsm7Error::UserDataTooLong` if the user data exceeds 140
+    /// octets.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut first = MTI_SUBMIT;
+        if self.reject_duplicates {
+            first |= 1 << 2;
+        }
+        if let Some(validity_period) = &self.validity_period {
+            first |= validity_period.format_bits();
+        }
+        if self.status_report_request {
+            first |= 1 << 5;
+        }
+        if self.user_data.has_header() {
+            first |= 1 << 6;
+        }
+        if self.reply_path {
+            first |= 1 << 7;
+        }
+
+        let mut bytes = vec![first, self.message_reference];
+        bytes.extend(self.destination.to_bytes()?);
+        bytes.push(self.protocol_identifier);
+        bytes.push(self.data_coding_scheme.to_octet());
+        if let Some(validity_period) = self.validity_period {
+            bytes.extend(validity_period.to_octets());
+        }
+        self.user_data.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Serialize the TPDU as an uppercase hex string.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`to_bytes`](Self::to_bytes).
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(hex::encode(&self.to_bytes()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdu::{EnhancedPeriod, EnhancedValidityPeriod, SmsTimestamp};
+    use crate::{CharacterSet, ConcatReference, Gsm7Error, NationalLanguage, SmsEncoding};
+
+    fn destination() -> SmsAddress {
+        SmsAddress::new("+46708251358").unwrap()
+    }
+
+    #[test]
+    fn test_minimal_submit() {
+        let submit = SmsSubmit::from_text(destination(), "hellohello", &SplitOptions::default())
+            .unwrap()
+            .remove(0);
+        assert_eq!(
+            submit.to_hex().unwrap(),
+            "01000B916407281553F800000AE8329BFD4697D9EC37"
+        );
+    }
+
+    #[test]
+    fn test_flags() {
+        let mut submit = SmsSubmit::new(destination(), UserData::binary(Vec::new(), vec![0xAB]));
+        submit.reject_duplicates = true;
+        submit.status_report_request = true;
+        submit.reply_path = true;
+        submit.message_reference = 0x42;
+        submit.validity_period = Some(ValidityPeriod::Absolute(
+            SmsTimestamp::from_octets([0x21, 0x40, 0x31, 0x21, 0x43, 0x65, 0x00]).unwrap(),
+        ));
+        let bytes = submit.to_bytes().unwrap();
+        assert_eq!(bytes[0], 0b1011_1101);
+        assert_eq!(bytes[1], 0x42);
+        assert_eq!(&bytes[10..12], [0x00, 0x04]);
+        assert_eq!(&bytes[12..19], [0x21, 0x40, 0x31, 0x21, 0x43, 0x65, 0x00]);
+        assert_eq!(&bytes[19..], [0x01, 0xAB]);
+        assert_eq!(SmsSubmit::from_bytes(&bytes).unwrap(), submit);
+    }
+
+    #[test]
+    fn test_parse_roundtrip() {
+        let options = SplitOptions {
+            reference: ConcatReference::SixteenBit(0x1234),
+            ..Default::default()
+        };
+        for (index, mut submit) in
+            SmsSubmit::from_text(destination(), &"Grüße € ".repeat(30), &options)
+                .unwrap()
+                .into_iter()
+                .enumerate()
+        {
+            submit.message_reference = index as u8;
+            submit.validity_period = Some(ValidityPeriod::Enhanced(EnhancedValidityPeriod {
+                single_shot: false,
+                period: EnhancedPeriod::Seconds(30),
+            }));
+            let parsed = SmsSubmit::from_hex(&submit.to_hex().unwrap()).unwrap();
+            assert_eq!(parsed, submit);
+        }
+
+        let hex = "11000B916407281553F80000AA0AE8329BFD4697D9EC37";
+        let parsed = SmsSubmit::from_hex(hex).unwrap();
+        assert_eq!(parsed.validity_period, Some(ValidityPeriod::Relative(0xAA)));
+        assert_eq!(
+            parsed.user_data.text().unwrap().as_deref(),
+            Some("hellohello")
+        );
+        assert!(matches!(
+            SmsSubmit::from_hex("19000B916407281553F80000AA0AE8329BFD4697D9EC37"),
+            Err(Gsm7Error::InvalidPduField {
+                field: "TP-VP",
+                offset: 12,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_concatenated_parts_set_udhi() {
+        let options = SplitOptions {
+            reference: ConcatReference::EightBit(0x07),
+            ..Default::default()
+        };
+        let parts = SmsSubmit::from_text(destination(), &"x".repeat(200), &options).unwrap();
+        assert_eq!(parts.len(), 2);
+
+        let bytes = parts[0].to_bytes().unwrap();
+        assert_eq!(bytes[0], 0x41);
+        // TP-UDL 160 septets: 6 header octets + 1 fill bit = 7 septets, then 153
+        assert_eq!(bytes[12], 160);
+        assert_eq!(&bytes[13..19], [0x05, 0x00, 0x03, 0x07, 0x02, 0x01]);
+        assert_eq!(bytes.len(), 13 + 140);
+    }
+
+    #[test]
+    fn test_ucs2_and_national_dcs() {
+        let submit = SmsSubmit::from_text(destination(), "Привет", &SplitOptions::default())
+            .unwrap()
+            .remove(0);
+        assert_eq!(submit.user_data.coding, CharacterSet::Ucs2);
+        assert_eq!(submit.data_coding_scheme, DataCodingScheme::UCS2);
+        assert_eq!(submit.to_bytes().unwrap()[12], 12);
+
+        let options = SplitOptions {
+            encoding: Some(SmsEncoding::Gsm7 {
+                locking_shift: Some(NationalLanguage::Turkish),
+                single_shift: None,
+            }),
+            ..Default::default()
+        };
+        let submit = SmsSubmit::from_text(destination(), "Şimdi", &options)
+            .unwrap()
+            .remove(0);
+        let bytes = submit.to_bytes().unwrap();
+        assert_eq!(bytes[0] & 0x40, 0x40);
+        assert_eq!(bytes[11], 0x00);
+        assert_eq!(&bytes[13..17], [0x03, 0x25, 0x01, 0x01]);
+    }
+
+    #[test]
+    fn test_user_data_too_long() {
+        let submit = SmsSubmit::new(destination(), UserData::binary(Vec::new(), vec![0; 141]));
+        assert_eq!(
+            submit.to_bytes(),
+            Err(Gsm7Error::UserDataTooLong { octets: 141 })
+        );
+    }
+}
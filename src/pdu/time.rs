@@ -0,0 +1,419 @@
+//! TP-SCTS and absolute TP-VP timestamps (3GPP TS 23.040 §9.2.3.11).
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::Reader;
+use crate::{Gsm7Error, Result};
+
+/// Seconds in a quarter hour, the unit of the timezone offset.
+const QUARTER_HOUR_SECONDS: i64 = 15 * 60;
+
+/// Largest timezone offset the BCD field can hold, in quarter hours.
+const MAX_OFFSET_QUARTERS: i8 = 79;
+
+/// A calendar time in UTC, to the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UtcDateTime {
+    /// Year, e.g. 2025.
+    pub year: i32,
+    /// Month, 1–12.
+    pub month: u8,
+    /// Day of the month, 1–31.
+    pub day: u8,
+    /// Hour, 0–23.
+    pub hour: u8,
+    /// Minute, 0–59.
+    pub minute: u8,
+    /// Second, 0–59.
+    pub second: u8,
+}
+
+impl UtcDateTime {
+    /// The time `seconds` after the Unix epoch (negative for earlier times).
+    pub fn from_unix_seconds(seconds: i64) -> Self {
+        let days = seconds.div_euclid(86_400);
+        let time = seconds.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: (time / 3600) as u8,
+            minute: (time / 60 % 60) as u8,
+            second: (time % 60) as u8,
+        }
+    }
+
+    /// Seconds since the Unix epoch.
+    pub fn unix_seconds(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86_400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+
+    /// Convert a `SystemTime`, truncating sub-second precision.
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_secs() as i64,
+            Err(before) => -(before.duration().as_secs_f64().ceil() as i64),
+        };
+        Self::from_unix_seconds(seconds)
+    }
+
+    /// Convert to a `SystemTime`.
+    pub fn to_system_time(&self) -> SystemTime {
+        let seconds = self.unix_seconds();
+        if seconds >= 0 {
+            UNIX_EPOCH + Duration::from_secs(seconds as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+        }
+    }
+}
+
+impl fmt::Display for UtcDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// A service centre timestamp: local time with its offset from UTC.
+///
+/// The field stores a two-digit year; 90–99 read as 1990–1999 and 00–89 as
+/// 2000–2089.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::pdu::SmsTimestamp;
+///
+/// let timestamp = SmsTimestamp::from_octets([0x52, 0x10, 0x61, 0x21, 0x43, 0x00, 0x80])?;
+/// assert_eq!(timestamp.to_string(), "2025-01-16T12:34:00+02:00");
+/// assert_eq!(timestamp.to_utc().to_string(), "2025-01-16T10:34:00Z");
+/// assert_eq!(timestamp.to_octets(), [0x52, 0x10, 0x61, 0x21, 0x43, 0x00, 0x80]);
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SmsTimestamp {
+    /// Year, 1990–2089.
+    pub year: u16,
+    /// Month, 1–12.
+    pub month: u8,
+    /// Day of the month, 1–31.
+    pub day: u8,
+    /// Hour, 0–23.
+    pub hour: u8,
+    /// Minute, 0–59.
+    pub minute: u8,
+    /// Second, 0–59.
+    pub second: u8,
+    /// Offset of the local time from UTC in quarter hours, -79 to 79.
+    pub offset_quarters: i8,
+}
+
+impl SmsTimestamp {
+    /// Decode the seven swapped-BCD octets of a TP-SCTS or absolute TP-VP.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidTimestamp` if a digit is not BCD or a field is
+    /// out of range.
+    pub fn from_octets(octets: [u8; 7]) -> Result<Self> {
+        let mut digits = [0u8; 6];
+        for (index, (digit, &octet)) in digits.iter_mut().zip(&octets).enumerate() {
+            *digit = swapped_bcd(octet).ok_or_else(|| Gsm7Error::InvalidTimestamp {
+                reason: format!("octet {} (0x{:02X}) is not BCD", index, octet),
+            })?;
+        }
+
+        // The sign of the offset sits in bit 3, the high bit of the tens digit
+        let zone = octets[6] & !0x08;
+        let quarters = swapped_bcd(zone).ok_or_else(|| Gsm7Error::InvalidTimestamp {
+            reason: format!("timezone octet 0x{:02X} is not BCD", octets[6]),
+        })? as i8;
+
+        let [year, month, day, hour, minute, second] = digits;
+        let timestamp = Self {
+            year: if year >= 90 { 1900 } else { 2000 } + u16::from(year),
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            offset_quarters: if octets[6] & 0x08 != 0 {
+                -quarters
+            } else {
+                quarters
+            },
+        };
+        timestamp.validate()?;
+        Ok(timestamp)
+    }
+
+    /// Read a seven-octet timestamp field.
+    pub(super) fn read(reader: &mut Reader, field: &'static str) -> Result<Self> {
+        let offset = reader.offset;
+        Self::from_octets(reader.array(field)?).map_err(|err| Gsm7Error::InvalidPduField {
+            field,
+            offset,
+            reason: err.to_string(),
+        })
+    }
+
+    /// Encode as seven swapped-BCD octets.
+    ///
+    /// Fields are expected to be in range; see [`from_utc`](Self::from_utc)
+    /// for a checked constructor.
+    pub fn to_octets(&self) -> [u8; 7] {
+        let mut zone = to_swapped_bcd(self.offset_quarters.unsigned_abs());
+        if self.offset_quarters < 0 {
+            zone |= 0x08;
+        }
+        [
+            to_swapped_bcd((self.year % 100) as u8),
+            to_swapped_bcd(self.month),
+            to_swapped_bcd(self.day),
+            to_swapped_bcd(self.hour),
+            to_swapped_bcd(self.minute),
+            to_swapped_bcd(self.second),
+            zone,
+        ]
+    }
+
+    /// The timestamp for a UTC time, expressed in local time at the given offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidTimestamp` if the offset exceeds 79 quarter
+    /// hours or the local year falls outside 1990–2089.
+    pub fn from_utc(utc: UtcDateTime, offset_quarters: i8) -> Result<Self> {
+        if offset_quarters.unsigned_abs() > MAX_OFFSET_QUARTERS as u8 {
+            return Err(Gsm7Error::InvalidTimestamp {
+                reason: format!("offset of {} quarter hours", offset_quarters),
+            });
+        }
+        let local = UtcDateTime::from_unix_seconds(
+            utc.unix_seconds() + i64::from(offset_quarters) * QUARTER_HOUR_SECONDS,
+        );
+        let timestamp = Self {
+            year: u16::try_from(local.year).unwrap_or(0),
+            month: local.month,
+            day: local.day,
+            hour: local.hour,
+            minute: local.minute,
+            second: local.second,
+            offset_quarters,
+        };
+        timestamp.validate()?;
+        Ok(timestamp)
+    }
+
+    /// The timestamp for a `SystemTime`, expressed in local time at the given offset.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`from_utc`](Self::from_utc).
+    pub fn from_system_time(time: SystemTime, offset_quarters: i8) -> Result<Self> {
+        Self::from_utc(UtcDateTime::from_system_time(time), offset_quarters)
+    }
+
+    /// The same instant in UTC.
+    pub fn to_utc(&self) -> UtcDateTime {
+        let local = UtcDateTime {
+            year: i32::from(self.year),
+            month: self.month,
+            day: self.day,
+            hour: self.hour,
+            minute: self.minute,
+            second: self.second,
+        };
+        UtcDateTime::from_unix_seconds(
+            local.unix_seconds() - i64::from(self.offset_quarters) * QUARTER_HOUR_SECONDS,
+        )
+    }
+
+    /// The same instant as a `SystemTime`.
+    pub fn to_system_time(&self) -> SystemTime {
+        self.to_utc().to_system_time()
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, value: u16| {
+            Err(Gsm7Error::InvalidTimestamp {
+                reason: format!("{} {} out of range", field, value),
+            })
+        };
+        if !(1990..=2089).contains(&self.year) {
+            return invalid("year", self.year);
+        }
+        if !(1..=12).contains(&self.month) {
+            return invalid("month", self.month.into());
+        }
+        if self.day == 0 || self.day > days_in_month(self.year, self.month) {
+            return invalid("day", self.day.into());
+        }
+        if self.hour > 23 {
+            return invalid("hour", self.hour.into());
+        }
+        if self.minute > 59 {
+            return invalid("minute", self.minute.into());
+        }
+        if self.second > 59 {
+            return invalid("second", self.second.into());
+        }
+        if self.offset_quarters.unsigned_abs() > MAX_OFFSET_QUARTERS as u8 {
+            return invalid("offset", self.offset_quarters.unsigned_abs().into());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SmsTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.offset_quarters < 0 { '-' } else { '+' };
+        let minutes = u16::from(self.offset_quarters.unsigned_abs()) * 15;
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            sign,
+            minutes / 60,
+            minutes % 60
+        )
+    }
+}
+
+/// Value of a swapped-BCD octet (units digit in the high nibble), if both
+/// nibbles are decimal digits.
+fn swapped_bcd(octet: u8) -> Option<u8> {
+    let (tens, units) = (octet & 0x0F, octet >> 4);
+    (tens < 10 && units < 10).then_some(tens * 10 + units)
+}
+
+fn to_swapped_bcd(value: u8) -> u8 {
+    (value % 10) << 4 | (value / 10 % 10)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian date for a count of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u8;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_negative_offset() {
+        // 1999-03-29 15:16:59, -5:00 (20 quarters, sign bit set)
+        let timestamp =
+            SmsTimestamp::from_octets([0x99, 0x30, 0x92, 0x51, 0x61, 0x95, 0x0A]).unwrap();
+        assert_eq!(timestamp.year, 1999);
+        assert_eq!(timestamp.offset_quarters, -20);
+        assert_eq!(timestamp.to_string(), "1999-03-29T15:16:59-05:00");
+        assert_eq!(timestamp.to_utc().to_string(), "1999-03-29T20:16:59Z");
+        assert_eq!(
+            timestamp.to_octets(),
+            [0x99, 0x30, 0x92, 0x51, 0x61, 0x95, 0x0A]
+        );
+    }
+
+    #[test]
+    fn test_utc_crosses_day_boundary() {
+        let utc = UtcDateTime {
+            year: 2024,
+            month: 2,
+            day: 28,
+            hour: 22,
+            minute: 30,
+            second: 0,
+        };
+        // +05:30 moves the local time into the leap day
+        let timestamp = SmsTimestamp::from_utc(utc, 22).unwrap();
+        assert_eq!(timestamp.to_string(), "2024-02-29T04:00:00+05:30");
+        assert_eq!(timestamp.to_utc(), utc);
+    }
+
+    #[test]
+    fn test_system_time_roundtrip() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_737_030_840);
+        let timestamp = SmsTimestamp::from_system_time(time, 4).unwrap();
+        assert_eq!(timestamp.to_string(), "2025-01-16T13:34:00+01:00");
+        assert_eq!(timestamp.to_system_time(), time);
+
+        let utc = UtcDateTime::from_unix_seconds(-1);
+        assert_eq!(utc.to_string(), "1969-12-31T23:59:59Z");
+        assert_eq!(UtcDateTime::from_system_time(utc.to_system_time()), utc);
+    }
+
+    #[test]
+    fn test_invalid_octets() {
+        assert!(matches!(
+            SmsTimestamp::from_octets([0x52, 0x10, 0xA1, 0x21, 0x43, 0x00, 0x80]),
+            Err(Gsm7Error::InvalidTimestamp { .. })
+        ));
+        // Month 13
+        assert!(SmsTimestamp::from_octets([0x52, 0x31, 0x61, 0x21, 0x43, 0x00, 0x80]).is_err());
+        // 2025-02-29
+        assert!(SmsTimestamp::from_octets([0x52, 0x20, 0x92, 0x21, 0x43, 0x00, 0x80]).is_err());
+        assert!(SmsTimestamp::from_utc(UtcDateTime::from_unix_seconds(0), 0).is_err());
+        assert!(SmsTimestamp::from_utc(UtcDateTime::from_unix_seconds(1 << 31), 80).is_err());
+    }
+
+    #[test]
+    fn test_civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        for days in [-800_000, -1, 0, 59, 10_956, 20_000, 1_000_000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
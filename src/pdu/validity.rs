@@ -0,0 +1,323 @@
+//! TP-VP validity period formats (3GPP TS 23.040 §9.2.3.12).
+
+use std::time::{Duration, SystemTime};
+
+use super::{Reader, SmsTimestamp};
+use crate::{Gsm7Error, Result};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// TP-VP with its format (TP-VPF).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidityPeriod {
+    /// Relative format: one octet counting from 5 minutes to 63 weeks; see
+    /// [`relative`](Self::relative) and [`duration`](Self::duration).
+    Relative(u8),
+    /// Enhanced format: seven octets, see TS 23.040 §9.2.3.12.3.
+    Enhanced(EnhancedValidityPeriod),
+    /// Absolute format: the time the message expires.
+    Absolute(SmsTimestamp),
+}
+
+/// Enhanced format TP-VP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnhancedValidityPeriod {
+    /// The service centre makes a single delivery attempt only.
+    pub single_shot: bool,
+    /// The period itself.
+    pub period: EnhancedPeriod,
+}
+
+/// Validity period of an enhanced format TP-VP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnhancedPeriod {
+    /// No validity period specified.
+    NotPresent,
+    /// One octet, coded as the relative format.
+    Relative(u8),
+    /// Relative time in seconds, 0–255.
+    Seconds(u8),
+    /// Relative time in hours, minutes and seconds.
+    HoursMinutesSeconds {
+        /// Hours, 0–99.
+        hours: u8,
+        /// Minutes, 0–59.
+        minutes: u8,
+        /// Seconds, 0–59.
+        seconds: u8,
+    },
+    /// Extended or reserved functionality indicator, with the raw octets.
+    Reserved([u8; 7]),
+}
+
+impl ValidityPeriod {
+    /// The shortest relative validity period covering `duration`.
+    ///
+    /// Relative periods count in steps of 5 minutes up to 12 hours, 30 minutes
+    /// up to 24 hours, days up to 30 days and weeks up to 63 weeks; longer
+    /// durations are clamped to 63 weeks.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use gsm7_alt::pdu::ValidityPeriod;
+    ///
+    /// let period = ValidityPeriod::relative(Duration::from_secs(4 * 24 * 3600));
+    /// assert_eq!(period, ValidityPeriod::Relative(0xAA));
+    /// assert_eq!(period.duration(), Some(Duration::from_secs(4 * 24 * 3600)));
+    /// ```
+    pub fn relative(duration: Duration) -> Self {
+        ValidityPeriod::Relative(relative_octet(duration))
+    }
+
+    /// The length of a relative or enhanced period, or `None` for the absolute
+    /// format and enhanced periods without a length.
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            ValidityPeriod::Relative(octet) => Some(relative_duration(*octet)),
+            ValidityPeriod::Enhanced(enhanced) => match enhanced.period {
+                EnhancedPeriod::Relative(octet) => Some(relative_duration(octet)),
+                EnhancedPeriod::Seconds(seconds) => Some(Duration::from_secs(seconds.into())),
+                EnhancedPeriod::HoursMinutesSeconds {
+                    hours,
+                    minutes,
+                    seconds,
+                } => Some(Duration::from_secs(
+                    u64::from(hours) * HOUR + u64::from(minutes) * MINUTE + u64::from(seconds),
+                )),
+                EnhancedPeriod::NotPresent | EnhancedPeriod::Reserved(_) => None,
+            },
+            ValidityPeriod::Absolute(_) => None,
+        }
+    }
+
+    /// When a message submitted at `submitted` expires, or `None` if the
+    /// period does not say.
+    pub fn expires_at(&self, submitted: SystemTime) -> Option<SystemTime> {
+        match self {
+            ValidityPeriod::Absolute(timestamp) => Some(timestamp.to_system_time()),
+            _ => self.duration().map(|duration| submitted + duration),
+        }
+    }
+
+    /// TP-VPF bits (4–3 of the first octet).
+    pub(super) fn format_bits(&self) -> u8 {
+        match self {
+            ValidityPeriod::Relative(_) => 0b10 << 3,
+            ValidityPeriod::Enhanced(_) => 0b01 << 3,
+            ValidityPeriod::Absolute(_) => 0b11 << 3,
+        }
+    }
+
+    /// The TP-VP octets.
+    pub(super) fn to_octets(self) -> Vec<u8> {
+        match self {
+            ValidityPeriod::Relative(octet) => vec![octet],
+            ValidityPeriod::Enhanced(enhanced) => enhanced.to_octets().to_vec(),
+            ValidityPeriod::Absolute(timestamp) => timestamp.to_octets().to_vec(),
+        }
+    }
+
+    /// Read the TP-VP announced by the TP-VPF bits of a first octet.
+    pub(super) fn read(reader: &mut Reader, first: u8) -> Result<Option<Self>> {
+        let offset = reader.offset;
+        let invalid = |err: Gsm7Error| Gsm7Error::InvalidPduField {
+            field: "TP-VP",
+            offset,
+            reason: err.to_string(),
+        };
+        Ok(match (first >> 3) & 0b11 {
+            0b00 => None,
+            0b10 => Some(ValidityPeriod::Relative(reader.u8("TP-VP")?)),
+            0b01 => Some(ValidityPeriod::Enhanced(
+                EnhancedValidityPeriod::from_octets(reader.array("TP-VP")?).map_err(invalid)?,
+            )),
+            _ => Some(ValidityPeriod::Absolute(
+                SmsTimestamp::from_octets(reader.array("TP-VP")?).map_err(invalid)?,
+            )),
+        })
+    }
+}
+
+impl EnhancedValidityPeriod {
+    /// Decode the seven enhanced format octets.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidTimestamp` if the hours, minutes and seconds
+    /// form is not valid BCD.
+    pub fn from_octets(octets: [u8; 7]) -> Result<Self> {
+        let functionality = octets[0];
+        let period = if functionality & 0x80 != 0 {
+            // Extension octets follow; the format is not defined
+            EnhancedPeriod::Reserved(octets)
+        } else {
+            match functionality & 0b111 {
+                0b000 => EnhancedPeriod::NotPresent,
+                0b001 => EnhancedPeriod::Relative(octets[1]),
+                0b010 => EnhancedPeriod::Seconds(octets[1]),
+                0b011 => {
+                    let digit = |octet: u8| {
+                        let (tens, units) = (octet & 0x0F, octet >> 4);
+                        (tens < 10 && units < 10)
+                            .then_some(tens * 10 + units)
+                            .ok_or(Gsm7Error::InvalidTimestamp {
+                                reason: format!("enhanced TP-VP octet 0x{:02X} is not BCD", octet),
+                            })
+                    };
+                    EnhancedPeriod::HoursMinutesSeconds {
+                        hours: digit(octets[1])?,
+                        minutes: digit(octets[2])?,
+                        seconds: digit(octets[3])?,
+                    }
+                }
+                _ => EnhancedPeriod::Reserved(octets),
+            }
+        };
+        Ok(Self {
+            single_shot: functionality & 0x40 != 0,
+            period,
+        })
+    }
+
+    /// Encode as seven octets, zero-padded.
+    pub fn to_octets(&self) -> [u8; 7] {
+        let swapped = |value: u8| (value % 10) << 4 | (value / 10 % 10);
+        let mut octets = match self.period {
+            EnhancedPeriod::NotPresent => [0; 7],
+            EnhancedPeriod::Relative(octet) => [0b001, octet, 0, 0, 0, 0, 0],
+            EnhancedPeriod::Seconds(seconds) => [0b010, seconds, 0, 0, 0, 0, 0],
+            EnhancedPeriod::HoursMinutesSeconds {
+                hours,
+                minutes,
+                seconds,
+            } => [
+                0b011,
+                swapped(hours),
+                swapped(minutes),
+                swapped(seconds),
+                0,
+                0,
+                0,
+            ],
+            EnhancedPeriod::Reserved(octets) => octets,
+        };
+        if self.single_shot {
+            octets[0] |= 0x40;
+        } else {
+            octets[0] &= !0x40;
+        }
+        octets
+    }
+}
+
+/// Length of a relative format TP-VP octet.
+fn relative_duration(octet: u8) -> Duration {
+    let octet = u64::from(octet);
+    Duration::from_secs(match octet {
+        0..=143 => (octet + 1) * 5 * MINUTE,
+        144..=167 => 12 * HOUR + (octet - 143) * 30 * MINUTE,
+        168..=196 => (octet - 166) * DAY,
+        _ => (octet - 192) * WEEK,
+    })
+}
+
+/// Smallest relative format TP-VP octet at least as long as `duration`.
+fn relative_octet(duration: Duration) -> u8 {
+    let seconds = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    let steps = |span: u64, step: u64| span.div_ceil(step);
+    let octet = if seconds <= 12 * HOUR {
+        steps(seconds, 5 * MINUTE).max(1) - 1
+    } else if seconds <= DAY {
+        143 + steps(seconds - 12 * HOUR, 30 * MINUTE)
+    } else if seconds <= 30 * DAY {
+        166 + steps(seconds, DAY)
+    } else {
+        (192 + steps(seconds, WEEK)).min(255)
+    };
+    octet as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_durations() {
+        assert_eq!(relative_duration(0), Duration::from_secs(5 * MINUTE));
+        assert_eq!(relative_duration(143), Duration::from_secs(12 * HOUR));
+        assert_eq!(relative_duration(167), Duration::from_secs(DAY));
+        assert_eq!(relative_duration(168), Duration::from_secs(2 * DAY));
+        assert_eq!(relative_duration(196), Duration::from_secs(30 * DAY));
+        assert_eq!(relative_duration(197), Duration::from_secs(5 * WEEK));
+        assert_eq!(relative_duration(255), Duration::from_secs(63 * WEEK));
+    }
+
+    #[test]
+    fn test_relative_rounds_up() {
+        for octet in 0..=255 {
+            let duration = relative_duration(octet);
+            assert_eq!(relative_octet(duration), octet);
+            if octet < 255 {
+                let longer = duration + Duration::from_secs(1);
+                assert_eq!(relative_octet(longer), octet + 1);
+            }
+        }
+        assert_eq!(relative_octet(Duration::ZERO), 0);
+        assert_eq!(relative_octet(Duration::from_secs(100 * WEEK)), 255);
+        // Between 30 days and 5 weeks the next step is 5 weeks
+        assert_eq!(relative_octet(Duration::from_secs(31 * DAY)), 197);
+    }
+
+    #[test]
+    fn test_enhanced_roundtrip() {
+        let enhanced = EnhancedValidityPeriod {
+            single_shot: true,
+            period: EnhancedPeriod::HoursMinutesSeconds {
+                hours: 12,
+                minutes: 34,
+                seconds: 56,
+            },
+        };
+        let octets = enhanced.to_octets();
+        assert_eq!(octets, [0x43, 0x21, 0x43, 0x65, 0, 0, 0]);
+        assert_eq!(
+            EnhancedValidityPeriod::from_octets(octets).unwrap(),
+            enhanced
+        );
+        assert_eq!(
+            ValidityPeriod::Enhanced(enhanced).duration(),
+            Some(Duration::from_secs(12 * HOUR + 34 * MINUTE + 56))
+        );
+
+        let seconds = EnhancedValidityPeriod::from_octets([0x02, 90, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(seconds.period, EnhancedPeriod::Seconds(90));
+        assert!(!seconds.single_shot);
+
+        let reserved = [0x85, 1, 2, 3, 4, 5, 6];
+        let parsed = EnhancedValidityPeriod::from_octets(reserved).unwrap();
+        assert_eq!(parsed.period, EnhancedPeriod::Reserved(reserved));
+        assert_eq!(parsed.to_octets(), reserved);
+    }
+
+    #[test]
+    fn test_expiry() {
+        let submitted = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        assert_eq!(
+            ValidityPeriod::Relative(11).expires_at(submitted),
+            Some(submitted + Duration::from_secs(HOUR))
+        );
+        let absolute =
+            SmsTimestamp::from_octets([0x52, 0x10, 0x61, 0x21, 0x43, 0x00, 0x00]).unwrap();
+        assert_eq!(
+            ValidityPeriod::Absolute(absolute).expires_at(submitted),
+            Some(absolute.to_system_time())
+        );
+        let none = EnhancedValidityPeriod::from_octets([0; 7]).unwrap();
+        assert_eq!(ValidityPeriod::Enhanced(none).expires_at(submitted), None);
+    }
+}
@@ -0,0 +1,88 @@
+//! Cursor over binary PDUs, shared by the SMS and SMPP parsers.
+
+use crate::{Gsm7Error, Result};
+
+/// Cursor over a PDU that reports truncation with the field and byte offset.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pub(crate) offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    pub(crate) fn u8(&mut self, field: &'static str) -> Result<u8> {
+        Ok(self.take(1, field)?[0])
+    }
+
+    /// A big-endian `u16`.
+    pub(crate) fn u16(&mut self, field: &'static str) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.array(field)?))
+    }
+
+    /// A big-endian `u32`.
+    pub(crate) fn u32(&mut self, field: &'static str) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.array(field)?))
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self, field: &'static str) -> Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N, field)?);
+        Ok(array)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    pub(crate) fn take(&mut self, len: usize, field: &'static str) -> Result<&'a [u8]> {
+        let bytes =
+            self.bytes
+                .get(self.offset..self.offset + len)
+                .ok_or(Gsm7Error::TruncatedPdu {
+                    field,
+                    offset: self.bytes.len(),
+                })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// A C-octet string of at most `max` octets, NUL included, read as
+    /// ISO-8859-1.
+    pub(crate) fn c_string(&mut self, field: &'static str, max: usize) -> Result<String> {
+        let rest = &self.bytes[self.offset.min(self.bytes.len())..];
+        let len = rest
+            .iter()
+            .position(|&octet| octet == 0)
+            .ok_or(Gsm7Error::TruncatedPdu {
+                field,
+                offset: self.bytes.len(),
+            })?;
+        if len >= max {
+            return Err(Gsm7Error::InvalidPduField {
+                field,
+                offset: self.offset,
+                reason: format!("{} octets, more than the maximum of {}", len, max - 1),
+            });
+        }
+        let value = self.take(len + 1, field)?[..len]
+            .iter()
+            .map(|&octet| char::from(octet))
+            .collect();
+        Ok(value)
+    }
+
+    /// Fail if anything follows the last field, `field`.
+    pub(crate) fn finish(&self, field: &'static str) -> Result<()> {
+        if self.offset < self.bytes.len() {
+            return Err(Gsm7Error::InvalidPduField {
+                field,
+                offset: self.offset,
+                reason: format!("{} trailing octets", self.bytes.len() - self.offset),
+            });
+        }
+        Ok(())
+    }
+}
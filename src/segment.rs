@@ -0,0 +1,291 @@
+//! SMS segment calculation.
+//!
+//! Billing is per segment, not per byte. A single-part message holds 160
+//! septets or 70 UCS-2 code units; once a message needs concatenation, every
+//! part loses six octets to the concatenation header and holds 153 septets or
+//! 67 code units. National language shift tables take further header space.
+//! Characters are never split across parts, so an escaped GSM character or a
+//! UTF-16 surrogate pair that does not fit moves to the next part whole.
+
+use std::ops::Range;
+
+use crate::{Alphabet, Code, Gsm7Error, Result, SmsEncoding, choose_encoding};
+
+/// One segment's share of the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentBoundary {
+    /// Byte range of the segment's characters in the source text.
+    pub text: Range<usize>,
+    /// Units the segment uses: septets for GSM 7-bit, UTF-16 code units for UCS-2.
+    pub units: usize,
+}
+
+/// Segment breakdown of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentInfo {
+    /// Encoding the message is measured in.
+    pub encoding: SmsEncoding,
+    /// Number of SMS segments needed.
+    pub segment_count: usize,
+    /// Units used by the whole text: septets for GSM 7-bit, UTF-16 code units for UCS-2.
+    pub units_used: usize,
+    /// Units still free in the last segment before another segment is needed.
+    pub units_remaining: usize,
+    /// Per-segment boundaries, in order.
+    pub segments: Vec<SegmentBoundary>,
+}
+
+impl SegmentInfo {
+    /// Maximum units in each segment: the single-part limit for one segment,
+    /// the concatenated limit otherwise.
+    pub fn units_per_segment(&self) -> usize {
+        if self.segment_count == 1 {
+            self.encoding.single_part_limit()
+        } else {
+            self.encoding.multi_part_limit()
+        }
+    }
+}
+
+/// Calculate the segments needed to send `content` in the cheapest encoding.
+///
+/// The encoding is picked by [`choose_encoding`].
+///
+/// # Arguments
+///
+/// * `content` - The text to send
+///
+/// # Returns
+///
+/// The segment breakdown.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{SmsEncoding, segment_info};
+///
+/// let info = segment_info(&"a".repeat(160));
+/// assert_eq!(info.encoding, SmsEncoding::GSM7_DEFAULT);
+/// assert_eq!(info.segment_count, 1);
+/// assert_eq!(info.units_remaining, 0);
+///
+/// // One more character and the message is concatenated at 153 septets per part
+/// let info = segment_info(&"a".repeat(161));
+/// assert_eq!(info.segment_count, 2);
+/// assert_eq!(info.segments[0].text, 0..153);
+/// assert_eq!(info.units_remaining, 153 - 8);
+/// ```
+pub fn segment_info(content: &str) -> SegmentInfo {
+    let encoding = choose_encoding(content).encoding;
+    layout(content, encoding).expect("chosen encoding represents the text")
+}
+
+/// Calculate the segments needed to send `content` in a given encoding.
+///
+/// # Arguments
+///
+/// * `content` - The text to send
+/// * `encoding` - The encoding to measure in
+///
+/// # Returns
+///
+/// The segment breakdown.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::UnsupportedCharacter` if `encoding` is GSM 7-bit and the
+/// text contains a character missing from its tables.
+pub fn segment_info_with_encoding(content: &str, encoding: SmsEncoding) -> Result<SegmentInfo> {
+    let costs = char_costs(content, encoding)?;
+    Ok(layout_costs(
+        content.len(),
+        &costs,
+        encoding,
+        encoding.single_part_limit(),
+        encoding.multi_part_limit(),
+    ))
+}
+
+/// Segment breakdown, or `None` if `encoding` cannot represent `content`.
+pub(crate) fn layout(content: &str, encoding: SmsEncoding) -> Option<SegmentInfo> {
+    segment_info_with_encoding(content, encoding).ok()
+}
+
+/// Byte offset and unit cost of every character of `content` in `encoding`.
+pub(crate) fn char_costs(content: &str, encoding: SmsEncoding) -> Result<Vec<(usize, usize)>> {
+    match encoding.gsm7_config() {
+        Some(config) => {
+            let alphabet = Alphabet::for_config(&config);
+            content
+                .char_indices()
+                .map(|(offset, ch)| match alphabet.code(ch) {
+                    Some(Code::Single(_)) => Ok((offset, 1)),
+                    Some(Code::Escape(_)) => Ok((offset, 2)),
+                    None => Err(Gsm7Error::UnsupportedCharacter {
+                        character: ch,
+                        code: ch as u32,
+                    }),
+                })
+                .collect()
+        }
+        None => Ok(content
+            .char_indices()
+            .map(|(offset, ch)| (offset, ch.len_utf16()))
+            .collect()),
+    }
+}
+
+/// Lay out indivisible pieces of text, given as byte offset and unit cost, into segments.
+pub(crate) fn layout_costs(
+    text_len: usize,
+    costs: &[(usize, usize)],
+    encoding: SmsEncoding,
+    single_limit: usize,
+    multi_limit: usize,
+) -> SegmentInfo {
+    let units_used = costs.iter().map(|&(_, cost)| cost).sum();
+
+    if units_used <= single_limit {
+        return SegmentInfo {
+            encoding,
+            segment_count: 1,
+            units_used,
+            units_remaining: single_limit - units_used,
+            segments: vec![SegmentBoundary {
+                text: 0..text_len,
+                units: units_used,
+            }],
+        };
+    }
+
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut units = 0;
+
+    for &(offset, cost) in costs {
+        if units > 0 && units + cost > multi_limit {
+            segments.push(SegmentBoundary {
+                text: start..offset,
+                units,
+            });
+            start = offset;
+            units = 0;
+        }
+        units += cost;
+    }
+    segments.push(SegmentBoundary {
+        text: start..text_len,
+        units,
+    });
+
+    SegmentInfo {
+        encoding,
+        segment_count: segments.len(),
+        units_used,
+        units_remaining: multi_limit - units,
+        segments,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NationalLanguage;
+
+    #[test]
+    fn test_single_part_limits() {
+        let info = segment_info("Hello");
+        assert_eq!(info.segment_count, 1);
+        assert_eq!(info.units_used, 5);
+        assert_eq!(info.units_remaining, 155);
+        assert_eq!(info.units_per_segment(), 160);
+
+        let info = segment_info(&"Ж".repeat(70));
+        assert_eq!(info.encoding, SmsEncoding::Ucs2);
+        assert_eq!(info.segment_count, 1);
+        assert_eq!(info.units_remaining, 0);
+
+        let info = segment_info(&"Ж".repeat(71));
+        assert_eq!(info.segment_count, 2);
+        assert_eq!(info.segments[0].units, 67);
+        assert_eq!(info.segments[1].units, 4);
+        assert_eq!(info.units_remaining, 63);
+        assert_eq!(info.units_per_segment(), 67);
+    }
+
+    #[test]
+    fn test_empty_text() {
+        let info = segment_info("");
+        assert_eq!(info.segment_count, 1);
+        assert_eq!(info.units_used, 0);
+        assert_eq!(info.units_remaining, 160);
+        assert_eq!(
+            info.segments,
+            [SegmentBoundary {
+                text: 0..0,
+                units: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_escape_counts_two_septets() {
+        let euros = |count| {
+            segment_info_with_encoding(&"€".repeat(count), SmsEncoding::GSM7_DEFAULT).unwrap()
+        };
+        let info = euros(80);
+        assert_eq!(info.units_used, 160);
+        assert_eq!(info.segment_count, 1);
+
+        let info = euros(81);
+        assert_eq!(info.segment_count, 2);
+        // 153 is odd: the 77th euro sign would straddle the boundary
+        assert_eq!(info.segments[0].units, 152);
+        assert_eq!(info.segments[0].text, 0..76 * 3);
+        assert_eq!(info.segments[1].units, 10);
+    }
+
+    #[test]
+    fn test_boundaries_cover_text() {
+        let text = "The quick brown fox {jumps} over the lazy dog. ".repeat(10);
+        let info = segment_info(&text);
+        assert_eq!(info.segment_count, 4);
+        assert_eq!(info.segments.first().unwrap().text.start, 0);
+        assert_eq!(info.segments.last().unwrap().text.end, text.len());
+        for pair in info.segments.windows(2) {
+            assert_eq!(pair[0].text.end, pair[1].text.start);
+        }
+        let total: usize = info.segments.iter().map(|segment| segment.units).sum();
+        assert_eq!(total, info.units_used);
+    }
+
+    #[test]
+    fn test_surrogate_pairs_not_split() {
+        let mut text = "a".repeat(66);
+        text.push_str(&"🦀".repeat(3));
+        let info = segment_info(&text);
+        assert_eq!(info.encoding, SmsEncoding::Ucs2);
+        assert_eq!(info.segment_count, 2);
+        assert_eq!(info.segments[0].units, 66);
+        assert_eq!(info.segments[1].units, 6);
+    }
+
+    #[test]
+    fn test_with_encoding() {
+        let encoding = SmsEncoding::Gsm7 {
+            locking_shift: None,
+            single_shift: Some(NationalLanguage::Turkish),
+        };
+        let info = segment_info_with_encoding("Ş", encoding).unwrap();
+        assert_eq!(info.units_used, 2);
+        assert_eq!(info.units_remaining, 153);
+
+        assert_eq!(
+            segment_info_with_encoding("Ж", SmsEncoding::GSM7_DEFAULT),
+            Err(Gsm7Error::UnsupportedCharacter {
+                character: 'Ж',
+                code: 0x416,
+            })
+        );
+    }
+}
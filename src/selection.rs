@@ -0,0 +1,298 @@
+//! Choosing the cheapest SMS encoding for a piece of text.
+//!
+//! A message can go out in the GSM 7-bit default alphabet, in GSM 7-bit with
+//! national language shift tables announced in the User Data Header, or in
+//! UCS-2. Shift tables make more text fit in GSM 7-bit, but every table costs a
+//! three-octet header element in every segment, so the cheapest choice depends
+//! on the text.
+
+use crate::{Gsm7Config, NationalLanguage, segment};
+
+/// Octets available for user data in a single SMS.
+const USER_DATA_OCTETS: usize = 140;
+
+/// Header octets used by an 8-bit reference concatenation IE.
+const CONCAT_IE_OCTETS: usize = 5;
+
+/// Header octets used by one national language shift IE.
+const SHIFT_IE_OCTETS: usize = 3;
+
+/// Character set used to send a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SmsEncoding {
+    /// GSM 7-bit, optionally with national language shift tables.
+    Gsm7 {
+        /// Locking-shift table replacing the default alphabet, if any.
+        locking_shift: Option<NationalLanguage>,
+        /// Single-shift table replacing the extension table, if any.
+        single_shift: Option<NationalLanguage>,
+    },
+    /// UCS-2, two octets per UTF-16 code unit.
+    Ucs2,
+}
+
+impl SmsEncoding {
+    /// The GSM 7-bit default alphabet without shift tables.
+    pub const GSM7_DEFAULT: SmsEncoding = SmsEncoding::Gsm7 {
+        locking_shift: None,
+        single_shift: None,
+    };
+
+    /// The encoding/decoding configuration for a GSM 7-bit encoding.
+    ///
+    /// Returns `None` for UCS-2.
+    pub fn gsm7_config(&self) -> Option<Gsm7Config> {
+        match *self {
+            SmsEncoding::Gsm7 {
+                locking_shift,
+                single_shift,
+            } => Some(Gsm7Config::national(locking_shift, single_shift)),
+            SmsEncoding::Ucs2 => None,
+        }
+    }
+
+    /// Number of national language shift IEs the User Data Header must carry.
+    pub fn shift_ie_count(&self) -> usize {
+        match *self {
+            SmsEncoding::Gsm7 {
+                locking_shift,
+                single_shift,
+            } => usize::from(locking_shift.is_some()) + usize::from(single_shift.is_some()),
+            SmsEncoding::Ucs2 => 0,
+        }
+    }
+
+    /// Maximum units (septets or UTF-16 code units) in a single-part message.
+    pub fn single_part_limit(&self) -> usize {
+        self.units_for_header(self.header_octets(false))
+    }
+
+    /// Maximum units (septets or UTF-16 code units) in each part of a concatenated message.
+    pub fn multi_part_limit(&self) -> usize {
+        self.units_for_header(self.header_octets(true))
+    }
+
+    /// User Data Header length in octets, including the UDHL octet.
+    fn header_octets(&self, concatenated: bool) -> usize {
+        let ie_octets =
+            usize::from(concatenated) * CONCAT_IE_OCTETS + self.shift_ie_count() * SHIFT_IE_OCTETS;
+        match ie_octets {
+            0 => 0,
+            octets => 1 + octets,
+        }
+    }
+
+    /// Units that fit in one SMS alongside a User Data Header of `header_octets` octets.
+    pub(crate) fn units_for_header(&self, header_octets: usize) -> usize {
+        let octets = USER_DATA_OCTETS - header_octets;
+        match self {
+            SmsEncoding::Gsm7 { .. } => octets * 8 / 7,
+            SmsEncoding::Ucs2 => octets / 2,
+        }
+    }
+}
+
+/// The result of [`choose_encoding`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingChoice {
+    /// The selected encoding.
+    pub encoding: SmsEncoding,
+    /// Units needed for the text: septets for GSM 7-bit, UTF-16 code units for UCS-2.
+    pub units: usize,
+    /// Number of SMS segments the text needs.
+    pub segments: usize,
+}
+
+/// Choose the encoding that sends `content` in the fewest segments.
+///
+/// Every national language and every locking-shift/single-shift combination is
+/// considered. Ties go to the encoding with fewer shift tables, so text that
+/// fits the default alphabet always uses it, and UCS-2 is only chosen when no
+/// GSM 7-bit table can represent the text.
+///
+/// # Arguments
+///
+/// * `content` - The text to send
+///
+/// # Returns
+///
+/// The chosen encoding with its unit and segment counts.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{NationalLanguage, SmsEncoding, choose_encoding};
+///
+/// assert_eq!(choose_encoding("Hello {world}").encoding, SmsEncoding::GSM7_DEFAULT);
+/// assert_eq!(choose_encoding("Hello 🦀").encoding, SmsEncoding::Ucs2);
+///
+/// let choice = choose_encoding("Şimdi geliyorum");
+/// assert_eq!(
+///     choice.encoding,
+///     SmsEncoding::Gsm7 {
+///         locking_shift: Some(NationalLanguage::Turkish),
+///         single_shift: None,
+///     }
+/// );
+/// assert_eq!(choice.units, 15);
+/// assert_eq!(choice.segments, 1);
+/// ```
+pub fn choose_encoding(content: &str) -> EncodingChoice {
+    choose_encoding_with_languages(content, &NationalLanguage::ALL)
+}
+
+/// Choose the encoding that sends `content` in the fewest segments, considering
+/// only the given national languages.
+///
+/// Use this when the receiving network only supports some shift tables. Passing
+/// an empty slice restricts the choice to the default alphabet and UCS-2.
+///
+/// # Arguments
+///
+/// * `content` - The text to send
+/// * `languages` - National languages whose shift tables may be used
+///
+/// # Returns
+///
+/// The chosen encoding with its unit and segment counts.
+pub fn choose_encoding_with_languages(
+    content: &str,
+    languages: &[NationalLanguage],
+) -> EncodingChoice {
+    let locking = std::iter::once(None).chain(
+        languages
+            .iter()
+            .filter(|language| language.has_locking_shift_table())
+            .map(|&language| Some(language)),
+    );
+
+    let mut best: Option<EncodingChoice> = None;
+    for locking_shift in locking {
+        for single_shift in std::iter::once(None).chain(languages.iter().copied().map(Some)) {
+            let encoding = SmsEncoding::Gsm7 {
+                locking_shift,
+                single_shift,
+            };
+            let Some(choice) = measure(content, encoding) else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|best| is_cheaper(&choice, best)) {
+                best = Some(choice);
+            }
+        }
+    }
+
+    best.unwrap_or_else(|| measure(content, SmsEncoding::Ucs2).expect("UCS-2 encodes any text"))
+}
+
+fn is_cheaper(candidate: &EncodingChoice, current: &EncodingChoice) -> bool {
+    let key = |choice: &EncodingChoice| {
+        (
+            choice.segments,
+            choice.encoding.shift_ie_count(),
+            choice.units,
+        )
+    };
+    key(candidate) < key(current)
+}
+
+/// Measure `content` in `encoding`, or `None` if the encoding cannot represent it.
+fn measure(content: &str, encoding: SmsEncoding) -> Option<EncodingChoice> {
+    let info = segment::layout(content, encoding)?;
+    Some(EncodingChoice {
+        encoding,
+        units: info.units_used,
+        segments: info.segment_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(SmsEncoding::GSM7_DEFAULT.single_part_limit(), 160);
+        assert_eq!(SmsEncoding::GSM7_DEFAULT.multi_part_limit(), 153);
+        assert_eq!(SmsEncoding::Ucs2.single_part_limit(), 70);
+        assert_eq!(SmsEncoding::Ucs2.multi_part_limit(), 67);
+
+        let one_shift = SmsEncoding::Gsm7 {
+            locking_shift: None,
+            single_shift: Some(NationalLanguage::Turkish),
+        };
+        assert_eq!(one_shift.single_part_limit(), 155);
+        assert_eq!(one_shift.multi_part_limit(), 149);
+
+        let two_shifts = SmsEncoding::Gsm7 {
+            locking_shift: Some(NationalLanguage::Turkish),
+            single_shift: Some(NationalLanguage::Turkish),
+        };
+        assert_eq!(two_shifts.single_part_limit(), 152);
+        assert_eq!(two_shifts.multi_part_limit(), 146);
+    }
+
+    #[test]
+    fn test_default_alphabet_preferred() {
+        let choice = choose_encoding("Hello World!");
+        assert_eq!(choice.encoding, SmsEncoding::GSM7_DEFAULT);
+        assert_eq!(choice.units, 12);
+        assert_eq!(choice.segments, 1);
+    }
+
+    #[test]
+    fn test_empty_text() {
+        let choice = choose_encoding("");
+        assert_eq!(choice.encoding, SmsEncoding::GSM7_DEFAULT);
+        assert_eq!(choice.segments, 1);
+    }
+
+    #[test]
+    fn test_locking_shift_beats_escapes() {
+        // Turkish letters through the single shift cost two septets each; a long
+        // Turkish text fits in fewer segments with the locking shift as well.
+        let text = "ğışçğışçğı".repeat(8);
+        let choice = choose_encoding(&text);
+        assert_eq!(
+            choice.encoding,
+            SmsEncoding::Gsm7 {
+                locking_shift: Some(NationalLanguage::Turkish),
+                single_shift: None,
+            }
+        );
+        assert_eq!(choice.units, 80);
+        assert_eq!(choice.segments, 1);
+    }
+
+    #[test]
+    fn test_hindi_uses_national_tables() {
+        let choice = choose_encoding("नमस्ते दुनिया");
+        assert!(matches!(
+            choice.encoding,
+            SmsEncoding::Gsm7 {
+                locking_shift: Some(NationalLanguage::Hindi),
+                ..
+            }
+        ));
+        assert_eq!(choice.segments, 1);
+    }
+
+    #[test]
+    fn test_restricted_languages_fall_back_to_ucs2() {
+        let choice = choose_encoding_with_languages("नमस्ते", &[NationalLanguage::Turkish]);
+        assert_eq!(choice.encoding, SmsEncoding::Ucs2);
+        assert_eq!(choice.units, 6);
+    }
+
+    #[test]
+    fn test_ucs2_surrogate_pairs_count_twice() {
+        let choice = choose_encoding(&"🦀".repeat(35));
+        assert_eq!(choice.encoding, SmsEncoding::Ucs2);
+        assert_eq!(choice.units, 70);
+        assert_eq!(choice.segments, 1);
+
+        // 36 crabs: 72 units, and 67-unit parts cannot hold half a surrogate pair
+        let choice = choose_encoding(&"🦀".repeat(36));
+        assert_eq!(choice.segments, 2);
+    }
+}
@@ -0,0 +1,258 @@
+//! SIM alpha identifiers (ETSI TS 102 221 Annex A, 3GPP TS 31.102).
+//!
+//! Names in EF_ADN, EF_SMSP, EF_SPN and similar files take a fixed number of
+//! octets, padded with 0xFF. The text is either unpacked GSM 7-bit or UCS-2 in
+//! one of three forms:
+//!
+//! * 0x80: UCS-2 code units;
+//! * 0x81: a character count, bits 15–8 of a base pointer, then one octet
+//!   per character;
+//! * 0x82: a character count, a 16-bit base pointer, then one octet per
+//!   character.
+//!
+//! In the 0x81 and 0x82 forms an octet below 0x80 is a GSM 7-bit character,
+//! and an octet from 0x80 up adds its low seven bits to the base pointer to
+//! give a UCS-2 character.
+
+use crate::{
+    Alphabet, Code, Gsm7Config, Gsm7Error, Result, Ucs2Mode, decode_ucs2, decode_with_config,
+    encode_ucs2_with_mode, encode_with_config,
+};
+
+/// Octet padding the unused end of a record.
+const PADDING: u8 = 0xFF;
+/// Tag of the plain UCS-2 form.
+const TAG_UCS2: u8 = 0x80;
+/// Tag of the form with bits 15–8 of the base pointer.
+const TAG_UCS2_BASE8: u8 = 0x81;
+/// Tag of the form with a 16-bit base pointer.
+const TAG_UCS2_BASE16: u8 = 0x82;
+/// Characters reachable from a base pointer.
+const WINDOW: u32 = 0x80;
+
+/// Encode a name for an alpha identifier of `length` octets.
+///
+/// All four forms are tried and the shortest that fits is used; on a tie the
+/// GSM 7-bit form wins, then 0x81, 0x82 and 0x80. The result is padded with
+/// 0xFF to `length` octets.
+///
+/// # Arguments
+///
+/// * `text` - The name
+/// * `length` - Octets available for the alpha identifier in the record
+///
+/// # Returns
+///
+/// Exactly `length` octets.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::UnsupportedUcs2Character` if the name contains a
+/// character outside the Basic Multilingual Plane, and
+/// `Gsm7Error::AlphaIdentifierTooLong` if no form fits in `length` octets.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{decode_alpha_identifier, encode_alpha_identifier};
+///
+/// let alpha = encode_alpha_identifier("Иван", 10)?;
+/// assert_eq!(alpha, [0x81, 0x04, 0x08, 0x98, 0xB2, 0xB0, 0xBD, 0xFF, 0xFF, 0xFF]);
+/// assert_eq!(decode_alpha_identifier(&alpha)?, "Иван");
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+pub fn encode_alpha_identifier(text: &str, length: usize) -> Result<Vec<u8>> {
+    let mut ucs2 = vec![TAG_UCS2];
+    ucs2.extend(encode_ucs2_with_mode(text, Ucs2Mode::Strict)?);
+
+    let mut alpha = [
+        encode_with_config(text, &Gsm7Config::strict()).ok(),
+        encode_with_base(text, TAG_UCS2_BASE8),
+        encode_with_base(text, TAG_UCS2_BASE16),
+        Some(ucs2),
+    ]
+    .into_iter()
+    .flatten()
+    .min_by_key(Vec::len)
+    .unwrap_or_default();
+
+    if alpha.len() > length {
+        return Err(Gsm7Error::AlphaIdentifierTooLong {
+            octets: alpha.len(),
+            limit: length,
+        });
+    }
+    alpha.resize(length, PADDING);
+    Ok(alpha)
+}
+
+/// Decode an alpha identifier in any of its forms.
+///
+/// 0xFF padding is removed: GSM 7-bit text ends at the first 0xFF, and
+/// trailing 0xFFFF code units are dropped from the 0x80 form. Unknown GSM
+/// 7-bit codes and base-pointer characters that are not valid become the
+/// replacement character.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::MalformedData` if a 0x81 or 0x82 form is shorter than
+/// its header or character count, and `Gsm7Error::InvalidUtf16` for a
+/// malformed 0x80 form.
+pub fn decode_alpha_identifier(data: &[u8]) -> Result<String> {
+    let config = Gsm7Config::default();
+    match data.first() {
+        Some(&TAG_UCS2) => {
+            let mut units = &data[1..data.len() - (data.len() - 1) % 2];
+            while let [rest @ .., PADDING, PADDING] = units {
+                units = rest;
+            }
+            decode_ucs2(units)
+        }
+        Some(&tag @ (TAG_UCS2_BASE8 | TAG_UCS2_BASE16)) => {
+            let malformed = || Gsm7Error::MalformedData {
+                reason: format!("alpha identifier 0x{tag:02X} is truncated"),
+            };
+            let (base, characters) = match *data {
+                [TAG_UCS2_BASE8, count, base, ref rest @ ..] => {
+                    (u32::from(base) << 7, rest.get(..usize::from(count)))
+                }
+                [TAG_UCS2_BASE16, count, high, low, ref rest @ ..] => (
+                    u32::from(u16::from_be_bytes([high, low])),
+                    rest.get(..usize::from(count)),
+                ),
+                _ => return Err(malformed()),
+            };
+            let characters = characters.ok_or_else(malformed)?;
+
+            let mut text = String::new();
+            for run in characters.chunk_by(|a, b| (a & 0x80) == (b & 0x80)) {
+                if run[0] & 0x80 == 0 {
+                    text.push_str(&decode_with_config(run, &config)?);
+                } else {
+                    text.extend(run.iter().map(|&octet| {
+                        char::from_u32(base + u32::from(octet & 0x7F))
+                            .unwrap_or(config.replacement_char)
+                    }));
+                }
+            }
+            Ok(text)
+        }
+        _ => {
+            let end = data
+                .iter()
+                .position(|&octet| octet == PADDING)
+                .unwrap_or(data.len());
+            decode_with_config(&data[..end], &config)
+        }
+    }
+}
+
+/// Encode `text` in the 0x81 or 0x82 form, or `None` if it does not fit in
+/// one base-pointer window or needs no UCS-2 characters at all.
+fn encode_with_base(text: &str, tag: u8) -> Option<Vec<u8>> {
+    let alphabet = Alphabet::for_config(&Gsm7Config::default());
+    let codes: Vec<std::result::Result<u8, u32>> = text
+        .chars()
+        .map(|ch| match alphabet.code(ch) {
+            Some(Code::Single(code)) => Ok(code),
+            _ => Err(u32::from(ch)),
+        })
+        .collect();
+
+    let wide = codes.iter().filter_map(|code| code.err());
+    let (min, max) = (wide.clone().min()?, wide.max()?);
+    let base = match tag {
+        TAG_UCS2_BASE8 if min < 0x8000 => min & !(WINDOW - 1),
+        TAG_UCS2_BASE8 => return None,
+        _ => min,
+    };
+    if max - base >= WINDOW || max > 0xFFFF {
+        return None;
+    }
+
+    let mut alpha = vec![tag, u8::try_from(codes.len()).ok()?];
+    if tag == TAG_UCS2_BASE8 {
+        alpha.push((base >> 7) as u8);
+    } else {
+        alpha.extend_from_slice(&(base as u16).to_be_bytes());
+    }
+    alpha.extend(codes.iter().map(|code| match *code {
+        Ok(code) => code,
+        Err(ch) => 0x80 | (ch - base) as u8,
+    }));
+    Some(alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gsm7() {
+        let alpha = encode_alpha_identifier("John €", 10).unwrap();
+        assert_eq!(
+            alpha,
+            [0x4A, 0x6F, 0x68, 0x6E, 0x20, 0x1B, 0x65, 0xFF, 0xFF, 0xFF]
+        );
+        assert_eq!(decode_alpha_identifier(&alpha).unwrap(), "John €");
+        assert_eq!(encode_alpha_identifier("", 4).unwrap(), [0xFF; 4]);
+        assert_eq!(decode_alpha_identifier(&[0xFF; 4]).unwrap(), "");
+        assert_eq!(decode_alpha_identifier(&[]).unwrap(), "");
+    }
+
+    #[test]
+    fn test_ucs2_forms() {
+        // Two scripts too far apart for a base pointer
+        let alpha = encode_alpha_identifier("Жx中", 8).unwrap();
+        assert_eq!(alpha, [0x80, 0x04, 0x16, 0x00, 0x78, 0x4E, 0x2D, 0xFF]);
+        assert_eq!(decode_alpha_identifier(&alpha).unwrap(), "Жx中");
+
+        // 0x0532..=0x0583 crosses a 128-character boundary, so only 0x82 fits
+        let alpha = encode_alpha_identifier("-\u{0532}\u{0583}-1", 9).unwrap();
+        assert_eq!(
+            alpha,
+            [0x82, 0x05, 0x05, 0x32, 0x2D, 0x80, 0xD1, 0x2D, 0x31]
+        );
+        assert_eq!(
+            decode_alpha_identifier(&alpha).unwrap(),
+            "-\u{0532}\u{0583}-1"
+        );
+    }
+
+    #[test]
+    fn test_decode_specification_examples() {
+        assert_eq!(
+            decode_alpha_identifier(&[0x82, 0x05, 0x05, 0x30, 0x2D, 0x82, 0xD3, 0x2D, 0x31])
+                .unwrap(),
+            "-\u{0532}\u{0583}-1"
+        );
+        assert_eq!(
+            decode_alpha_identifier(&[0x81, 0x04, 0x13, 0x53, 0x95, 0xA6, 0xA6, 0xFF]).unwrap(),
+            "S\u{0995}\u{09A6}\u{09A6}"
+        );
+        // UCS-2 padding, including an odd trailing octet
+        assert_eq!(
+            decode_alpha_identifier(&[0x80, 0x04, 0x16, 0x00, 0xFF, 0xFF, 0xFF, 0xFF]).unwrap(),
+            "Жÿ"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            encode_alpha_identifier("Иван", 6),
+            Err(Gsm7Error::AlphaIdentifierTooLong {
+                octets: 7,
+                limit: 6,
+            })
+        );
+        assert!(matches!(
+            encode_alpha_identifier("🦀", 20),
+            Err(Gsm7Error::UnsupportedUcs2Character { .. })
+        ));
+        assert!(matches!(
+            decode_alpha_identifier(&[0x81, 0x05, 0x08, 0x98]),
+            Err(Gsm7Error::MalformedData { .. })
+        ));
+    }
+}
@@ -0,0 +1,125 @@
+//! bind_transmitter, bind_receiver, bind_transceiver and their responses
+//! (SMPP 3.4 §4.1).
+
+use super::{Reader, Tlv, read_tlvs, write_c_string, write_tlvs};
+use crate::Result;
+
+/// Body of a bind_transmitter, bind_receiver or bind_transceiver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bind {
+    /// system_id: the ESME's account, up to 15 characters.
+    pub system_id: String,
+    /// password, up to 8 characters.
+    pub password: String,
+    /// system_type, up to 12 characters, often empty.
+    pub system_type: String,
+    /// interface_version: 0x34 for SMPP 3.4, 0x50 for SMPP 5.0.
+    pub interface_version: u8,
+    /// addr_ton of the addresses served by a receiver.
+    pub addr_ton: u8,
+    /// addr_npi of the addresses served by a receiver.
+    pub addr_npi: u8,
+    /// address_range: a regular expression over the served addresses.
+    pub address_range: String,
+}
+
+impl Bind {
+    /// SMPP 3.4 interface_version.
+    pub const SMPP_3_4: u8 = 0x34;
+    /// SMPP 5.0 interface_version.
+    pub const SMPP_5_0: u8 = 0x50;
+
+    /// A SMPP 3.4 bind with the given credentials and everything else empty.
+    pub fn new(system_id: &str, password: &str) -> Self {
+        Self {
+            system_id: system_id.to_string(),
+            password: password.to_string(),
+            system_type: String::new(),
+            interface_version: Self::SMPP_3_4,
+            addr_ton: 0,
+            addr_npi: 0,
+            address_range: String::new(),
+        }
+    }
+
+    pub(super) fn read(reader: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            system_id: reader.c_string("system_id", 16)?,
+            password: reader.c_string("password", 9)?,
+            system_type: reader.c_string("system_type", 13)?,
+            interface_version: reader.u8("interface_version")?,
+            addr_ton: reader.u8("addr_ton")?,
+            addr_npi: reader.u8("addr_npi")?,
+            address_range: reader.c_string("address_range", 41)?,
+        })
+    }
+
+    pub(super) fn write(&self, bytes: &mut Vec<u8>) -> Result<()> {
+        write_c_string(bytes, "system_id", &self.system_id, 16)?;
+        write_c_string(bytes, "password", &self.password, 9)?;
+        write_c_string(bytes, "system_type", &self.system_type, 13)?;
+        bytes.extend([self.interface_version, self.addr_ton, self.addr_npi]);
+        write_c_string(bytes, "address_range", &self.address_range, 41)
+    }
+}
+
+/// Body of a bind response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BindResp {
+    /// system_id of the SMSC.
+    pub system_id: String,
+    /// Optional parameters, usually sc_interface_version.
+    pub tlvs: Vec<Tlv>,
+}
+
+impl BindResp {
+    /// A response from the SMSC `system_id` announcing SMPP 3.4.
+    pub fn new(system_id: &str) -> Self {
+        Self {
+            system_id: system_id.to_string(),
+            tlvs: vec![Tlv::ScInterfaceVersion(Bind::SMPP_3_4)],
+        }
+    }
+
+    pub(super) fn read(reader: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            system_id: reader.c_string("system_id", 16)?,
+            tlvs: read_tlvs(reader)?,
+        })
+    }
+
+    pub(super) fn write(&self, bytes: &mut Vec<u8>) -> Result<()> {
+        write_c_string(bytes, "system_id", &self.system_id, 16)?;
+        write_tlvs(bytes, &self.tlvs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::smpp::{Pdu, PduBody};
+
+    use super::*;
+
+    #[test]
+    fn test_bind_wire_format() {
+        let bind = Bind::new("esme", "secret");
+        let bytes = Pdu::new(1, PduBody::BindTransceiver(bind.clone()))
+            .to_bytes()
+            .unwrap();
+        assert_eq!(bytes[..8], [0, 0, 0, 33, 0, 0, 0, 9]);
+        assert_eq!(bytes[16..], *b"esme\0secret\0\0\x34\0\0\0");
+        assert_eq!(
+            Pdu::from_bytes(&bytes).unwrap().body,
+            PduBody::BindTransceiver(bind)
+        );
+    }
+
+    #[test]
+    fn test_bind_resp_roundtrip() {
+        let pdu = Pdu::new(1, PduBody::BindReceiverResp(BindResp::new("SMSC")));
+        let bytes = pdu.to_bytes().unwrap();
+        assert_eq!(bytes[4..8], [0x80, 0, 0, 1]);
+        assert_eq!(bytes[21..], [0x02, 0x10, 0x00, 0x01, 0x34]);
+        assert_eq!(Pdu::from_bytes(&bytes).unwrap(), pdu);
+    }
+}
@@ -0,0 +1,323 @@
+//! Text in short_message and message_payload, by data_coding (SMPP 3.4 §5.2.19).
+
+use crate::{
+    CharacterSet, DataCodingScheme, Gsm7Config, Gsm7Error, Result, decode_ucs2, decode_with_config,
+    encode_ucs2, encode_with_config, fill_bits_for_udh, pack_septets_cr_padded,
+    unpack_septets_cr_padded, unpack_septets_with_fill,
+};
+
+/// How the octets of a message map to text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextCoding {
+    /// GSM 7-bit default alphabet, one septet per octet.
+    Gsm7,
+    /// GSM 7-bit default alphabet, packed.
+    Gsm7Packed,
+    /// IA5 (CCITT T.50), i.e. ASCII.
+    Ascii,
+    /// ISO-8859-1.
+    Latin1,
+    /// UCS-2, big-endian, with UTF-16 surrogate pairs.
+    Ucs2,
+    /// Octets that are not text.
+    Binary,
+}
+
+impl TextCoding {
+    /// The coding of a data_coding value as SMPP 3.4 defines it.
+    ///
+    /// 0x00 is the SMSC default alphabet, taken as unpacked GSM 7-bit. Values
+    /// from 0x10 up follow the GSM data coding scheme.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::UnsupportedDataCoding` for the Japanese, Cyrillic,
+    /// Hebrew and pictogram codings, compressed text and reserved values.
+    pub fn for_data_coding(data_coding: u8) -> Result<Self> {
+        match data_coding {
+            0x00 => Ok(TextCoding::Gsm7),
+            0x01 => Ok(TextCoding::Ascii),
+            0x02 | 0x04 => Ok(TextCoding::Binary),
+            0x03 => Ok(TextCoding::Latin1),
+            0x08 => Ok(TextCoding::Ucs2),
+            0x05..=0x0F => Err(Gsm7Error::UnsupportedDataCoding { dcs: data_coding }),
+            _ => {
+                let dcs = DataCodingScheme::from_octet(data_coding);
+                match dcs.character_set() {
+                    _ if dcs.is_compressed() => {
+                        Err(Gsm7Error::UnsupportedDataCoding { dcs: data_coding })
+                    }
+                    Some(CharacterSet::Gsm7) => Ok(TextCoding::Gsm7),
+                    Some(CharacterSet::EightBit) => Ok(TextCoding::Binary),
+                    Some(CharacterSet::Ucs2) => Ok(TextCoding::Ucs2),
+                    None => Err(Gsm7Error::UnsupportedDataCoding { dcs: data_coding }),
+                }
+            }
+        }
+    }
+
+    /// Encode `text`, failing on characters the coding lacks.
+    ///
+    /// See [`encode_with_config`](Self::encode_with_config).
+    pub fn encode(self, text: &str) -> Result<Vec<u8>> {
+        self.encode_with_config(text, &Gsm7Config::strict())
+    }
+
+    /// Encode `text`.
+    ///
+    /// Packed GSM 7-bit text follows the `<CR>` padding rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The message text
+    /// * `config` - Strictness, replacement character and national language
+    ///   tables; in non-strict mode IA5 and ISO-8859-1 also replace missing
+    ///   characters, with `?` if the replacement character is missing too
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::UnsupportedCharacter` or
+    /// `Gsm7Error::UnsupportedCharsetCharacter` for a character the coding
+    /// lacks in strict mode, `Gsm7Error::UnsupportedUcs2Character` as
+    /// [`encode_ucs2`](crate::encode_ucs2) does, and
+    /// `Gsm7Error::UnsupportedDataCoding` for binary data.
+    pub fn encode_with_config(self, text: &str, config: &Gsm7Config) -> Result<Vec<u8>> {
+        match self {
+            TextCoding::Gsm7 => encode_with_config(text, config),
+            TextCoding::Gsm7Packed => {
+                Ok(pack_septets_cr_padded(&encode_with_config(text, config)?))
+            }
+            TextCoding::Ascii => encode_single_octet(text, "IA5", 0x7F, config),
+            TextCoding::Latin1 => encode_single_octet(text, "ISO-8859-1", 0xFF, config),
+            TextCoding::Ucs2 => encode_ucs2(text),
+            TextCoding::Binary => Err(Gsm7Error::UnsupportedDataCoding { dcs: 0x04 }),
+        }
+    }
+
+    /// Decode `octets` with the default [`Gsm7Config`].
+    ///
+    /// See [`decode_with_config`](Self::decode_with_config).
+    pub fn decode(self, octets: &[u8]) -> Result<Option<String>> {
+        self.decode_with_config(octets, &Gsm7Config::default())
+    }
+
+    /// Decode `octets`.
+    ///
+    /// # Arguments
+    ///
+    /// * `octets` - The encoded message
+    /// * `config` - Decoding options for GSM 7-bit text; its replacement
+    ///   character also stands in for IA5 codes above 0x7F
+    ///
+    /// # Returns
+    ///
+    /// The text, or `None` for binary data.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`decode_with_config`](crate::decode_with_config)
+    /// for GSM 7-bit text, and `Gsm7Error::InvalidUtf16` or
+    /// `Gsm7Error::MalformedData` for malformed UCS-2.
+    pub fn decode_with_config(self, octets: &[u8], config: &Gsm7Config) -> Result<Option<String>> {
+        match self {
+            TextCoding::Gsm7 => {
+                let septets: Vec<u8> = octets.iter().map(|&octet| octet & 0x7F).collect();
+                decode_with_config(&septets, config).map(Some)
+            }
+            TextCoding::Gsm7Packed => {
+                decode_with_config(&unpack_septets_cr_padded(octets), config).map(Some)
+            }
+            TextCoding::Ascii => Ok(Some(
+                octets
+                    .iter()
+                    .map(|&octet| {
+                        if octet.is_ascii() {
+                            char::from(octet)
+                        } else {
+                            config.replacement_char
+                        }
+                    })
+                    .collect(),
+            )),
+            TextCoding::Latin1 => Ok(Some(
+                octets.iter().map(|&octet| char::from(octet)).collect(),
+            )),
+            TextCoding::Ucs2 => decode_ucs2(octets).map(Some),
+            TextCoding::Binary => Ok(None),
+        }
+    }
+
+    /// Decode `octets` that start with a User Data Header of `header_len`
+    /// octets, skipping the header and, for packed GSM 7-bit, the fill bits.
+    /// A last septet of `<CR>` that fills the spare bits is dropped, as
+    /// [`unpack_septets_cr_padded`](crate::unpack_septets_cr_padded) does.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidPduField` if the header runs past the end
+    /// of `octets`, and the errors of decoding the text.
+    pub(crate) fn decode_after_header(
+        self,
+        octets: &[u8],
+        header_len: usize,
+        config: &Gsm7Config,
+    ) -> Result<Option<String>> {
+        let text = octets
+            .get(header_len..)
+            .ok_or_else(|| Gsm7Error::InvalidPduField {
+                field: "UDHL",
+                offset: 0,
+                reason: format!(
+                    "header of {header_len} octets in a message of {}",
+                    octets.len()
+                ),
+            })?;
+        match self {
+            TextCoding::Gsm7Packed => {
+                let fill_bits = fill_bits_for_udh(header_len);
+                let bits = (text.len() * 8).saturating_sub(usize::from(fill_bits));
+                let mut septets = unpack_septets_with_fill(text, bits / 7, fill_bits)?;
+                if bits.is_multiple_of(7) && septets.last() == Some(&0x0D) {
+                    septets.pop();
+                }
+                decode_with_config(&septets, config).map(Some)
+            }
+            _ => self.decode_with_config(text, config),
+        }
+    }
+}
+
+/// Encode `text` one octet per character, up to `max`.
+fn encode_single_octet(
+    text: &str,
+    charset: &'static str,
+    max: u8,
+    config: &Gsm7Config,
+) -> Result<Vec<u8>> {
+    let octet = |character: char| u8::try_from(character).ok().filter(|&octet| octet <= max);
+    text.chars()
+        .map(|character| match octet(character) {
+            Some(octet) => Ok(octet),
+            None if !config.strict => Ok(octet(config.replacement_char).unwrap_or(b'?')),
+            None => Err(Gsm7Error::UnsupportedCharsetCharacter {
+                character,
+                code: character as u32,
+                charset,
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_coding_table() {
+        assert_eq!(TextCoding::for_data_coding(0x00), Ok(TextCoding::Gsm7));
+        assert_eq!(TextCoding::for_data_coding(0x01), Ok(TextCoding::Ascii));
+        assert_eq!(TextCoding::for_data_coding(0x03), Ok(TextCoding::Latin1));
+        assert_eq!(TextCoding::for_data_coding(0x04), Ok(TextCoding::Binary));
+        assert_eq!(TextCoding::for_data_coding(0x08), Ok(TextCoding::Ucs2));
+        assert_eq!(TextCoding::for_data_coding(0x18), Ok(TextCoding::Ucs2));
+        assert_eq!(TextCoding::for_data_coding(0xF0), Ok(TextCoding::Gsm7));
+        assert_eq!(TextCoding::for_data_coding(0xF5), Ok(TextCoding::Binary));
+        assert_eq!(
+            TextCoding::for_data_coding(0x06),
+            Err(Gsm7Error::UnsupportedDataCoding { dcs: 0x06 })
+        );
+        assert_eq!(
+            TextCoding::for_data_coding(0x20),
+            Err(Gsm7Error::UnsupportedDataCoding { dcs: 0x20 })
+        );
+    }
+
+    #[test]
+    fn test_roundtrips() {
+        let cases = [
+            (TextCoding::Gsm7, "Hello {world} €"),
+            (TextCoding::Gsm7Packed, "1234567"),
+            (TextCoding::Ascii, "plain ASCII"),
+            (TextCoding::Latin1, "Größe: 5 ¼"),
+            (TextCoding::Ucs2, "Привет 🦀"),
+        ];
+        for (coding, text) in cases {
+            let octets = coding.encode(text).unwrap();
+            assert_eq!(coding.decode(&octets).unwrap().as_deref(), Some(text));
+        }
+        assert_eq!(TextCoding::Gsm7.encode("@").unwrap(), [0x00]);
+        assert_eq!(TextCoding::Binary.decode(&[1, 2]), Ok(None));
+    }
+
+    #[test]
+    fn test_unsupported_characters() {
+        assert_eq!(
+            TextCoding::Ascii.encode("é"),
+            Err(Gsm7Error::UnsupportedCharsetCharacter {
+                character: 'é',
+                code: 0xE9,
+                charset: "IA5",
+            })
+        );
+        assert!(matches!(
+            TextCoding::Latin1.encode("€"),
+            Err(Gsm7Error::UnsupportedCharsetCharacter { .. })
+        ));
+        assert!(matches!(
+            TextCoding::Gsm7.encode("ж"),
+            Err(Gsm7Error::UnsupportedCharacter { .. })
+        ));
+
+        let config = Gsm7Config::default();
+        assert_eq!(
+            TextCoding::Ascii.encode_with_config("né", &config).unwrap(),
+            b"n?"
+        );
+        assert_eq!(
+            TextCoding::Latin1
+                .encode_with_config("n€", &config)
+                .unwrap(),
+            b"n?"
+        );
+    }
+
+    #[test]
+    fn test_header_fill_bits() {
+        let header_len = 6;
+        let decode = |septets: &[u8]| {
+            let octets =
+                crate::pack_septets_with_fill(septets, fill_bits_for_udh(header_len)).unwrap();
+            let mut message = vec![0x05, 0x00, 0x03, 0x01, 0x02, 0x02];
+            message.extend(octets);
+            TextCoding::Gsm7Packed
+                .decode_after_header(&message, header_len, &Gsm7Config::default())
+                .unwrap()
+                .unwrap()
+        };
+        // Eight septets and a fill bit leave seven spare bits, padded with <CR>
+        assert_eq!(decode(b"Part two\r"), "Part two");
+        // A ninth septet that is a real '@' stays
+        assert_eq!(decode(b"ABCDEFGH\x00"), "ABCDEFGH@");
+        assert_eq!(
+            TextCoding::Gsm7
+                .decode_after_header(b"\x00hi", 1, &Gsm7Config::default())
+                .unwrap()
+                .as_deref(),
+            Some("hi")
+        );
+
+        // A header and no text
+        let header = [0x05, 0x00, 0x03, 0x01, 0x02, 0x02];
+        assert_eq!(
+            TextCoding::Gsm7Packed.decode_after_header(&header, 6, &Gsm7Config::default()),
+            Ok(Some(String::new()))
+        );
+        // A header longer than the message
+        for coding in [TextCoding::Gsm7Packed, TextCoding::Gsm7] {
+            assert!(matches!(
+                coding.decode_after_header(&[0x05], 6, &Gsm7Config::default()),
+                Err(Gsm7Error::InvalidPduField { field: "UDHL", .. })
+            ));
+        }
+    }
+}
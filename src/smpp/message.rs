@@ -0,0 +1,701 @@
+//! submit_sm, deliver_sm, data_sm and their responses (SMPP 3.4 §4.4–§4.7).
+
+use super::tlv::TAG_MESSAGE_PAYLOAD;
+use super::{DataCodingProfile, Reader, TextCoding, Tlv, read_tlvs, write_c_string, write_tlvs};
+use crate::{Gsm7Config, Gsm7Error, Result};
+
+/// Most octets short_message can hold; longer text goes in message_payload.
+const MAX_SHORT_MESSAGE_OCTETS: usize = 254;
+/// esm_class bit: the message starts with a User Data Header.
+const ESM_UDHI: u8 = 0x40;
+
+/// An address with its type of number and numbering plan indicator.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SmppAddress {
+    /// Type of number: 0 unknown, 1 international, 2 national, 5 alphanumeric, …
+    pub ton: u8,
+    /// Numbering plan indicator: 0 unknown, 1 E.164, …
+    pub npi: u8,
+    /// The address digits or name.
+    pub address: String,
+}
+
+impl SmppAddress {
+    /// An address with the given TON and NPI.
+    pub fn new(ton: u8, npi: u8, address: &str) -> Self {
+        Self {
+            ton,
+            npi,
+            address: address.to_string(),
+        }
+    }
+
+    /// An international E.164 number, without the leading `+`.
+    pub fn international(number: &str) -> Self {
+        Self::new(1, 1, number)
+    }
+
+    /// An alphanumeric sender name.
+    pub fn alphanumeric(name: &str) -> Self {
+        Self::new(5, 0, name)
+    }
+
+    fn read(reader: &mut Reader, field: &'static str, max: usize) -> Result<Self> {
+        Ok(Self {
+            ton: reader.u8("addr_ton")?,
+            npi: reader.u8("addr_npi")?,
+            address: reader.c_string(field, max)?,
+        })
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>, field: &'static str, max: usize) -> Result<()> {
+        bytes.extend([self.ton, self.npi]);
+        write_c_string(bytes, field, &self.address, max)
+    }
+}
+
+/// Body of a submit_sm, or of a deliver_sm, which has the same fields.
+///
+/// Text is kept encoded in `short_message` or a message_payload TLV, as given
+/// by `data_coding`; use [`set_text`](Self::set_text) and
+/// [`text`](Self::text) to convert.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubmitSm {
+    /// service_type, up to 5 characters, normally empty.
+    pub service_type: String,
+    /// source_addr.
+    pub source: SmppAddress,
+    /// destination_addr.
+    pub destination: SmppAddress,
+    /// esm_class: messaging mode, message type and GSM features such as UDHI.
+    pub esm_class: u8,
+    /// protocol_id: TP-PID.
+    pub protocol_id: u8,
+    /// priority_flag.
+    pub priority_flag: u8,
+    /// schedule_delivery_time in SMPP time format, or empty for immediate.
+    pub schedule_delivery_time: String,
+    /// validity_period in SMPP time format, or empty for the SMSC default.
+    pub validity_period: String,
+    /// registered_delivery: bit 0 requests a delivery receipt.
+    pub registered_delivery: u8,
+    /// replace_if_present_flag.
+    pub replace_if_present_flag: u8,
+    /// data_coding of the message.
+    pub data_coding: u8,
+    /// sm_default_msg_id: index of a canned message, or 0.
+    pub sm_default_msg_id: u8,
+    /// short_message: up to 254 octets, empty when message_payload is used.
+    pub short_message: Vec<u8>,
+    /// Optional parameters.
+    pub tlvs: Vec<Tlv>,
+}
+
+/// Body of a deliver_sm.
+pub type DeliverSm = SubmitSm;
+
+impl SubmitSm {
+    /// A message from `source` to `destination` with every other field empty.
+    pub fn new(source: SmppAddress, destination: SmppAddress) -> Self {
+        Self {
+            source,
+            destination,
+            ..Self::default()
+        }
+    }
+
+    /// A message with `text` in the GSM 7-bit default alphabet (data_coding
+    /// 0x00) if it fits, in UCS-2 (0x08) otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`set_text`](Self::set_text).
+    pub fn from_text(source: SmppAddress, destination: SmppAddress, text: &str) -> Result<Self> {
+        Self::from_text_with_profile(source, destination, text, &DataCodingProfile::standard())
+    }
+
+    /// A message with `text` in the SMSC default alphabet of `profile`
+    /// (data_coding 0x00) if it fits, in UCS-2 (0x08) otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`set_text`](Self::set_text).
+    pub fn from_text_with_profile(
+        source: SmppAddress,
+        destination: SmppAddress,
+        text: &str,
+        profile: &DataCodingProfile,
+    ) -> Result<Self> {
+        let mut submit = Self::new(source, destination);
+        submit.set_text_with_profile(
+            text,
+            profile.data_coding_for(text),
+            profile,
+            &Gsm7Config::strict(),
+        )?;
+        Ok(submit)
+    }
+
+    /// Encode `text` as `data_coding` says and store it.
+    ///
+    /// Text of up to 254 octets goes in short_message; longer text goes in a
+    /// message_payload TLV with short_message left empty. A previous
+    /// message_payload is removed and the UDHI bit cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The message text
+    /// * `data_coding` - The data_coding value; see
+    ///   [`TextCoding::for_data_coding`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::UnsupportedDataCoding` if `data_coding` carries no
+    /// supported text, and `Gsm7Error::UnsupportedCharacter` or
+    /// `Gsm7Error::UnsupportedCharsetCharacter` if the text cannot be encoded.
+    pub fn set_text(&mut self, text: &str, data_coding: u8) -> Result<()> {
+        self.set_text_with_profile(
+            text,
+            data_coding,
+            &DataCodingProfile::standard(),
+            &Gsm7Config::strict(),
+        )
+    }
+
+    /// Encode `text` as `profile` reads `data_coding` and store it, like
+    /// [`set_text`](Self::set_text).
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DataCodingProfile::encode`].
+    pub fn set_text_with_profile(
+        &mut self,
+        text: &str,
+        data_coding: u8,
+        profile: &DataCodingProfile,
+        config: &Gsm7Config,
+    ) -> Result<()> {
+        let octets = profile.encode(text, data_coding, config)?;
+        self.data_coding = data_coding;
+        self.esm_class &= !ESM_UDHI;
+        self.tlvs.retain(|tlv| tlv.tag() != TAG_MESSAGE_PAYLOAD);
+        if octets.len() > MAX_SHORT_MESSAGE_OCTETS {
+            self.short_message.clear();
+            self.tlvs.push(Tlv::MessagePayload(octets));
+        } else {
+            self.short_message = octets;
+        }
+        Ok(())
+    }
+
+    /// The message octets: the message_payload TLV if present, short_message
+    /// otherwise.
+    pub fn payload(&self) -> &[u8] {
+        message_payload(&self.tlvs).unwrap_or(&self.short_message)
+    }
+
+    /// Decode the message text as `data_coding` says, skipping a User Data
+    /// Header when esm_class has the UDHI bit.
+    ///
+    /// # Returns
+    ///
+    /// The text, or `None` for binary data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::UnsupportedDataCoding` for a data_coding without
+    /// supported text, `Gsm7Error::InvalidPduField` for a User Data Header
+    /// longer than the message, and `Gsm7Error::InvalidUtf16` or
+    /// `Gsm7Error::MalformedData` for malformed UCS-2.
+    pub fn text(&self) -> Result<Option<String>> {
+        self.text_with_config(&Gsm7Config::default())
+    }
+
+    /// Decode the message text like [`text`](Self::text), with `config` for
+    /// GSM 7-bit text.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`text`](Self::text), and
+    /// `Gsm7Error::UnsupportedCharacter` for an unknown GSM 7-bit code when
+    /// `config` is strict.
+    pub fn text_with_config(&self, config: &Gsm7Config) -> Result<Option<String>> {
+        self.text_with_profile(&DataCodingProfile::standard(), config)
+    }
+
+    /// Decode the message text as `profile` reads `data_coding`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`text_with_config`](Self::text_with_config).
+    pub fn text_with_profile(
+        &self,
+        profile: &DataCodingProfile,
+        config: &Gsm7Config,
+    ) -> Result<Option<String>> {
+        decode_text(
+            self.payload(),
+            profile.coding(self.data_coding)?,
+            self.esm_class,
+            config,
+        )
+    }
+
+    /// The receipted_message_id TLV of a delivery receipt.
+    pub fn receipted_message_id(&self) -> Option<&str> {
+        self.tlvs.iter().find_map(|tlv| match tlv {
+            Tlv::ReceiptedMessageId(id) => Some(id.as_str()),
+            _ => None,
+        })
+    }
+
+    pub(super) fn read(reader: &mut Reader) -> Result<Self> {
+        let service_type = reader.c_string("service_type", 6)?;
+        let source = SmppAddress::read(reader, "source_addr", 21)?;
+        let destination = SmppAddress::read(reader, "destination_addr", 21)?;
+        let esm_class = reader.u8("esm_class")?;
+        let protocol_id = reader.u8("protocol_id")?;
+        let priority_flag = reader.u8("priority_flag")?;
+        let schedule_delivery_time = reader.c_string("schedule_delivery_time", 17)?;
+        let validity_period = reader.c_string("validity_period", 17)?;
+        let registered_delivery = reader.u8("registered_delivery")?;
+        let replace_if_present_flag = reader.u8("replace_if_present_flag")?;
+        let data_coding = reader.u8("data_coding")?;
+        let sm_default_msg_id = reader.u8("sm_default_msg_id")?;
+        let sm_length = reader.u8("sm_length")?;
+        let short_message = reader
+            .take(usize::from(sm_length), "short_message")?
+            .to_vec();
+        Ok(Self {
+            service_type,
+            source,
+            destination,
+            esm_class,
+            protocol_id,
+            priority_flag,
+            schedule_delivery_time,
+            validity_period,
+            registered_delivery,
+            replace_if_present_flag,
+            data_coding,
+            sm_default_msg_id,
+            short_message,
+            tlvs: read_tlvs(reader)?,
+        })
+    }
+
+    pub(super) fn write(&self, bytes: &mut Vec<u8>) -> Result<()> {
+        let sm_length = u8::try_from(self.short_message.len())
+            .ok()
+            .filter(|&len| usize::from(len) <= MAX_SHORT_MESSAGE_OCTETS)
+            .ok_or_else(|| Gsm7Error::InvalidSmppField {
+                field: "short_message",
+                reason: format!(
+                    "{} octets, more than the maximum of {MAX_SHORT_MESSAGE_OCTETS}",
+                    self.short_message.len()
+                ),
+            })?;
+        write_c_string(bytes, "service_type", &self.service_type, 6)?;
+        self.source.write(bytes, "source_addr", 21)?;
+        self.destination.write(bytes, "destination_addr", 21)?;
+        bytes.extend([self.esm_class, self.protocol_id, self.priority_flag]);
+        write_c_string(
+            bytes,
+            "schedule_delivery_time",
+            &self.schedule_delivery_time,
+            17,
+        )?;
+        write_c_string(bytes, "validity_period", &self.validity_period, 17)?;
+        bytes.extend([
+            self.registered_delivery,
+            self.replace_if_present_flag,
+            self.data_coding,
+            self.sm_default_msg_id,
+            sm_length,
+        ]);
+        bytes.extend_from_slice(&self.short_message);
+        write_tlvs(bytes, &self.tlvs)
+    }
+}
+
+/// Body of a submit_sm_resp, deliver_sm_resp or data_sm_resp.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubmitSmResp {
+    /// message_id assigned by the SMSC, up to 64 characters.
+    pub message_id: String,
+    /// Optional parameters, allowed in SMPP 5.0 and in data_sm_resp.
+    pub tlvs: Vec<Tlv>,
+}
+
+impl SubmitSmResp {
+    /// A response carrying `message_id`.
+    pub fn new(message_id: &str) -> Self {
+        Self {
+            message_id: message_id.to_string(),
+            tlvs: Vec::new(),
+        }
+    }
+
+    pub(super) fn read(reader: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            message_id: reader.c_string("message_id", 65)?,
+            tlvs: read_tlvs(reader)?,
+        })
+    }
+
+    pub(super) fn write(&self, bytes: &mut Vec<u8>) -> Result<()> {
+        write_c_string(bytes, "message_id", &self.message_id, 65)?;
+        write_tlvs(bytes, &self.tlvs)
+    }
+}
+
+/// Body of a data_sm, which carries its message in a message_payload TLV.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataSm {
+    /// service_type, up to 5 characters, normally empty.
+    pub service_type: String,
+    /// source_addr, up to 64 characters.
+    pub source: SmppAddress,
+    /// destination_addr, up to 64 characters.
+    pub destination: SmppAddress,
+    /// esm_class.
+    pub esm_class: u8,
+    /// registered_delivery.
+    pub registered_delivery: u8,
+    /// data_coding of the message_payload.
+    pub data_coding: u8,
+    /// Optional parameters.
+    pub tlvs: Vec<Tlv>,
+}
+
+impl DataSm {
+    /// A message from `source` to `destination` with every other field empty.
+    pub fn new(source: SmppAddress, destination: SmppAddress) -> Self {
+        Self {
+            source,
+            destination,
+            ..Self::default()
+        }
+    }
+
+    /// Encode `text` as `data_coding` says and store it in message_payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`SubmitSm::set_text`].
+    pub fn set_text(&mut self, text: &str, data_coding: u8) -> Result<()> {
+        self.set_text_with_profile(
+            text,
+            data_coding,
+            &DataCodingProfile::standard(),
+            &Gsm7Config::strict(),
+        )
+    }
+
+    /// Encode `text` as `profile` reads `data_coding` and store it in
+    /// message_payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DataCodingProfile::encode`].
+    pub fn set_text_with_profile(
+        &mut self,
+        text: &str,
+        data_coding: u8,
+        profile: &DataCodingProfile,
+        config: &Gsm7Config,
+    ) -> Result<()> {
+        let octets = profile.encode(text, data_coding, config)?;
+        self.data_coding = data_coding;
+        self.esm_class &= !ESM_UDHI;
+        self.tlvs.retain(|tlv| tlv.tag() != TAG_MESSAGE_PAYLOAD);
+        self.tlvs.push(Tlv::MessagePayload(octets));
+        Ok(())
+    }
+
+    /// The message_payload TLV, if present.
+    pub fn payload(&self) -> Option<&[u8]> {
+        message_payload(&self.tlvs)
+    }
+
+    /// Decode the message_payload text as `data_coding` says.
+    ///
+    /// # Returns
+    ///
+    /// The text, or `None` for binary data or a data_sm without a payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`SubmitSm::text`].
+    pub fn text(&self) -> Result<Option<String>> {
+        self.text_with_profile(&DataCodingProfile::standard(), &Gsm7Config::default())
+    }
+
+    /// Decode the message_payload text as `profile` reads `data_coding`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`SubmitSm::text_with_config`].
+    pub fn text_with_profile(
+        &self,
+        profile: &DataCodingProfile,
+        config: &Gsm7Config,
+    ) -> Result<Option<String>> {
+        match self.payload() {
+            Some(payload) => decode_text(
+                payload,
+                profile.coding(self.data_coding)?,
+                self.esm_class,
+                config,
+            ),
+            None => Ok(None),
+        }
+    }
+
+    /// The receipted_message_id TLV of a delivery receipt.
+    pub fn receipted_message_id(&self) -> Option<&str> {
+        self.tlvs.iter().find_map(|tlv| match tlv {
+            Tlv::ReceiptedMessageId(id) => Some(id.as_str()),
+            _ => None,
+        })
+    }
+
+    pub(super) fn read(reader: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            service_type: reader.c_string("service_type", 6)?,
+            source: SmppAddress::read(reader, "source_addr", 65)?,
+            destination: SmppAddress::read(reader, "destination_addr", 65)?,
+            esm_class: reader.u8("esm_class")?,
+            registered_delivery: reader.u8("registered_delivery")?,
+            data_coding: reader.u8("data_coding")?,
+            tlvs: read_tlvs(reader)?,
+        })
+    }
+
+    pub(super) fn write(&self, bytes: &mut Vec<u8>) -> Result<()> {
+        write_c_string(bytes, "service_type", &self.service_type, 6)?;
+        self.source.write(bytes, "source_addr", 65)?;
+        self.destination.write(bytes, "destination_addr", 65)?;
+        bytes.extend([self.esm_class, self.registered_delivery, self.data_coding]);
+        write_tlvs(bytes, &self.tlvs)
+    }
+}
+
+/// The value of the message_payload TLV in `tlvs`.
+fn message_payload(tlvs: &[Tlv]) -> Option<&[u8]> {
+    tlvs.iter().find_map(|tlv| match tlv {
+        Tlv::MessagePayload(payload) => Some(payload.as_slice()),
+        _ => None,
+    })
+}
+
+/// Decode `payload`, skipping the User Data Header if `esm_class` has UDHI.
+fn decode_text(
+    payload: &[u8],
+    coding: TextCoding,
+    esm_class: u8,
+    config: &Gsm7Config,
+) -> Result<Option<String>> {
+    if esm_class & ESM_UDHI != 0 {
+        let header_len = payload.first().map_or(0, |&udhl| usize::from(udhl) + 1);
+        coding.decode_after_header(payload, header_len, config)
+    } else {
+        coding.decode_with_config(payload, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::smpp::{Pdu, PduBody};
+
+    use super::*;
+
+    fn submit() -> SubmitSm {
+        SubmitSm::new(
+            SmppAddress::international("447700900001"),
+            SmppAddress::international("447700900002"),
+        )
+    }
+
+    #[test]
+    fn test_submit_sm_wire_format() {
+        let mut submit = submit();
+        submit.registered_delivery = 1;
+        submit.set_text("Hi €", 0x00).unwrap();
+        let bytes = Pdu::new(2, PduBody::SubmitSm(submit.clone()))
+            .to_bytes()
+            .unwrap();
+        let body = &bytes[16..];
+        assert_eq!(body[..3], [0, 1, 1]);
+        assert_eq!(
+            body[body.len() - 10..],
+            [1, 0, 0, 0, 5, b'H', b'i', b' ', 0x1B, 0x65]
+        );
+        assert_eq!(
+            Pdu::from_bytes(&bytes).unwrap().body,
+            PduBody::SubmitSm(submit)
+        );
+    }
+
+    #[test]
+    fn test_text_by_data_coding() {
+        let mut submit = submit();
+        for (text, data_coding) in [
+            ("Hello", 0x00),
+            ("Hello", 0x01),
+            ("Grüße", 0x03),
+            ("Привет", 0x08),
+            ("Hello", 0xF0),
+        ] {
+            submit.set_text(text, data_coding).unwrap();
+            assert_eq!(submit.text().unwrap().as_deref(), Some(text));
+        }
+        let submit = SubmitSm::from_text(Default::default(), Default::default(), "Дом").unwrap();
+        assert_eq!(submit.data_coding, 0x08);
+        assert_eq!(submit.short_message, [0x04, 0x14, 0x04, 0x3E, 0x04, 0x3C]);
+    }
+
+    #[test]
+    fn test_text_with_profile() {
+        let packed = DataCodingProfile::gsm7_packed();
+        let submit = SubmitSm::from_text_with_profile(
+            Default::default(),
+            Default::default(),
+            "hellohello",
+            &packed,
+        )
+        .unwrap();
+        assert_eq!(submit.data_coding, 0x00);
+        assert_eq!(
+            submit.short_message,
+            [0xE8, 0x32, 0x9B, 0xFD, 0x46, 0x97, 0xD9, 0xEC, 0x37]
+        );
+        let config = Gsm7Config::default();
+        assert_eq!(
+            submit
+                .text_with_profile(&packed, &config)
+                .unwrap()
+                .as_deref(),
+            Some("hellohello")
+        );
+        assert_ne!(submit.text().unwrap().as_deref(), Some("hellohello"));
+
+        let mut data = DataSm::default();
+        let latin1 = DataCodingProfile::latin1();
+        data.set_text_with_profile("Größe", 0x00, &latin1, &config)
+            .unwrap();
+        assert_eq!(data.payload(), Some(&b"Gr\xF6\xDFe"[..]));
+        assert_eq!(
+            data.text_with_profile(&latin1, &config).unwrap().as_deref(),
+            Some("Größe")
+        );
+    }
+
+    #[test]
+    fn test_long_text_uses_message_payload() {
+        let mut submit = submit();
+        let text = "x".repeat(300);
+        submit.set_text(&text, 0x00).unwrap();
+        assert!(submit.short_message.is_empty());
+        assert_eq!(submit.payload().len(), 300);
+        let bytes = Pdu::new(1, PduBody::SubmitSm(submit)).to_bytes().unwrap();
+        let PduBody::SubmitSm(mut submit) = Pdu::from_bytes(&bytes).unwrap().body else {
+            panic!("not a submit_sm");
+        };
+        assert_eq!(submit.text().unwrap(), Some(text));
+
+        submit.set_text("short", 0x00).unwrap();
+        assert!(submit.tlvs.is_empty());
+    }
+
+    #[test]
+    fn test_udh_is_skipped() {
+        let mut submit = submit();
+        submit.esm_class = ESM_UDHI;
+        submit.short_message = vec![0x05, 0x00, 0x03, 0x2A, 0x02, 0x01, b'A', b'B'];
+        assert_eq!(submit.text().unwrap().as_deref(), Some("AB"));
+
+        let packed = DataCodingProfile::gsm7_packed();
+        let config = Gsm7Config::default();
+        submit.short_message.truncate(6);
+        assert_eq!(
+            submit.text_with_profile(&packed, &config),
+            Ok(Some(String::new()))
+        );
+        submit.short_message = vec![0x05];
+        assert!(matches!(
+            submit.text_with_profile(&packed, &config),
+            Err(Gsm7Error::InvalidPduField { field: "UDHL", .. })
+        ));
+    }
+
+    #[test]
+    fn test_deliver_sm_receipt() {
+        let mut deliver = submit();
+        deliver.esm_class = 0x04;
+        deliver.short_message = b"id:42 stat:DELIVRD".to_vec();
+        deliver.tlvs = vec![
+            Tlv::ReceiptedMessageId("42".to_string()),
+            Tlv::MessageState(2),
+        ];
+        let bytes = Pdu::new(9, PduBody::DeliverSm(deliver.clone()))
+            .to_bytes()
+            .unwrap();
+        assert_eq!(bytes[4..8], [0, 0, 0, 5]);
+        let PduBody::DeliverSm(parsed) = Pdu::from_bytes(&bytes).unwrap().body else {
+            panic!("not a deliver_sm");
+        };
+        assert_eq!(parsed, deliver);
+        assert_eq!(parsed.receipted_message_id(), Some("42"));
+    }
+
+    #[test]
+    fn test_data_sm_and_responses() {
+        let mut data = DataSm::new(
+            SmppAddress::international("447700900001"),
+            SmppAddress::international("447700900002"),
+        );
+        data.set_text("Ünïcødé ✓", 0x08).unwrap();
+        data.tlvs.push(Tlv::SarMsgRefNum(5));
+        let pdu = Pdu::new(4, PduBody::DataSm(data));
+        let parsed = Pdu::from_bytes(&pdu.to_bytes().unwrap()).unwrap();
+        assert_eq!(parsed, pdu);
+        let PduBody::DataSm(data) = parsed.body else {
+            panic!("not a data_sm");
+        };
+        assert_eq!(data.text().unwrap().as_deref(), Some("Ünïcødé ✓"));
+
+        for body in [
+            PduBody::SubmitSmResp(SubmitSmResp::new("abc123")),
+            PduBody::DeliverSmResp(SubmitSmResp::default()),
+            PduBody::DataSmResp(SubmitSmResp::new("def")),
+        ] {
+            let pdu = Pdu::new(4, body);
+            assert_eq!(Pdu::from_bytes(&pdu.to_bytes().unwrap()).unwrap(), pdu);
+        }
+    }
+
+    #[test]
+    fn test_field_limits() {
+        let mut submit = submit();
+        submit.short_message = vec![0; 255];
+        assert!(matches!(
+            Pdu::new(1, PduBody::SubmitSm(submit)).to_bytes(),
+            Err(Gsm7Error::InvalidSmppField {
+                field: "short_message",
+                ..
+            })
+        ));
+        let submit = SubmitSm::new(
+            SmppAddress::international(&"1".repeat(21)),
+            Default::default(),
+        );
+        assert!(matches!(
+            Pdu::new(1, PduBody::SubmitSm(submit)).to_bytes(),
+            Err(Gsm7Error::InvalidSmppField {
+                field: "source_addr",
+                ..
+            })
+        ));
+    }
+}
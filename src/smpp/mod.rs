@@ -0,0 +1,465 @@
+//! SMPP 3.4 and 5.0 protocol data units.
+//!
+//! Covers the PDUs a gateway needs to send and receive messages: the binds,
+//! submit_sm, deliver_sm, data_sm, their responses, enquire_link, unbind and
+//! generic_nack. short_message and message_payload text goes through the
+//! crate's GSM 7-bit and UCS-2 encoders, chosen by data_coding.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gsm7_alt::smpp::{Pdu, PduBody, SmppAddress, SubmitSm};
+//!
+//! let submit = SubmitSm::from_text(
+//!     SmppAddress::alphanumeric("Shop"),
+//!     SmppAddress::international("447700900123"),
+//!     "Your code is 1234 €",
+//! )?;
+//! let bytes = Pdu::new(1, PduBody::SubmitSm(submit)).to_bytes()?;
+//!
+//! let pdu = Pdu::from_bytes(&bytes)?;
+//! let PduBody::SubmitSm(submit) = pdu.body else { unreachable!() };
+//! assert_eq!(submit.data_coding, 0x00);
+//! assert_eq!(submit.text()?.as_deref(), Some("Your code is 1234 €"));
+//! # Ok::<(), gsm7_alt::Gsm7Error>(())
+//! ```
+
+mod bind;
+mod coding;
+mod message;
+mod profile;
+mod receipt;
+mod simulator;
+mod tlv;
+
+pub use bind::{Bind, BindResp};
+pub use coding::TextCoding;
+pub use message::{DataSm, DeliverSm, SmppAddress, SubmitSm, SubmitSmResp};
+pub use profile::DataCodingProfile;
+pub use receipt::{DeliveryReceipt, ReceiptField, ReceiptState};
+pub use simulator::{MoMessage, SmscOptions, SmscSimulator, StoredMessage};
+pub use tlv::Tlv;
+
+use crate::reader::Reader;
+use crate::{Gsm7Error, Result};
+
+/// command_status: no error.
+pub const ESME_ROK: u32 = 0x0000_0000;
+/// command_status: invalid command length.
+pub const ESME_RINVCMDLEN: u32 = 0x0000_0002;
+/// command_status: invalid command id.
+pub const ESME_RINVCMDID: u32 = 0x0000_0003;
+/// command_status: incorrect bind status for the command.
+pub const ESME_RINVBNDSTS: u32 = 0x0000_0004;
+/// command_status: the ESME is already bound.
+pub const ESME_RALYBND: u32 = 0x0000_0005;
+/// command_status: system error.
+pub const ESME_RSYSERR: u32 = 0x0000_0008;
+/// command_status: bind failed.
+pub const ESME_RBINDFAIL: u32 = 0x0000_000D;
+/// command_status: invalid password.
+pub const ESME_RINVPASWD: u32 = 0x0000_000E;
+/// command_status: invalid system id.
+pub const ESME_RINVSYSID: u32 = 0x0000_000F;
+/// command_status: submit_sm or submit_multi failed.
+pub const ESME_RSUBMITFAIL: u32 = 0x0000_0045;
+/// command_status: invalid data_coding.
+pub const ESME_RINVDCS: u32 = 0x0000_0104;
+
+/// Length of the PDU header: command_length, command_id, command_status and
+/// sequence_number.
+pub const HEADER_OCTETS: usize = 16;
+
+/// Bit set in the command_id of every response.
+const RESPONSE: u32 = 0x8000_0000;
+
+const GENERIC_NACK: u32 = 0x8000_0000;
+const BIND_RECEIVER: u32 = 0x0000_0001;
+const BIND_TRANSMITTER: u32 = 0x0000_0002;
+const SUBMIT_SM: u32 = 0x0000_0004;
+const DELIVER_SM: u32 = 0x0000_0005;
+const UNBIND: u32 = 0x0000_0006;
+const BIND_TRANSCEIVER: u32 = 0x0000_0009;
+const ENQUIRE_LINK: u32 = 0x0000_0015;
+const DATA_SM: u32 = 0x0000_0103;
+
+/// One SMPP PDU: the header fields and the body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pdu {
+    /// command_status: `ESME_ROK` in requests, the outcome in responses.
+    pub command_status: u32,
+    /// sequence_number, echoed in the response.
+    pub sequence_number: u32,
+    /// The command and its fields.
+    pub body: PduBody,
+}
+
+/// The command of a PDU and its mandatory and optional fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PduBody {
+    /// bind_transmitter.
+    BindTransmitter(Bind),
+    /// bind_transmitter_resp.
+    BindTransmitterResp(BindResp),
+    /// bind_receiver.
+    BindReceiver(Bind),
+    /// bind_receiver_resp.
+    BindReceiverResp(BindResp),
+    /// bind_transceiver.
+    BindTransceiver(Bind),
+    /// bind_transceiver_resp.
+    BindTransceiverResp(BindResp),
+    /// submit_sm.
+    SubmitSm(SubmitSm),
+    /// submit_sm_resp.
+    SubmitSmResp(SubmitSmResp),
+    /// deliver_sm.
+    DeliverSm(DeliverSm),
+    /// deliver_sm_resp; the message_id is unused and normally empty.
+    DeliverSmResp(SubmitSmResp),
+    /// data_sm.
+    DataSm(DataSm),
+    /// data_sm_resp.
+    DataSmResp(SubmitSmResp),
+    /// enquire_link.
+    EnquireLink,
+    /// enquire_link_resp.
+    EnquireLinkResp,
+    /// unbind.
+    Unbind,
+    /// unbind_resp.
+    UnbindResp,
+    /// generic_nack, the response to a PDU that could not be understood.
+    GenericNack,
+}
+
+impl PduBody {
+    /// The command_id of this command.
+    pub fn command_id(&self) -> u32 {
+        match self {
+            PduBody::BindTransmitter(_) => BIND_TRANSMITTER,
+            PduBody::BindTransmitterResp(_) => BIND_TRANSMITTER | RESPONSE,
+            PduBody::BindReceiver(_) => BIND_RECEIVER,
+            PduBody::BindReceiverResp(_) => BIND_RECEIVER | RESPONSE,
+            PduBody::BindTransceiver(_) => BIND_TRANSCEIVER,
+            PduBody::BindTransceiverResp(_) => BIND_TRANSCEIVER | RESPONSE,
+            PduBody::SubmitSm(_) => SUBMIT_SM,
+            PduBody::SubmitSmResp(_) => SUBMIT_SM | RESPONSE,
+            PduBody::DeliverSm(_) => DELIVER_SM,
+            PduBody::DeliverSmResp(_) => DELIVER_SM | RESPONSE,
+            PduBody::DataSm(_) => DATA_SM,
+            PduBody::DataSmResp(_) => DATA_SM | RESPONSE,
+            PduBody::EnquireLink => ENQUIRE_LINK,
+            PduBody::EnquireLinkResp => ENQUIRE_LINK | RESPONSE,
+            PduBody::Unbind => UNBIND,
+            PduBody::UnbindResp => UNBIND | RESPONSE,
+            PduBody::GenericNack => GENERIC_NACK,
+        }
+    }
+
+    /// Whether this is a response rather than a request.
+    pub fn is_response(&self) -> bool {
+        self.command_id() & RESPONSE != 0
+    }
+
+    fn read(command_id: u32, command_status: u32, reader: &mut Reader) -> Result<Self> {
+        // Error responses may leave out the body
+        let resp = |reader: &mut Reader| {
+            if command_status != ESME_ROK && reader.is_empty() {
+                Ok(SubmitSmResp::default())
+            } else {
+                SubmitSmResp::read(reader)
+            }
+        };
+        let bind_resp = |reader: &mut Reader| {
+            if command_status != ESME_ROK && reader.is_empty() {
+                Ok(BindResp::default())
+            } else {
+                BindResp::read(reader)
+            }
+        };
+        let body = match command_id {
+            BIND_TRANSMITTER => PduBody::BindTransmitter(Bind::read(reader)?),
+            BIND_RECEIVER => PduBody::BindReceiver(Bind::read(reader)?),
+            BIND_TRANSCEIVER => PduBody::BindTransceiver(Bind::read(reader)?),
+            SUBMIT_SM => PduBody::SubmitSm(SubmitSm::read(reader)?),
+            DELIVER_SM => PduBody::DeliverSm(SubmitSm::read(reader)?),
+            DATA_SM => PduBody::DataSm(DataSm::read(reader)?),
+            ENQUIRE_LINK => PduBody::EnquireLink,
+            UNBIND => PduBody::Unbind,
+            GENERIC_NACK => PduBody::GenericNack,
+            _ if command_id == BIND_TRANSMITTER | RESPONSE => {
+                PduBody::BindTransmitterResp(bind_resp(reader)?)
+            }
+            _ if command_id == BIND_RECEIVER | RESPONSE => {
+                PduBody::BindReceiverResp(bind_resp(reader)?)
+            }
+            _ if command_id == BIND_TRANSCEIVER | RESPONSE => {
+                PduBody::BindTransceiverResp(bind_resp(reader)?)
+            }
+            _ if command_id == SUBMIT_SM | RESPONSE => PduBody::SubmitSmResp(resp(reader)?),
+            _ if command_id == DELIVER_SM | RESPONSE => PduBody::DeliverSmResp(resp(reader)?),
+            _ if command_id == DATA_SM | RESPONSE => PduBody::DataSmResp(resp(reader)?),
+            _ if command_id == ENQUIRE_LINK | RESPONSE => PduBody::EnquireLinkResp,
+            _ if command_id == UNBIND | RESPONSE => PduBody::UnbindResp,
+            _ => {
+                return Err(Gsm7Error::InvalidPduField {
+                    field: "command_id",
+                    offset: 4,
+                    reason: format!("unsupported command 0x{command_id:08X}"),
+                });
+            }
+        };
+        reader.finish("PDU body")?;
+        Ok(body)
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>, command_status: u32) -> Result<()> {
+        match self {
+            PduBody::BindTransmitter(bind)
+            | PduBody::BindReceiver(bind)
+            | PduBody::BindTransceiver(bind) => bind.write(bytes),
+            PduBody::BindTransmitterResp(resp)
+            | PduBody::BindReceiverResp(resp)
+            | PduBody::BindTransceiverResp(resp) => {
+                if command_status != ESME_ROK && *resp == BindResp::default() {
+                    return Ok(());
+                }
+                resp.write(bytes)
+            }
+            PduBody::SubmitSm(submit) | PduBody::DeliverSm(submit) => submit.write(bytes),
+            PduBody::SubmitSmResp(resp)
+            | PduBody::DeliverSmResp(resp)
+            | PduBody::DataSmResp(resp) => {
+                if command_status != ESME_ROK && *resp == SubmitSmResp::default() {
+                    return Ok(());
+                }
+                resp.write(bytes)
+            }
+            PduBody::DataSm(data) => data.write(bytes),
+            PduBody::EnquireLink
+            | PduBody::EnquireLinkResp
+            | PduBody::Unbind
+            | PduBody::UnbindResp
+            | PduBody::GenericNack => Ok(()),
+        }
+    }
+}
+
+impl Pdu {
+    /// A request, or a successful response, with `ESME_ROK` status.
+    pub fn new(sequence_number: u32, body: PduBody) -> Self {
+        Self {
+            command_status: ESME_ROK,
+            sequence_number,
+            body,
+        }
+    }
+
+    /// A generic_nack for the PDU with `sequence_number`.
+    pub fn generic_nack(sequence_number: u32, command_status: u32) -> Self {
+        Self {
+            command_status,
+            sequence_number,
+            body: PduBody::GenericNack,
+        }
+    }
+
+    /// The command_length of a PDU from its first four octets, for framing
+    /// PDUs on a stream.
+    ///
+    /// # Returns
+    ///
+    /// The length of the whole PDU including the header, or `None` if fewer
+    /// than four octets are available.
+    pub fn command_length(bytes: &[u8]) -> Option<usize> {
+        let length: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+        Some(u32::from_be_bytes(length) as usize)
+    }
+
+    /// Parse one PDU.
+    ///
+    /// C-octet strings are read as ISO-8859-1. Error responses without a
+    /// body parse with empty fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Exactly one PDU, header included
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::TruncatedPdu` if the PDU is shorter than its
+    /// command_length or a field runs past the end, and
+    /// `Gsm7Error::InvalidPduField` for a bad command_length, an unsupported
+    /// command_id, an unterminated or overlong C-octet string, or trailing
+    /// octets.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        let command_length = reader.u32("command_length")? as usize;
+        if command_length < HEADER_OCTETS {
+            return Err(Gsm7Error::InvalidPduField {
+                field: "command_length",
+                offset: 0,
+                reason: format!("{command_length} is shorter than the header"),
+            });
+        }
+        if bytes.len() < command_length {
+            return Err(Gsm7Error::TruncatedPdu {
+                field: "PDU body",
+                offset: bytes.len(),
+            });
+        }
+        if bytes.len() > command_length {
+            return Err(Gsm7Error::InvalidPduField {
+                field: "command_length",
+                offset: 0,
+                reason: format!("{command_length}, but {} octets were given", bytes.len()),
+            });
+        }
+        let command_id = reader.u32("command_id")?;
+        let command_status = reader.u32("command_status")?;
+        let sequence_number = reader.u32("sequence_number")?;
+        let body = PduBody::read(command_id, command_status, &mut reader)?;
+        Ok(Self {
+            command_status,
+            sequence_number,
+            body,
+        })
+    }
+
+    /// Serialize the PDU, header included.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidSmppField` if a C-octet string is too long
+    /// or holds a NUL or a character above U+00FF, a short_message is longer
+    /// than 254 octets, or a TLV value is longer than 65535 octets.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![0; 4];
+        bytes.extend_from_slice(&self.body.command_id().to_be_bytes());
+        bytes.extend_from_slice(&self.command_status.to_be_bytes());
+        bytes.extend_from_slice(&self.sequence_number.to_be_bytes());
+        self.body.write(&mut bytes, self.command_status)?;
+        let length = u32::try_from(bytes.len()).map_err(|_| Gsm7Error::InvalidSmppField {
+            field: "command_length",
+            reason: format!("PDU is {} octets", bytes.len()),
+        })?;
+        bytes[..4].copy_from_slice(&length.to_be_bytes());
+        Ok(bytes)
+    }
+}
+
+/// Append `value` as a C-octet string of at most `max` octets, NUL included.
+fn write_c_string(bytes: &mut Vec<u8>, field: &'static str, value: &str, max: usize) -> Result<()> {
+    let invalid = |reason: String| Gsm7Error::InvalidSmppField { field, reason };
+    let octets = value
+        .chars()
+        .map(|ch| u8::try_from(ch).ok().filter(|&octet| octet != 0))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid("not an ISO-8859-1 string without NUL".to_string()))?;
+    if octets.len() >= max {
+        return Err(invalid(format!(
+            "{} octets, more than the maximum of {}",
+            octets.len(),
+            max - 1
+        )));
+    }
+    bytes.extend(octets);
+    bytes.push(0);
+    Ok(())
+}
+
+/// Append every TLV in `tlvs`.
+fn write_tlvs(bytes: &mut Vec<u8>, tlvs: &[Tlv]) -> Result<()> {
+    tlvs.iter().try_for_each(|tlv| tlv.write(bytes))
+}
+
+/// Every TLV up to the end of the PDU.
+fn read_tlvs(reader: &mut Reader) -> Result<Vec<Tlv>> {
+    let mut tlvs = Vec::new();
+    while !reader.is_empty() {
+        let tag = reader.u16("TLV tag")?;
+        let length = reader.u16("TLV length")?;
+        tlvs.push(Tlv::parse(
+            tag,
+            reader.take(usize::from(length), "TLV value")?,
+        ));
+    }
+    Ok(tlvs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enquire_link_wire_format() {
+        let bytes = Pdu::new(7, PduBody::EnquireLink).to_bytes().unwrap();
+        assert_eq!(bytes, [0, 0, 0, 16, 0, 0, 0, 0x15, 0, 0, 0, 0, 0, 0, 0, 7]);
+        assert_eq!(Pdu::command_length(&bytes), Some(16));
+        assert_eq!(
+            Pdu::from_bytes(&bytes).unwrap(),
+            Pdu::new(7, PduBody::EnquireLink)
+        );
+    }
+
+    #[test]
+    fn test_error_response_without_body() {
+        let nack = Pdu {
+            command_status: ESME_RSUBMITFAIL,
+            sequence_number: 3,
+            body: PduBody::SubmitSmResp(SubmitSmResp::default()),
+        };
+        let bytes = nack.to_bytes().unwrap();
+        assert_eq!(bytes.len(), HEADER_OCTETS);
+        assert_eq!(Pdu::from_bytes(&bytes).unwrap(), nack);
+        assert!(nack.body.is_response());
+    }
+
+    #[test]
+    fn test_framing_errors() {
+        let bytes = Pdu::new(1, PduBody::Unbind).to_bytes().unwrap();
+        assert_eq!(
+            Pdu::from_bytes(&bytes[..12]),
+            Err(Gsm7Error::TruncatedPdu {
+                field: "PDU body",
+                offset: 12,
+            })
+        );
+        let mut long = bytes.clone();
+        long.push(0);
+        assert!(matches!(
+            Pdu::from_bytes(&long),
+            Err(Gsm7Error::InvalidPduField {
+                field: "command_length",
+                ..
+            })
+        ));
+        let mut unknown = bytes;
+        unknown[7] = 0x99;
+        assert!(matches!(
+            Pdu::from_bytes(&unknown),
+            Err(Gsm7Error::InvalidPduField {
+                field: "command_id",
+                ..
+            })
+        ));
+        assert_eq!(Pdu::command_length(&[0, 0]), None);
+    }
+
+    #[test]
+    fn test_c_string_limits() {
+        let mut bytes = Vec::new();
+        assert!(write_c_string(&mut bytes, "system_id", "0123456789abcdef", 16).is_err());
+        assert!(write_c_string(&mut bytes, "system_id", "a\0b", 16).is_err());
+        write_c_string(&mut bytes, "system_id", "Zürich", 16).unwrap();
+        assert_eq!(
+            Reader::new(&bytes).c_string("system_id", 16).unwrap(),
+            "Zürich"
+        );
+        assert!(matches!(
+            Reader::new(b"abc").c_string("system_id", 16),
+            Err(Gsm7Error::TruncatedPdu { .. })
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+//! Operator data_coding profiles.
+//!
+//! SMPP leaves data_coding 0x00 to the SMSC ("SMSC default alphabet"), and
+//! SMSCs differ: most take unpacked GSM 7-bit, some packed GSM 7-bit, some
+//! ISO-8859-1 or IA5. A profile records how one SMSC reads each data_coding
+//! value so the same gateway code works against all of them.
+
+use std::collections::BTreeMap;
+
+use super::TextCoding;
+use crate::{Gsm7Config, Result};
+
+/// How an SMSC maps data_coding values to text codings.
+///
+/// Values without an override follow [`TextCoding::for_data_coding`].
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::Gsm7Config;
+/// use gsm7_alt::smpp::{DataCodingProfile, TextCoding};
+///
+/// let profile = DataCodingProfile::named("gsm7-packed").unwrap();
+/// assert_eq!(profile.coding(0x00)?, TextCoding::Gsm7Packed);
+/// assert_eq!(profile.coding(0x08)?, TextCoding::Ucs2);
+///
+/// let octets = profile.encode("hellohello", 0x00, &Gsm7Config::strict())?;
+/// assert_eq!(octets, [0xE8, 0x32, 0x9B, 0xFD, 0x46, 0x97, 0xD9, 0xEC, 0x37]);
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataCodingProfile {
+    /// Name of the profile, for configuration files and logs.
+    pub name: String,
+    /// Codings that differ from the SMPP 3.4 table, by data_coding value.
+    pub overrides: BTreeMap<u8, TextCoding>,
+}
+
+impl Default for DataCodingProfile {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl DataCodingProfile {
+    /// Names accepted by [`named`](Self::named).
+    pub const NAMES: [&'static str; 4] = ["standard", "gsm7-packed", "latin1", "ia5"];
+
+    /// A profile called `name` with no overrides.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// The SMPP 3.4 table, with data_coding 0x00 as unpacked GSM 7-bit.
+    pub fn standard() -> Self {
+        Self::new("standard")
+    }
+
+    /// data_coding 0x00 and the GSM 7-bit general and message class codings
+    /// as packed GSM 7-bit.
+    pub fn gsm7_packed() -> Self {
+        let mut profile = Self::new("gsm7-packed").with(0x00, TextCoding::Gsm7Packed);
+        profile.overrides.extend(
+            (0x10..=0xFF)
+                .filter(|&data_coding| {
+                    TextCoding::for_data_coding(data_coding) == Ok(TextCoding::Gsm7)
+                })
+                .map(|data_coding| (data_coding, TextCoding::Gsm7Packed)),
+        );
+        profile
+    }
+
+    /// data_coding 0x00 as ISO-8859-1.
+    pub fn latin1() -> Self {
+        Self::new("latin1").with(0x00, TextCoding::Latin1)
+    }
+
+    /// data_coding 0x00 as IA5 (ASCII).
+    pub fn ia5() -> Self {
+        Self::new("ia5").with(0x00, TextCoding::Ascii)
+    }
+
+    /// The built-in profile called `name`, one of [`NAMES`](Self::NAMES).
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Self::standard()),
+            "gsm7-packed" => Some(Self::gsm7_packed()),
+            "latin1" => Some(Self::latin1()),
+            "ia5" => Some(Self::ia5()),
+            _ => None,
+        }
+    }
+
+    /// The same profile with `data_coding` read as `coding`.
+    pub fn with(mut self, data_coding: u8, coding: TextCoding) -> Self {
+        self.overrides.insert(data_coding, coding);
+        self
+    }
+
+    /// The coding of `data_coding` under this profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::UnsupportedDataCoding` if the value has no
+    /// override and carries no supported text.
+    pub fn coding(&self, data_coding: u8) -> Result<TextCoding> {
+        match self.overrides.get(&data_coding) {
+            Some(&coding) => Ok(coding),
+            None => TextCoding::for_data_coding(data_coding),
+        }
+    }
+
+    /// The data_coding to send `text` with: 0x00 if the SMSC default
+    /// alphabet holds it, 0x08 (UCS-2) otherwise.
+    pub fn data_coding_for(&self, text: &str) -> u8 {
+        let fits = self
+            .coding(0x00)
+            .and_then(|coding| coding.encode_with_config(text, &Gsm7Config::strict()))
+            .is_ok();
+        if fits { 0x00 } else { 0x08 }
+    }
+
+    /// Encode `text` for `data_coding` under this profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`coding`](Self::coding) and
+    /// [`TextCoding::encode_with_config`].
+    pub fn encode(&self, text: &str, data_coding: u8, config: &Gsm7Config) -> Result<Vec<u8>> {
+        self.coding(data_coding)?.encode_with_config(text, config)
+    }
+
+    /// Decode `octets` sent with `data_coding` under this profile.
+    ///
+    /// # Returns
+    ///
+    /// The text, or `None` for binary data.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`coding`](Self::coding) and
+    /// [`TextCoding::decode_with_config`].
+    pub fn decode(
+        &self,
+        octets: &[u8],
+        data_coding: u8,
+        config: &Gsm7Config,
+    ) -> Result<Option<String>> {
+        self.coding(data_coding)?.decode_with_config(octets, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Gsm7Error;
+
+    #[test]
+    fn test_default_alphabet_by_profile() {
+        let expected = [
+            ("standard", TextCoding::Gsm7),
+            ("gsm7-packed", TextCoding::Gsm7Packed),
+            ("latin1", TextCoding::Latin1),
+            ("ia5", TextCoding::Ascii),
+        ];
+        for (name, coding) in expected {
+            let profile = DataCodingProfile::named(name).unwrap();
+            assert_eq!(profile.name, name);
+            assert_eq!(profile.coding(0x00), Ok(coding));
+            assert_eq!(profile.coding(0x08), Ok(TextCoding::Ucs2));
+        }
+        assert_eq!(DataCodingProfile::named("carrier-x"), None);
+        assert_eq!(DataCodingProfile::NAMES.len(), expected.len());
+    }
+
+    #[test]
+    fn test_gsm7_packed_covers_dcs_groups() {
+        let profile = DataCodingProfile::gsm7_packed();
+        assert_eq!(profile.coding(0xF0), Ok(TextCoding::Gsm7Packed));
+        assert_eq!(profile.coding(0x11), Ok(TextCoding::Gsm7Packed));
+        assert_eq!(profile.coding(0xF4), Ok(TextCoding::Binary));
+        assert_eq!(profile.coding(0x18), Ok(TextCoding::Ucs2));
+    }
+
+    #[test]
+    fn test_overrides_and_text() {
+        let profile = DataCodingProfile::new("carrier").with(0x06, TextCoding::Latin1);
+        let config = Gsm7Config::default();
+        let octets = profile.encode("Ünïcödé", 0x06, &config).unwrap();
+        assert_eq!(octets.len(), 7);
+        assert_eq!(
+            profile.decode(&octets, 0x06, &config).unwrap().as_deref(),
+            Some("Ünïcödé")
+        );
+        assert_eq!(
+            DataCodingProfile::standard().coding(0x06),
+            Err(Gsm7Error::UnsupportedDataCoding { dcs: 0x06 })
+        );
+
+        let latin1 = DataCodingProfile::latin1();
+        assert_eq!(latin1.data_coding_for("Größe"), 0x00);
+        assert_eq!(latin1.data_coding_for("€"), 0x08);
+        assert_eq!(DataCodingProfile::standard().data_coding_for("€"), 0x00);
+        assert_eq!(DataCodingProfile::ia5().data_coding_for("é"), 0x08);
+    }
+}
@@ -0,0 +1,539 @@
+//! Delivery receipt text (SMPP 3.4 Appendix B).
+//!
+//! SMSCs report delivery in the text of a deliver_sm, in the de-facto format
+//!
+//! ```text
+//! id:IIIIIIIIII sub:SSS dlvrd:DDD submit date:YYMMDDhhmm done date:YYMMDDhhmm stat:DDDDDDD err:E text:...
+//! ```
+//!
+//! with variations between carriers: upper-case or underscored keys, seconds
+//! in the dates, full state names, fields left out or extra fields added.
+//!
+//! The dates name no time zone. They are read as written, in whatever zone
+//! the SMSC uses, and are not converted to UTC.
+
+use super::{DataCodingProfile, DeliverSm, Tlv};
+use crate::pdu::UtcDateTime;
+use crate::{Gsm7Config, Gsm7Error, Result};
+
+/// A field of a delivery receipt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ReceiptField {
+    /// `id`: the message id from the submit_sm_resp.
+    Id,
+    /// `sub`: number of messages submitted.
+    Submitted,
+    /// `dlvrd`: number of messages delivered.
+    Delivered,
+    /// `submit date`.
+    SubmitDate,
+    /// `done date`.
+    DoneDate,
+    /// `stat`: final state.
+    State,
+    /// `err`: network or SMSC error code.
+    Error,
+    /// `text`: the start of the original message.
+    Text,
+}
+
+impl ReceiptField {
+    /// Every field, in the order of the format.
+    pub const ALL: [ReceiptField; 8] = [
+        ReceiptField::Id,
+        ReceiptField::Submitted,
+        ReceiptField::Delivered,
+        ReceiptField::SubmitDate,
+        ReceiptField::DoneDate,
+        ReceiptField::State,
+        ReceiptField::Error,
+        ReceiptField::Text,
+    ];
+
+    /// Key spellings, lower case, longest first where one is a prefix of
+    /// another.
+    fn keys(self) -> &'static [&'static str] {
+        match self {
+            ReceiptField::Id => &["id"],
+            ReceiptField::Submitted => &["sub"],
+            ReceiptField::Delivered => &["dlvrd", "dlvd"],
+            ReceiptField::SubmitDate => &["submit date", "submit_date", "submitdate"],
+            ReceiptField::DoneDate => &["done date", "done_date", "donedate"],
+            ReceiptField::State => &["status", "state", "stat"],
+            ReceiptField::Error => &["error", "err"],
+            ReceiptField::Text => &["text"],
+        }
+    }
+}
+
+/// Final state of a message (the `stat` field).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReceiptState {
+    /// DELIVRD.
+    Delivered,
+    /// EXPIRED.
+    Expired,
+    /// DELETED.
+    Deleted,
+    /// UNDELIV.
+    Undeliverable,
+    /// ACCEPTD.
+    Accepted,
+    /// UNKNOWN.
+    Unknown,
+    /// REJECTD.
+    Rejected,
+    /// ENROUTE.
+    Enroute,
+    /// A state this parser does not know, as given.
+    Other(String),
+}
+
+impl ReceiptState {
+    /// Parse a state, accepting the seven-letter codes and the full words,
+    /// such as `UNDELIVERED` or `REJECTED`, in any case.
+    pub fn parse(value: &str) -> Self {
+        match value.to_ascii_uppercase().as_str() {
+            "DELIVRD" | "DELIVERED" => ReceiptState::Delivered,
+            "EXPIRED" => ReceiptState::Expired,
+            "DELETED" | "DELETD" => ReceiptState::Deleted,
+            "UNDELIV" | "UNDELIVERED" | "UNDELIVERABLE" => ReceiptState::Undeliverable,
+            "ACCEPTD" | "ACCEPTED" => ReceiptState::Accepted,
+            "UNKNOWN" => ReceiptState::Unknown,
+            "REJECTD" | "REJECTED" | "REJECT" => ReceiptState::Rejected,
+            "ENROUTE" => ReceiptState::Enroute,
+            _ => ReceiptState::Other(value.to_string()),
+        }
+    }
+
+    /// The state for a message_state TLV value.
+    pub fn from_message_state(state: u8) -> Self {
+        match state {
+            1 => ReceiptState::Enroute,
+            2 => ReceiptState::Delivered,
+            3 => ReceiptState::Expired,
+            4 => ReceiptState::Deleted,
+            5 => ReceiptState::Undeliverable,
+            6 => ReceiptState::Accepted,
+            7 => ReceiptState::Unknown,
+            8 => ReceiptState::Rejected,
+            _ => ReceiptState::Other(state.to_string()),
+        }
+    }
+
+    /// The seven-letter code used in receipts.
+    pub fn code(&self) -> &str {
+        match self {
+            ReceiptState::Delivered => "DELIVRD",
+            ReceiptState::Expired => "EXPIRED",
+            ReceiptState::Deleted => "DELETED",
+            ReceiptState::Undeliverable => "UNDELIV",
+            ReceiptState::Accepted => "ACCEPTD",
+            ReceiptState::Unknown => "UNKNOWN",
+            ReceiptState::Rejected => "REJECTD",
+            ReceiptState::Enroute => "ENROUTE",
+            ReceiptState::Other(value) => value,
+        }
+    }
+
+    /// Whether the message will not change state again.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, ReceiptState::Enroute | ReceiptState::Accepted)
+    }
+}
+
+/// A parsed delivery receipt.
+///
+/// Fields that are absent or cannot be read are `None` and listed in
+/// `missing`.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::smpp::{DeliveryReceipt, ReceiptField, ReceiptState};
+///
+/// let receipt = DeliveryReceipt::parse(
+///     "id:0A1B2C3D sub:001 dlvrd:001 submit date:2403151230 \
+///      done date:2403151231 stat:DELIVRD err:000 text:Your code is 1234",
+/// )?;
+/// assert_eq!(receipt.id.as_deref(), Some("0A1B2C3D"));
+/// assert_eq!(receipt.state, Some(ReceiptState::Delivered));
+/// assert_eq!(receipt.done_date.unwrap().minute, 31);
+/// assert!(receipt.missing.is_empty());
+///
+/// let receipt = DeliveryReceipt::parse("ID:42 STAT:UNDELIV ERR:34")?;
+/// assert_eq!(receipt.error, Some(34));
+/// assert!(receipt.missing.contains(&ReceiptField::DoneDate));
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryReceipt {
+    /// The message id from the submit_sm_resp.
+    pub id: Option<String>,
+    /// Number of messages submitted.
+    pub submitted: Option<u32>,
+    /// Number of messages delivered.
+    pub delivered: Option<u32>,
+    /// When the message was submitted, as written. No time zone is implied:
+    /// it is the SMSC's, often local time, despite the `UtcDateTime` type.
+    pub submit_date: Option<UtcDateTime>,
+    /// When the message reached its final state, as written, in the same
+    /// zone as `submit_date`.
+    pub done_date: Option<UtcDateTime>,
+    /// The final state.
+    pub state: Option<ReceiptState>,
+    /// The error code; decimal, or hex with a `0x` prefix.
+    pub error: Option<u32>,
+    /// The start of the original message.
+    pub text: Option<String>,
+    /// Fields that were absent or could not be read, in format order.
+    pub missing: Vec<ReceiptField>,
+}
+
+impl DeliveryReceipt {
+    /// Parse receipt text.
+    ///
+    /// Keys are matched without regard to case, with spaces or underscores
+    /// in the two-word keys and optional spaces before the colon. Unknown
+    /// fields are skipped. `text` runs to the end, colons included. Dates
+    /// may have 10 (YYMMDDhhmm), 12 (with seconds) or 14 (four-digit year)
+    /// digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::MalformedData` if no receipt field is found.
+    pub fn parse(text: &str) -> Result<Self> {
+        let values = split_fields(text);
+        if values.iter().all(Option::is_none) {
+            return Err(Gsm7Error::MalformedData {
+                reason: "no delivery receipt fields found".to_string(),
+            });
+        }
+        let value = |field: ReceiptField| values[field as usize];
+
+        let mut receipt = Self {
+            id: value(ReceiptField::Id)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string),
+            submitted: value(ReceiptField::Submitted).and_then(|count| count.parse().ok()),
+            delivered: value(ReceiptField::Delivered).and_then(|count| count.parse().ok()),
+            submit_date: value(ReceiptField::SubmitDate).and_then(parse_date),
+            done_date: value(ReceiptField::DoneDate).and_then(parse_date),
+            state: value(ReceiptField::State)
+                .filter(|state| !state.is_empty())
+                .map(ReceiptState::parse),
+            error: value(ReceiptField::Error).and_then(parse_error),
+            text: value(ReceiptField::Text).map(str::to_string),
+            missing: Vec::new(),
+        };
+        receipt.update_missing();
+        Ok(receipt)
+    }
+
+    /// Parse the receipt in a deliver_sm, decoding its text under the
+    /// standard data_coding profile.
+    ///
+    /// See [`from_deliver_sm_with_profile`](Self::from_deliver_sm_with_profile).
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of
+    /// [`from_deliver_sm_with_profile`](Self::from_deliver_sm_with_profile).
+    pub fn from_deliver_sm(deliver: &DeliverSm) -> Result<Self> {
+        Self::from_deliver_sm_with_profile(
+            deliver,
+            &DataCodingProfile::standard(),
+            &Gsm7Config::default(),
+        )
+    }
+
+    /// Parse the receipt in a deliver_sm.
+    ///
+    /// The short_message or message_payload is decoded as `profile` reads
+    /// its data_coding; a binary payload is read as ISO-8859-1. The
+    /// receipted_message_id and message_state TLVs fill in the id and state
+    /// when the text lacks them.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`SubmitSm::text_with_profile`](super::SubmitSm::text_with_profile),
+    /// and `Gsm7Error::MalformedData` if neither the text nor the TLVs hold a
+    /// receipt field.
+    pub fn from_deliver_sm_with_profile(
+        deliver: &DeliverSm,
+        profile: &DataCodingProfile,
+        config: &Gsm7Config,
+    ) -> Result<Self> {
+        let text = match deliver.text_with_profile(profile, config)? {
+            Some(text) => text,
+            None => deliver
+                .payload()
+                .iter()
+                .map(|&octet| char::from(octet))
+                .collect(),
+        };
+        let tlv_id = deliver.receipted_message_id();
+        let tlv_state = deliver.tlvs.iter().find_map(|tlv| match tlv {
+            Tlv::MessageState(state) => Some(*state),
+            _ => None,
+        });
+
+        let mut receipt = match Self::parse(&text) {
+            Ok(receipt) => receipt,
+            Err(error) if tlv_id.is_none() && tlv_state.is_none() => return Err(error),
+            Err(_) => Self::empty(),
+        };
+        if receipt.id.is_none() {
+            receipt.id = tlv_id.map(str::to_string);
+        }
+        if receipt.state.is_none() {
+            receipt.state = tlv_state.map(ReceiptState::from_message_state);
+        }
+        receipt.update_missing();
+        Ok(receipt)
+    }
+
+    fn empty() -> Self {
+        Self {
+            id: None,
+            submitted: None,
+            delivered: None,
+            submit_date: None,
+            done_date: None,
+            state: None,
+            error: None,
+            text: None,
+            missing: Vec::new(),
+        }
+    }
+
+    fn update_missing(&mut self) {
+        self.missing = ReceiptField::ALL
+            .into_iter()
+            .filter(|field| match field {
+                ReceiptField::Id => self.id.is_none(),
+                ReceiptField::Submitted => self.submitted.is_none(),
+                ReceiptField::Delivered => self.delivered.is_none(),
+                ReceiptField::SubmitDate => self.submit_date.is_none(),
+                ReceiptField::DoneDate => self.done_date.is_none(),
+                ReceiptField::State => self.state.is_none(),
+                ReceiptField::Error => self.error.is_none(),
+                ReceiptField::Text => self.text.is_none(),
+            })
+            .collect();
+    }
+}
+
+/// The trimmed value of each field in `text`, indexed by [`ReceiptField`].
+///
+/// A key counts at the start of the text or after whitespace. The first
+/// occurrence of a field wins.
+fn split_fields(text: &str) -> [Option<&str>; 8] {
+    let lower = text.to_ascii_lowercase();
+    let mut starts: Vec<(ReceiptField, usize, usize)> = Vec::new();
+    let mut index = 0;
+    while index < text.len() {
+        let at_boundary = index == 0 || text[..index].ends_with(char::is_whitespace);
+        let found = at_boundary.then(|| key_at(&lower[index..])).flatten();
+        match found {
+            Some((field, len)) => {
+                starts.push((field, index, index + len));
+                if field == ReceiptField::Text {
+                    break;
+                }
+                index += len;
+            }
+            None => index += text[index..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    let mut values = [None; 8];
+    for (position, &(field, _, value_start)) in starts.iter().enumerate() {
+        let value_end = starts
+            .get(position + 1)
+            .map_or(text.len(), |&(_, next_start, _)| next_start);
+        let value = &text[value_start..value_end];
+        let value = if field == ReceiptField::Text {
+            value
+        } else {
+            // Unknown fields after this one end its value
+            value.split_whitespace().next().unwrap_or_default()
+        };
+        if values[field as usize].is_none() {
+            values[field as usize] = Some(value.trim());
+        }
+    }
+    values
+}
+
+/// The field whose key starts `text`, and the length of the key with its
+/// colon and any spaces around it.
+fn key_at(text: &str) -> Option<(ReceiptField, usize)> {
+    ReceiptField::ALL.into_iter().find_map(|field| {
+        field.keys().iter().find_map(|key| {
+            let rest = text.strip_prefix(key)?;
+            let after_spaces = rest.trim_start_matches(' ');
+            let value = after_spaces.strip_prefix(':')?;
+            let value_start = value.len() - value.trim_start_matches(' ').len();
+            Some((field, text.len() - value.len() + value_start))
+        })
+    })
+}
+
+/// Parse YYMMDDhhmm, YYMMDDhhmmss or YYYYMMDDhhmmss.
+fn parse_date(value: &str) -> Option<UtcDateTime> {
+    if !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (year, rest) = match value.len() {
+        10 | 12 => (2000 + value[..2].parse::<i32>().ok()?, &value[2..]),
+        14 => (value[..4].parse().ok()?, &value[4..]),
+        _ => return None,
+    };
+    let number = |range: std::ops::Range<usize>| rest.get(range)?.parse::<u8>().ok();
+    let date = UtcDateTime {
+        year,
+        month: number(0..2)?,
+        day: number(2..4)?,
+        hour: number(4..6)?,
+        minute: number(6..8)?,
+        second: if rest.len() > 8 { number(8..10)? } else { 0 },
+    };
+    let valid = (1..=12).contains(&date.month)
+        && (1..=31).contains(&date.day)
+        && date.hour < 24
+        && date.minute < 60
+        && date.second < 60;
+    valid.then_some(date)
+}
+
+/// Parse an error code, decimal or `0x` hex.
+fn parse_error(value: &str) -> Option<u32> {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smpp::{SmppAddress, SubmitSm};
+
+    #[test]
+    fn test_standard_format() {
+        let receipt = DeliveryReceipt::parse(
+            "id:7220624 sub:001 dlvrd:001 submit date:2103091741 done date:2103091741 \
+             stat:DELIVRD err:000 text:Hello: world",
+        )
+        .unwrap();
+        assert_eq!(
+            receipt,
+            DeliveryReceipt {
+                id: Some("7220624".to_string()),
+                submitted: Some(1),
+                delivered: Some(1),
+                submit_date: Some(UtcDateTime {
+                    year: 2021,
+                    month: 3,
+                    day: 9,
+                    hour: 17,
+                    minute: 41,
+                    second: 0,
+                }),
+                done_date: parse_date("2103091741"),
+                state: Some(ReceiptState::Delivered),
+                error: Some(0),
+                text: Some("Hello: world".to_string()),
+                missing: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_carrier_variations() {
+        let receipt = DeliveryReceipt::parse(
+            "ID : abc-1 SUB:1 DLVD:0 Submit_Date:20240229235959 done date:240301000010 \
+             Status:undelivered network:23415 Error:0x0B Text:",
+        )
+        .unwrap();
+        assert_eq!(receipt.id.as_deref(), Some("abc-1"));
+        assert_eq!(receipt.delivered, Some(0));
+        assert_eq!(receipt.submit_date.unwrap().second, 59);
+        assert_eq!(receipt.done_date.unwrap().day, 1);
+        assert_eq!(receipt.state, Some(ReceiptState::Undeliverable));
+        assert_eq!(receipt.error, Some(0x0B));
+        assert_eq!(receipt.text.as_deref(), Some(""));
+        assert!(receipt.missing.is_empty());
+
+        for (state, expected) in [
+            ("Rejected", ReceiptState::Rejected),
+            ("DELETD", ReceiptState::Deleted),
+            ("queued", ReceiptState::Other("queued".to_string())),
+        ] {
+            assert_eq!(ReceiptState::parse(state), expected);
+        }
+
+        let receipt = DeliveryReceipt::parse("id:1 stat:expired submit date:24133").unwrap();
+        assert_eq!(receipt.state, Some(ReceiptState::Expired));
+        assert!(receipt.state.as_ref().unwrap().is_final());
+        assert_eq!(
+            receipt.missing,
+            [
+                ReceiptField::Submitted,
+                ReceiptField::Delivered,
+                ReceiptField::SubmitDate,
+                ReceiptField::DoneDate,
+                ReceiptField::Error,
+                ReceiptField::Text,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_a_receipt() {
+        assert!(matches!(
+            DeliveryReceipt::parse("Hello, see you at 10:30"),
+            Err(Gsm7Error::MalformedData { .. })
+        ));
+        // Keys inside words do not count
+        assert!(DeliveryReceipt::parse("valid:yes substat:1").is_err());
+    }
+
+    #[test]
+    fn test_from_deliver_sm() {
+        let text = "id:42 sub:001 dlvrd:001 submit date:2401011200 done date:2401011201 \
+                    stat:DELIVRD err:000 text:Привет";
+        let mut deliver = SubmitSm::from_text(
+            SmppAddress::international("447700900123"),
+            SmppAddress::alphanumeric("Shop"),
+            text,
+        )
+        .unwrap();
+        assert_eq!(deliver.data_coding, 0x08);
+        let receipt = DeliveryReceipt::from_deliver_sm(&deliver).unwrap();
+        assert_eq!(receipt.text.as_deref(), Some("Привет"));
+        assert!(receipt.missing.is_empty());
+
+        // Text without id or state, completed from the TLVs
+        deliver.data_coding = 0x04;
+        deliver.short_message = b"sub:1 dlvrd:0".to_vec();
+        deliver.tlvs = vec![
+            Tlv::ReceiptedMessageId("42".to_string()),
+            Tlv::MessageState(5),
+        ];
+        let receipt = DeliveryReceipt::from_deliver_sm(&deliver).unwrap();
+        assert_eq!(receipt.id.as_deref(), Some("42"));
+        assert_eq!(receipt.state, Some(ReceiptState::Undeliverable));
+        assert_eq!(receipt.delivered, Some(0));
+        assert!(!receipt.missing.contains(&ReceiptField::Id));
+
+        deliver.short_message = b"no receipt here".to_vec();
+        let receipt = DeliveryReceipt::from_deliver_sm(&deliver).unwrap();
+        assert_eq!(receipt.id.as_deref(), Some("42"));
+        assert!(receipt.missing.contains(&ReceiptField::Text));
+    }
+}
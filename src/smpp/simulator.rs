@@ -0,0 +1,938 @@
+//! A local SMSC for integration tests.
+//!
+//! [`SmscSimulator`] listens on a TCP port, usually on localhost, and plays
+//! the SMSC side of SMPP 3.4: it accepts binds, stores every submit_sm with
+//! its text decoded by [`decode_with_config`](crate::decode_with_config),
+//! answers with message ids, sends delivery receipts and delivers MO
+//! messages, either scripted for each bind or injected by the test. Each
+//! connection runs on its own thread.
+
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{
+    Bind, BindResp, DataCodingProfile, DeliverSm, ESME_RALYBND, ESME_RINVBNDSTS, ESME_RINVCMDID,
+    ESME_RINVCMDLEN, ESME_RINVDCS, ESME_RINVPASWD, ESME_ROK, ESME_RSUBMITFAIL, ESME_RSYSERR,
+    HEADER_OCTETS, Pdu, PduBody, SmppAddress, SubmitSm, SubmitSmResp, Tlv,
+};
+use crate::pdu::UtcDateTime;
+use crate::{Gsm7Config, Gsm7Error, Result};
+
+/// How often blocked threads look for shutdown and due MO messages.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// Largest PDU accepted: a 64 KiB message_payload and the fixed fields.
+const MAX_PDU_OCTETS: usize = 0x10000 + 1024;
+/// esm_class of an SMSC delivery receipt.
+const ESM_DELIVERY_RECEIPT: u8 = 0x04;
+/// message_state DELIVERED.
+const STATE_DELIVERED: u8 = 2;
+/// Characters of the original text quoted in a receipt.
+const RECEIPT_TEXT_CHARS: usize = 20;
+
+/// Settings for an [`SmscSimulator`].
+#[derive(Debug, Clone)]
+pub struct SmscOptions {
+    /// Address to listen on; port 0 picks a free port.
+    pub address: SocketAddr,
+    /// system_id returned in bind responses.
+    pub system_id: String,
+    /// Password every bind must present, or `None` to accept any.
+    pub password: Option<String>,
+    /// How this SMSC reads data_coding values.
+    pub profile: DataCodingProfile,
+    /// Decoding options for the stored text.
+    pub config: Gsm7Config,
+    /// Send a delivery receipt for each submit_sm that requests one. A
+    /// receipt that `profile` cannot encode is not sent.
+    pub delivery_receipts: bool,
+    /// MO messages sent to every receiver or transceiver after it binds.
+    pub mo_script: Vec<MoMessage>,
+}
+
+impl Default for SmscOptions {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from(([127, 0, 0, 1], 0)),
+            system_id: "SMSC".to_string(),
+            password: None,
+            profile: DataCodingProfile::standard(),
+            config: Gsm7Config::default(),
+            delivery_receipts: true,
+            mo_script: Vec::new(),
+        }
+    }
+}
+
+/// A scripted MO message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoMessage {
+    /// Time after the bind at which the message is delivered.
+    pub delay: Duration,
+    /// The deliver_sm to send.
+    pub deliver: DeliverSm,
+}
+
+impl MoMessage {
+    /// A message with `text`, delivered straight after the bind.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`SubmitSm::from_text`].
+    pub fn new(source: SmppAddress, destination: SmppAddress, text: &str) -> Result<Self> {
+        Ok(Self {
+            delay: Duration::ZERO,
+            deliver: SubmitSm::from_text(source, destination, text)?,
+        })
+    }
+
+    /// The same message, delivered `delay` after the bind.
+    pub fn after(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+}
+
+/// A submit_sm the simulator accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredMessage {
+    /// message_id returned in the submit_sm_resp.
+    pub message_id: String,
+    /// system_id of the session that submitted it.
+    pub system_id: String,
+    /// The submit_sm as received.
+    pub submit: SubmitSm,
+    /// The decoded text, or `None` for binary data.
+    pub text: Option<String>,
+    /// When the simulator received it.
+    pub submitted_at: SystemTime,
+}
+
+/// An SMSC running on background threads until it is shut down or dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::smpp::{SmscOptions, SmscSimulator};
+///
+/// let smsc = SmscSimulator::start(SmscOptions::default())?;
+/// let address = smsc.local_addr(); // connect the ESME under test here
+/// assert!(address.ip().is_loopback());
+/// assert!(smsc.messages().is_empty());
+/// smsc.shutdown();
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct SmscSimulator {
+    local_addr: SocketAddr,
+    shared: Arc<Shared>,
+    acceptor: Option<JoinHandle<()>>,
+}
+
+impl SmscSimulator {
+    /// Listen on `options.address` and start accepting connections.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the address cannot be bound.
+    pub fn start(options: SmscOptions) -> io::Result<Self> {
+        let listener = TcpListener::bind(options.address)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            options,
+            state: Mutex::new(State::default()),
+            changed: Condvar::new(),
+            stopping: AtomicBool::new(false),
+        });
+        let acceptor = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || accept(&shared, &listener))
+        };
+        Ok(Self {
+            local_addr,
+            shared,
+            acceptor: Some(acceptor),
+        })
+    }
+
+    /// The address the simulator listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Every message submitted so far, oldest first.
+    pub fn messages(&self) -> Vec<StoredMessage> {
+        self.shared.state().messages.clone()
+    }
+
+    /// Wait until at least `count` messages have been submitted or `timeout`
+    /// passes.
+    ///
+    /// # Returns
+    ///
+    /// Every message submitted so far, which may be fewer than `count`.
+    pub fn wait_for_messages(&self, count: usize, timeout: Duration) -> Vec<StoredMessage> {
+        let state = self.shared.state();
+        let (state, _) = self
+            .shared
+            .changed
+            .wait_timeout_while(state, timeout, |state| state.messages.len() < count)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.messages.clone()
+    }
+
+    /// Every deliver_sm sent so far, receipts and MO messages alike.
+    pub fn deliveries(&self) -> Vec<DeliverSm> {
+        self.shared.state().deliveries.clone()
+    }
+
+    /// Number of sessions currently bound.
+    pub fn bound_sessions(&self) -> usize {
+        self.shared.state().sessions.len()
+    }
+
+    /// Deliver an MO message to the first bound receiver or transceiver.
+    ///
+    /// # Returns
+    ///
+    /// `true` if it was sent, `false` if it is queued until the next receiver
+    /// binds.
+    pub fn inject_mo(&self, deliver: DeliverSm) -> bool {
+        self.shared.deliver(None, None, deliver)
+    }
+
+    /// Stop accepting connections, close every session and wait for the
+    /// threads to finish.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.shared.stopping.store(true, Ordering::SeqCst);
+        if let Some(acceptor) = self.acceptor.take() {
+            let _ = acceptor.join();
+        }
+    }
+}
+
+impl Drop for SmscSimulator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// State shared by the simulator handle and its threads.
+#[derive(Debug)]
+struct Shared {
+    options: SmscOptions,
+    state: Mutex<State>,
+    /// Signalled when a message is stored.
+    changed: Condvar,
+    stopping: AtomicBool,
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    /// Send `deliver` to session `preferred` if it receives, otherwise to the
+    /// first receiving session of `system_id` (any for `None`), otherwise
+    /// queue it.
+    ///
+    /// The state lock is released while writing, so a client that stops
+    /// reading only stalls its own session. The message is recorded in
+    /// `deliveries` before it is written, so a client that has it also finds
+    /// it there. A session whose write fails is dropped and the message taken
+    /// back and queued again.
+    fn deliver(&self, preferred: Option<u64>, system_id: Option<&str>, deliver: DeliverSm) -> bool {
+        let mut state = self.state();
+        state.next_sequence = state.next_sequence % 0x7FFF_FFFF + 1;
+        let pdu = Pdu::new(state.next_sequence, PduBody::DeliverSm(deliver.clone()));
+        let Ok(bytes) = pdu.to_bytes() else {
+            return false;
+        };
+        let Some((id, writer)) = state.receiver(preferred, system_id) else {
+            state.queued.push((system_id.map(str::to_string), deliver));
+            return false;
+        };
+        state.deliveries.push(deliver.clone());
+        drop(state);
+
+        if write_all(&writer, &bytes).is_ok() {
+            return true;
+        }
+        let mut state = self.state();
+        if let Some(index) = state.deliveries.iter().rposition(|sent| *sent == deliver) {
+            state.deliveries.remove(index);
+        }
+        state.sessions.retain(|session| session.id != id);
+        state.queued.push((system_id.map(str::to_string), deliver));
+        false
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    messages: Vec<StoredMessage>,
+    deliveries: Vec<DeliverSm>,
+    sessions: Vec<Session>,
+    /// deliver_sm waiting for a receiver, with the system_id it is for.
+    queued: Vec<(Option<String>, DeliverSm)>,
+    next_session: u64,
+    next_message_id: u64,
+    next_sequence: u32,
+}
+
+impl State {
+    /// The id and writer of session `preferred` if it receives, otherwise of
+    /// the first receiving session of `system_id` (any for `None`).
+    fn receiver(
+        &self,
+        preferred: Option<u64>,
+        system_id: Option<&str>,
+    ) -> Option<(u64, Arc<Mutex<TcpStream>>)> {
+        self.sessions
+            .iter()
+            .filter(|session| session.receives)
+            .find(|session| Some(session.id) == preferred)
+            .or_else(|| {
+                self.sessions.iter().find(|session| {
+                    session.receives && system_id.is_none_or(|id| session.system_id == id)
+                })
+            })
+            .map(|session| (session.id, Arc::clone(&session.writer)))
+    }
+}
+
+/// A bound session.
+#[derive(Debug)]
+struct Session {
+    id: u64,
+    system_id: String,
+    /// Bound as receiver or transceiver.
+    receives: bool,
+    writer: Arc<Mutex<TcpStream>>,
+}
+
+/// Which way a session carries messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindKind {
+    Transmitter,
+    Receiver,
+    Transceiver,
+}
+
+impl BindKind {
+    fn transmits(self) -> bool {
+        self != BindKind::Receiver
+    }
+
+    fn receives(self) -> bool {
+        self != BindKind::Transmitter
+    }
+}
+
+/// The bind of a connection.
+#[derive(Debug, Clone)]
+struct Bound {
+    id: u64,
+    system_id: String,
+    kind: BindKind,
+}
+
+/// Whether a session goes on after a PDU.
+enum Flow {
+    Continue,
+    Close,
+}
+
+/// Accept connections until the simulator stops, then wait for the sessions.
+fn accept(shared: &Arc<Shared>, listener: &TcpListener) {
+    let mut sessions = Vec::new();
+    while !shared.is_stopping() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let shared = Arc::clone(shared);
+                sessions.push(thread::spawn(move || {
+                    let _ = serve(&shared, stream);
+                }));
+            }
+            Err(_) => thread::sleep(POLL_INTERVAL),
+        }
+    }
+    for session in sessions {
+        let _ = session.join();
+    }
+}
+
+/// Run one connection: read PDUs, answer them and send due MO messages.
+fn serve(shared: &Shared, stream: TcpStream) -> io::Result<()> {
+    let mut connection = Connection {
+        shared,
+        reader: stream.try_clone()?,
+        writer: Arc::new(Mutex::new(stream)),
+        session: None,
+        script: VecDeque::new(),
+    };
+    connection.reader.set_nonblocking(false)?;
+    connection.reader.set_read_timeout(Some(POLL_INTERVAL))?;
+    connection.run()
+}
+
+/// One TCP connection and its bind.
+struct Connection<'a> {
+    shared: &'a Shared,
+    reader: TcpStream,
+    writer: Arc<Mutex<TcpStream>>,
+    session: Option<Bound>,
+    /// Scripted MO messages and when they are due.
+    script: VecDeque<(Instant, DeliverSm)>,
+}
+
+impl Connection<'_> {
+    fn run(&mut self) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        let mut chunk = [0; 4096];
+        while !self.shared.is_stopping() {
+            self.send_due_mo();
+            match self.reader.read(&mut chunk) {
+                Ok(0) => return Ok(()),
+                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+                Err(error)
+                    if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                {
+                    continue;
+                }
+                Err(error) => return Err(error),
+            }
+            while let Some(length) = Pdu::command_length(&buffer) {
+                if !(HEADER_OCTETS..=MAX_PDU_OCTETS).contains(&length) {
+                    self.send(&Pdu::generic_nack(0, ESME_RINVCMDLEN))?;
+                    return Ok(());
+                }
+                if buffer.len() < length {
+                    break;
+                }
+                let bytes: Vec<u8> = buffer.drain(..length).collect();
+                if let Flow::Close = self.handle(&bytes)? {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, bytes: &[u8]) -> io::Result<Flow> {
+        let pdu = match Pdu::from_bytes(bytes) {
+            Ok(pdu) => pdu,
+            Err(error) => {
+                let sequence_number =
+                    u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+                let status = match error {
+                    Gsm7Error::InvalidPduField {
+                        field: "command_id",
+                        ..
+                    } => ESME_RINVCMDID,
+                    _ => ESME_RSYSERR,
+                };
+                self.send(&Pdu::generic_nack(sequence_number, status))?;
+                return Ok(Flow::Continue);
+            }
+        };
+        let sequence_number = pdu.sequence_number;
+        let reply = |command_status, body| Pdu {
+            command_status,
+            sequence_number,
+            body,
+        };
+        match pdu.body {
+            PduBody::BindTransmitter(bind) => {
+                let status = self.bind(&bind, BindKind::Transmitter);
+                self.send(&reply(
+                    status,
+                    PduBody::BindTransmitterResp(self.bind_resp(status)),
+                ))?;
+            }
+            PduBody::BindReceiver(bind) => {
+                let status = self.bind(&bind, BindKind::Receiver);
+                self.send(&reply(
+                    status,
+                    PduBody::BindReceiverResp(self.bind_resp(status)),
+                ))?;
+                self.after_receiver_bind(status);
+            }
+            PduBody::BindTransceiver(bind) => {
+                let status = self.bind(&bind, BindKind::Transceiver);
+                self.send(&reply(
+                    status,
+                    PduBody::BindTransceiverResp(self.bind_resp(status)),
+                ))?;
+                self.after_receiver_bind(status);
+            }
+            PduBody::SubmitSm(submit) => self.submit(sequence_number, submit)?,
+            PduBody::EnquireLink => self.send(&reply(ESME_ROK, PduBody::EnquireLinkResp))?,
+            PduBody::Unbind => {
+                self.send(&reply(ESME_ROK, PduBody::UnbindResp))?;
+                return Ok(Flow::Close);
+            }
+            PduBody::DeliverSmResp(_) | PduBody::GenericNack | PduBody::EnquireLinkResp => {}
+            _ => self.send(&Pdu::generic_nack(sequence_number, ESME_RINVCMDID))?,
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Register the bind and return the command_status for the response.
+    fn bind(&mut self, bind: &Bind, kind: BindKind) -> u32 {
+        if self.session.is_some() {
+            return ESME_RALYBND;
+        }
+        if self
+            .shared
+            .options
+            .password
+            .as_deref()
+            .is_some_and(|expected| expected != bind.password)
+        {
+            return ESME_RINVPASWD;
+        }
+        let mut state = self.shared.state();
+        state.next_session += 1;
+        let id = state.next_session;
+        state.sessions.push(Session {
+            id,
+            system_id: bind.system_id.clone(),
+            receives: kind.receives(),
+            writer: Arc::clone(&self.writer),
+        });
+        self.session = Some(Bound {
+            id,
+            system_id: bind.system_id.clone(),
+            kind,
+        });
+        ESME_ROK
+    }
+
+    fn bind_resp(&self, status: u32) -> BindResp {
+        if status == ESME_ROK {
+            BindResp::new(&self.shared.options.system_id)
+        } else {
+            BindResp::default()
+        }
+    }
+
+    /// Start the MO script and send what was queued for this receiver.
+    fn after_receiver_bind(&mut self, status: u32) {
+        let Some(Bound { id, system_id, .. }) = self.session.clone().filter(|_| status == ESME_ROK)
+        else {
+            return;
+        };
+        let now = Instant::now();
+        self.script = self
+            .shared
+            .options
+            .mo_script
+            .iter()
+            .map(|mo| (now + mo.delay, mo.deliver.clone()))
+            .collect();
+
+        let ready: Vec<_> = {
+            let mut state = self.shared.state();
+            let (ready, waiting) = std::mem::take(&mut state.queued)
+                .into_iter()
+                .partition(|(target, _)| target.as_ref().is_none_or(|target| *target == system_id));
+            state.queued = waiting;
+            ready
+        };
+        for (target, deliver) in ready {
+            self.shared.deliver(Some(id), target.as_deref(), deliver);
+        }
+    }
+
+    fn send_due_mo(&mut self) {
+        let now = Instant::now();
+        while self.script.front().is_some_and(|(due, _)| *due <= now) {
+            if let Some((_, deliver)) = self.script.pop_front() {
+                let id = self.session.as_ref().map(|bound| bound.id);
+                self.shared.deliver(id, None, deliver);
+            }
+        }
+    }
+
+    /// Store a submit_sm, answer it and send its receipt.
+    fn submit(&mut self, sequence_number: u32, submit: SubmitSm) -> io::Result<()> {
+        let Some(Bound { id, system_id, .. }) =
+            self.session.clone().filter(|bound| bound.kind.transmits())
+        else {
+            let nack = Pdu {
+                command_status: ESME_RINVBNDSTS,
+                sequence_number,
+                body: PduBody::SubmitSmResp(SubmitSmResp::default()),
+            };
+            return self.send(&nack);
+        };
+
+        let options = &self.shared.options;
+        let text = match submit.text_with_profile(&options.profile, &options.config) {
+            Ok(text) => text,
+            Err(error) => {
+                let status = match error {
+                    Gsm7Error::UnsupportedDataCoding { .. } => ESME_RINVDCS,
+                    _ => ESME_RSUBMITFAIL,
+                };
+                let nack = Pdu {
+                    command_status: status,
+                    sequence_number,
+                    body: PduBody::SubmitSmResp(SubmitSmResp::default()),
+                };
+                return self.send(&nack);
+            }
+        };
+
+        // Store the message before answering, so the ESME finds it as soon
+        // as it has the response
+        let wants_receipt = options.delivery_receipts && submit.registered_delivery & 0x01 != 0;
+        let (message_id, receipt) = {
+            let mut state = self.shared.state();
+            state.next_message_id += 1;
+            let stored = StoredMessage {
+                message_id: format!("{:08X}", state.next_message_id),
+                system_id: system_id.clone(),
+                submit,
+                text,
+                submitted_at: SystemTime::now(),
+            };
+            let receipt = wants_receipt
+                .then(|| receipt(&stored, SystemTime::now(), &options.profile).ok())
+                .flatten();
+            let message_id = stored.message_id.clone();
+            state.messages.push(stored);
+            (message_id, receipt)
+        };
+        self.shared.changed.notify_all();
+
+        self.send(&Pdu::new(
+            sequence_number,
+            PduBody::SubmitSmResp(SubmitSmResp::new(&message_id)),
+        ))?;
+        if let Some(receipt) = receipt {
+            self.shared.deliver(Some(id), Some(&system_id), receipt);
+        }
+        Ok(())
+    }
+
+    fn send(&self, pdu: &Pdu) -> io::Result<()> {
+        let bytes = pdu
+            .to_bytes()
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+        write_all(&self.writer, &bytes)
+    }
+}
+
+impl Drop for Connection<'_> {
+    /// Forget the session and close the socket, also when the session thread
+    /// panics.
+    fn drop(&mut self) {
+        if let Some(bound) = &self.session {
+            self.shared
+                .state()
+                .sessions
+                .retain(|session| session.id != bound.id);
+        }
+        let _ = self.reader.shutdown(Shutdown::Both);
+    }
+}
+
+fn write_all(writer: &Mutex<TcpStream>, bytes: &[u8]) -> io::Result<()> {
+    writer
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .write_all(bytes)
+}
+
+/// A delivery receipt for `stored` in the usual text format.
+fn receipt(
+    stored: &StoredMessage,
+    done: SystemTime,
+    profile: &DataCodingProfile,
+) -> Result<DeliverSm> {
+    let quoted: String = stored
+        .text
+        .as_deref()
+        .unwrap_or_default()
+        .chars()
+        .take(RECEIPT_TEXT_CHARS)
+        .collect();
+    let text = format!(
+        "id:{} sub:001 dlvrd:001 submit date:{} done date:{} stat:DELIVRD err:000 text:{}",
+        stored.message_id,
+        receipt_date(stored.submitted_at),
+        receipt_date(done),
+        quoted,
+    );
+    let mut deliver = SubmitSm::from_text_with_profile(
+        stored.submit.destination.clone(),
+        stored.submit.source.clone(),
+        &text,
+        profile,
+    )?;
+    deliver.esm_class = ESM_DELIVERY_RECEIPT;
+    deliver.tlvs.extend([
+        Tlv::ReceiptedMessageId(stored.message_id.clone()),
+        Tlv::MessageState(STATE_DELIVERED),
+    ]);
+    Ok(deliver)
+}
+
+/// A receipt date, YYMMDDhhmm in UTC.
+fn receipt_date(time: SystemTime) -> String {
+    let utc = UtcDateTime::from_system_time(time);
+    format!(
+        "{:02}{:02}{:02}{:02}{:02}",
+        utc.year.rem_euclid(100),
+        utc.month,
+        utc.day,
+        utc.hour,
+        utc.minute
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smpp::{DeliveryReceipt, ReceiptState};
+
+    /// A blocking ESME side for the tests.
+    struct Esme {
+        stream: TcpStream,
+        sequence_number: u32,
+    }
+
+    impl Esme {
+        fn connect(smsc: &SmscSimulator) -> Self {
+            let stream = TcpStream::connect(smsc.local_addr()).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            Self {
+                stream,
+                sequence_number: 0,
+            }
+        }
+
+        fn send(&mut self, body: PduBody) -> u32 {
+            self.sequence_number += 1;
+            let bytes = Pdu::new(self.sequence_number, body).to_bytes().unwrap();
+            self.stream.write_all(&bytes).unwrap();
+            self.sequence_number
+        }
+
+        fn receive(&mut self) -> Pdu {
+            let mut header = [0; HEADER_OCTETS];
+            self.stream.read_exact(&mut header).unwrap();
+            let mut bytes = header.to_vec();
+            bytes.resize(Pdu::command_length(&header).unwrap(), 0);
+            self.stream.read_exact(&mut bytes[HEADER_OCTETS..]).unwrap();
+            Pdu::from_bytes(&bytes).unwrap()
+        }
+
+        fn request(&mut self, body: PduBody) -> Pdu {
+            let sequence_number = self.send(body);
+            let response = self.receive();
+            assert_eq!(response.sequence_number, sequence_number);
+            response
+        }
+    }
+
+    fn handset() -> SmppAddress {
+        SmppAddress::international("447700900123")
+    }
+
+    fn shortcode() -> SmppAddress {
+        SmppAddress::new(3, 0, "80001")
+    }
+
+    #[test]
+    fn test_submit_and_receipt() {
+        let smsc = SmscSimulator::start(SmscOptions::default()).unwrap();
+        let mut esme = Esme::connect(&smsc);
+        let bound = esme.request(PduBody::BindTransceiver(Bind::new("gateway", "pw")));
+        assert_eq!(bound.command_status, ESME_ROK);
+        assert_eq!(
+            bound.body,
+            PduBody::BindTransceiverResp(BindResp::new("SMSC"))
+        );
+
+        let mut submit = SubmitSm::from_text(shortcode(), handset(), "Grüße {you} €5").unwrap();
+        submit.registered_delivery = 1;
+        let response = esme.request(PduBody::SubmitSm(submit.clone()));
+        let PduBody::SubmitSmResp(resp) = response.body else {
+            panic!("not a submit_sm_resp: {response:?}");
+        };
+
+        let receipt = esme.receive();
+        let PduBody::DeliverSm(receipt) = receipt.body else {
+            panic!("not a deliver_sm: {receipt:?}");
+        };
+        assert_eq!(receipt.esm_class, ESM_DELIVERY_RECEIPT);
+        assert_eq!(
+            receipt.receipted_message_id(),
+            Some(resp.message_id.as_str())
+        );
+        assert_eq!(receipt.destination, shortcode());
+        let text = receipt.text().unwrap().unwrap();
+        assert!(text.starts_with(&format!("id:{} sub:001", resp.message_id)));
+        assert!(text.ends_with("stat:DELIVRD err:000 text:Grüße {you} €5"));
+        let parsed = DeliveryReceipt::from_deliver_sm(&receipt).unwrap();
+        assert_eq!(parsed.id.as_ref(), Some(&resp.message_id));
+        assert_eq!(parsed.state, Some(ReceiptState::Delivered));
+        assert!(parsed.missing.is_empty());
+
+        // Stored before the response and the receipt were sent
+        let messages = smsc.messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message_id, resp.message_id);
+        assert_eq!(messages[0].system_id, "gateway");
+        assert_eq!(messages[0].submit, submit);
+        assert_eq!(messages[0].text.as_deref(), Some("Grüße {you} €5"));
+        assert_eq!(smsc.deliveries(), [receipt]);
+
+        let unbound = esme.request(PduBody::Unbind);
+        assert_eq!(unbound.body, PduBody::UnbindResp);
+    }
+
+    #[test]
+    fn test_decoding_uses_config() {
+        let options = SmscOptions {
+            config: Gsm7Config {
+                replacement_char: '#',
+                ..Gsm7Config::default()
+            },
+            ..SmscOptions::default()
+        };
+        let smsc = SmscSimulator::start(options).unwrap();
+        let mut esme = Esme::connect(&smsc);
+        esme.request(PduBody::BindTransmitter(Bind::new("gateway", "pw")));
+
+        let mut submit = SubmitSm::new(shortcode(), handset());
+        submit.short_message = vec![0x41, 0x1B, 0x41];
+        esme.request(PduBody::SubmitSm(submit));
+
+        let mut submit = SubmitSm::new(shortcode(), handset());
+        submit.data_coding = 0x06;
+        let response = esme.request(PduBody::SubmitSm(submit));
+        assert_eq!(response.command_status, ESME_RINVDCS);
+
+        let messages = smsc.wait_for_messages(1, Duration::from_secs(5));
+        assert_eq!(messages[0].text.as_deref(), Some("A#"));
+        assert_eq!(smsc.messages().len(), 1);
+    }
+
+    #[test]
+    fn test_profile() {
+        let profile = DataCodingProfile::latin1();
+        let options = SmscOptions {
+            profile: profile.clone(),
+            ..SmscOptions::default()
+        };
+        let smsc = SmscSimulator::start(options).unwrap();
+        let mut esme = Esme::connect(&smsc);
+        esme.request(PduBody::BindTransceiver(Bind::new("gateway", "pw")));
+
+        let mut submit = SubmitSm::new(shortcode(), handset());
+        submit.short_message = b"Gr\xFC\xDFe".to_vec();
+        submit.registered_delivery = 1;
+        esme.request(PduBody::SubmitSm(submit));
+        let messages = smsc.wait_for_messages(1, Duration::from_secs(5));
+        assert_eq!(messages[0].text.as_deref(), Some("Grüße"));
+
+        let PduBody::DeliverSm(receipt) = esme.receive().body else {
+            panic!("not a deliver_sm");
+        };
+        assert_eq!(receipt.data_coding, 0x00);
+        let text = receipt
+            .text_with_profile(&profile, &Gsm7Config::default())
+            .unwrap()
+            .unwrap();
+        assert!(text.ends_with("text:Grüße"));
+    }
+
+    #[test]
+    fn test_scripted_and_injected_mo() {
+        let options = SmscOptions {
+            mo_script: vec![
+                MoMessage::new(handset(), shortcode(), "STOP").unwrap(),
+                MoMessage::new(handset(), shortcode(), "Привет")
+                    .unwrap()
+                    .after(Duration::from_millis(50)),
+            ],
+            ..SmscOptions::default()
+        };
+        let smsc = SmscSimulator::start(options).unwrap();
+        let deliver = SubmitSm::from_text(handset(), shortcode(), "queued").unwrap();
+        assert!(!smsc.inject_mo(deliver));
+
+        let mut esme = Esme::connect(&smsc);
+        esme.request(PduBody::BindReceiver(Bind::new("gateway", "pw")));
+        let mut texts = Vec::new();
+        for _ in 0..3 {
+            let pdu = esme.receive();
+            let PduBody::DeliverSm(deliver) = pdu.body else {
+                panic!("not a deliver_sm: {pdu:?}");
+            };
+            esme.send(PduBody::DeliverSmResp(SubmitSmResp::default()));
+            texts.push(deliver.text().unwrap().unwrap());
+        }
+        assert_eq!(texts, ["queued", "STOP", "Привет"]);
+
+        let deliver = SubmitSm::from_text(handset(), shortcode(), "live").unwrap();
+        assert!(smsc.inject_mo(deliver.clone()));
+        assert_eq!(esme.receive().body, PduBody::DeliverSm(deliver));
+        assert_eq!(smsc.deliveries().len(), 4);
+    }
+
+    #[test]
+    fn test_bind_rules() {
+        let options = SmscOptions {
+            password: Some("secret".to_string()),
+            ..SmscOptions::default()
+        };
+        let smsc = SmscSimulator::start(options).unwrap();
+        let mut esme = Esme::connect(&smsc);
+
+        let submit = SubmitSm::from_text(shortcode(), handset(), "early").unwrap();
+        let response = esme.request(PduBody::SubmitSm(submit.clone()));
+        assert_eq!(response.command_status, ESME_RINVBNDSTS);
+
+        let response = esme.request(PduBody::BindTransmitter(Bind::new("gateway", "wrong")));
+        assert_eq!(response.command_status, ESME_RINVPASWD);
+        assert_eq!(smsc.bound_sessions(), 0);
+
+        let response = esme.request(PduBody::BindReceiver(Bind::new("gateway", "secret")));
+        assert_eq!(response.command_status, ESME_ROK);
+        assert_eq!(smsc.bound_sessions(), 1);
+        let response = esme.request(PduBody::SubmitSm(submit));
+        assert_eq!(response.command_status, ESME_RINVBNDSTS);
+        let response = esme.request(PduBody::BindReceiver(Bind::new("gateway", "secret")));
+        assert_eq!(response.command_status, ESME_RALYBND);
+
+        let response = esme.request(PduBody::EnquireLink);
+        assert_eq!(response.body, PduBody::EnquireLinkResp);
+        let response = esme.request(PduBody::DataSm(Default::default()));
+        assert_eq!(response.body, PduBody::GenericNack);
+        assert_eq!(response.command_status, ESME_RINVCMDID);
+
+        drop(esme);
+        smsc.shutdown();
+    }
+}
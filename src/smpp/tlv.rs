@@ -0,0 +1,204 @@
+//! Optional parameters (SMPP 3.4 §5.3, SMPP 5.0 §4.8).
+
+use crate::{Gsm7Error, Result};
+
+/// Tag: sc_interface_version.
+pub(crate) const TAG_SC_INTERFACE_VERSION: u16 = 0x0210;
+/// Tag: user_message_reference.
+pub(crate) const TAG_USER_MESSAGE_REFERENCE: u16 = 0x0204;
+/// Tag: sar_msg_ref_num.
+pub(crate) const TAG_SAR_MSG_REF_NUM: u16 = 0x020C;
+/// Tag: sar_total_segments.
+pub(crate) const TAG_SAR_TOTAL_SEGMENTS: u16 = 0x020E;
+/// Tag: sar_segment_seqnum.
+pub(crate) const TAG_SAR_SEGMENT_SEQNUM: u16 = 0x020F;
+/// Tag: receipted_message_id.
+pub(crate) const TAG_RECEIPTED_MESSAGE_ID: u16 = 0x001E;
+/// Tag: network_error_code.
+pub(crate) const TAG_NETWORK_ERROR_CODE: u16 = 0x0423;
+/// Tag: message_payload.
+pub(crate) const TAG_MESSAGE_PAYLOAD: u16 = 0x0424;
+/// Tag: message_state.
+pub(crate) const TAG_MESSAGE_STATE: u16 = 0x0427;
+
+/// One tag-length-value optional parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tlv {
+    /// message_payload (0x0424): the message instead of short_message, up to 64 KiB.
+    MessagePayload(Vec<u8>),
+    /// sar_msg_ref_num (0x020C): reference shared by the segments of a message.
+    SarMsgRefNum(u16),
+    /// sar_total_segments (0x020E): number of segments.
+    SarTotalSegments(u8),
+    /// sar_segment_seqnum (0x020F): this segment's number, from 1.
+    SarSegmentSeqnum(u8),
+    /// receipted_message_id (0x001E): SMSC message id a receipt refers to.
+    ReceiptedMessageId(String),
+    /// message_state (0x0427): final state of a receipted message.
+    MessageState(u8),
+    /// network_error_code (0x0423): network type and error code.
+    NetworkErrorCode {
+        /// Network type: 1 ANSI-136, 2 IS-95, 3 GSM, …
+        network_type: u8,
+        /// Network specific error code.
+        error_code: u16,
+    },
+    /// sc_interface_version (0x0210): SMPP version the SMSC supports.
+    ScInterfaceVersion(u8),
+    /// user_message_reference (0x0204): ESME assigned reference.
+    UserMessageReference(u16),
+    /// Any other parameter, or a known one with a malformed value.
+    Unknown {
+        /// Tag.
+        tag: u16,
+        /// Value.
+        value: Vec<u8>,
+    },
+}
+
+impl Tlv {
+    /// The parameter tag.
+    pub fn tag(&self) -> u16 {
+        match self {
+            Tlv::MessagePayload(_) => TAG_MESSAGE_PAYLOAD,
+            Tlv::SarMsgRefNum(_) => TAG_SAR_MSG_REF_NUM,
+            Tlv::SarTotalSegments(_) => TAG_SAR_TOTAL_SEGMENTS,
+            Tlv::SarSegmentSeqnum(_) => TAG_SAR_SEGMENT_SEQNUM,
+            Tlv::ReceiptedMessageId(_) => TAG_RECEIPTED_MESSAGE_ID,
+            Tlv::MessageState(_) => TAG_MESSAGE_STATE,
+            Tlv::NetworkErrorCode { .. } => TAG_NETWORK_ERROR_CODE,
+            Tlv::ScInterfaceVersion(_) => TAG_SC_INTERFACE_VERSION,
+            Tlv::UserMessageReference(_) => TAG_USER_MESSAGE_REFERENCE,
+            Tlv::Unknown { tag, .. } => *tag,
+        }
+    }
+
+    pub(super) fn parse(tag: u16, value: &[u8]) -> Self {
+        Self::parse_known(tag, value).unwrap_or_else(|| Tlv::Unknown {
+            tag,
+            value: value.to_vec(),
+        })
+    }
+
+    fn parse_known(tag: u16, value: &[u8]) -> Option<Self> {
+        Some(match (tag, value) {
+            (TAG_MESSAGE_PAYLOAD, _) => Tlv::MessagePayload(value.to_vec()),
+            (TAG_SAR_MSG_REF_NUM, &[high, low]) => {
+                Tlv::SarMsgRefNum(u16::from_be_bytes([high, low]))
+            }
+            (TAG_SAR_TOTAL_SEGMENTS, &[total]) => Tlv::SarTotalSegments(total),
+            (TAG_SAR_SEGMENT_SEQNUM, &[sequence]) => Tlv::SarSegmentSeqnum(sequence),
+            (TAG_RECEIPTED_MESSAGE_ID, [id @ .., 0]) if id.is_ascii() => {
+                Tlv::ReceiptedMessageId(id.iter().map(|&b| char::from(b)).collect())
+            }
+            (TAG_MESSAGE_STATE, &[state]) => Tlv::MessageState(state),
+            (TAG_NETWORK_ERROR_CODE, &[network_type, high, low]) => Tlv::NetworkErrorCode {
+                network_type,
+                error_code: u16::from_be_bytes([high, low]),
+            },
+            (TAG_SC_INTERFACE_VERSION, &[version]) => Tlv::ScInterfaceVersion(version),
+            (TAG_USER_MESSAGE_REFERENCE, &[high, low]) => {
+                Tlv::UserMessageReference(u16::from_be_bytes([high, low]))
+            }
+            _ => return None,
+        })
+    }
+
+    /// Append tag, length and value to `bytes`.
+    pub(super) fn write(&self, bytes: &mut Vec<u8>) -> Result<()> {
+        let value: Vec<u8> = match self {
+            Tlv::MessagePayload(payload) => payload.clone(),
+            Tlv::SarMsgRefNum(reference) | Tlv::UserMessageReference(reference) => {
+                reference.to_be_bytes().to_vec()
+            }
+            Tlv::SarTotalSegments(value)
+            | Tlv::SarSegmentSeqnum(value)
+            | Tlv::MessageState(value)
+            | Tlv::ScInterfaceVersion(value) => vec![*value],
+            Tlv::ReceiptedMessageId(id) => {
+                if !id.is_ascii() || id.contains('\0') {
+                    return Err(Gsm7Error::InvalidSmppField {
+                        field: "receipted_message_id",
+                        reason: "not a printable C-octet string".to_string(),
+                    });
+                }
+                let mut value = id.as_bytes().to_vec();
+                value.push(0);
+                value
+            }
+            Tlv::NetworkErrorCode {
+                network_type,
+                error_code,
+            } => {
+                let [high, low] = error_code.to_be_bytes();
+                vec![*network_type, high, low]
+            }
+            Tlv::Unknown { value, .. } => value.clone(),
+        };
+        let length = u16::try_from(value.len()).map_err(|_| Gsm7Error::InvalidSmppField {
+            field: "TLV",
+            reason: format!(
+                "value of tag 0x{:04X} is {} octets",
+                self.tag(),
+                value.len()
+            ),
+        })?;
+        bytes.extend_from_slice(&self.tag().to_be_bytes());
+        bytes.extend_from_slice(&length.to_be_bytes());
+        bytes.extend(value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let tlvs = [
+            Tlv::MessagePayload(b"payload".to_vec()),
+            Tlv::SarMsgRefNum(0x1234),
+            Tlv::SarTotalSegments(3),
+            Tlv::SarSegmentSeqnum(2),
+            Tlv::ReceiptedMessageId("A1B2".to_string()),
+            Tlv::MessageState(2),
+            Tlv::NetworkErrorCode {
+                network_type: 3,
+                error_code: 0x0101,
+            },
+            Tlv::ScInterfaceVersion(0x34),
+            Tlv::UserMessageReference(7),
+            Tlv::Unknown {
+                tag: 0x1400,
+                value: vec![1, 2, 3],
+            },
+        ];
+        for tlv in tlvs {
+            let mut bytes = Vec::new();
+            tlv.write(&mut bytes).unwrap();
+            assert_eq!(bytes[..2], tlv.tag().to_be_bytes());
+            assert_eq!(Tlv::parse(tlv.tag(), &bytes[4..]), tlv);
+        }
+    }
+
+    #[test]
+    fn test_receipted_message_id_wire_format() {
+        let mut bytes = Vec::new();
+        Tlv::ReceiptedMessageId("42".to_string())
+            .write(&mut bytes)
+            .unwrap();
+        assert_eq!(bytes, [0x00, 0x1E, 0x00, 0x03, b'4', b'2', 0x00]);
+    }
+
+    #[test]
+    fn test_malformed_known_tag_is_kept() {
+        assert_eq!(
+            Tlv::parse(TAG_SAR_TOTAL_SEGMENTS, &[1, 2]),
+            Tlv::Unknown {
+                tag: TAG_SAR_TOTAL_SEGMENTS,
+                value: vec![1, 2],
+            }
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! Splitting long text into concatenated SMS parts.
+//!
+//! Each part carries a User Data Header with a concatenation information
+//! element (3GPP TS 23.040 §9.2.3.24.1 and §9.2.3.24.8) and, when national
+//! language tables are in use, the shift IEs. Parts are cut on character
+//! boundaries, so an escape sequence or a UTF-16 surrogate pair always stays in
+//! one part.
+
+use std::ops::Range;
+
+use crate::segment::{char_costs, layout_costs};
+use crate::{
+    Gsm7Config, Gsm7Error, InformationElement, Result, SmsEncoding, UserDataHeader,
+    choose_encoding, encode_ucs2, encode_with_config, pack_septets_with_fill,
+};
+
+/// Concatenation reference number shared by all parts of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConcatReference {
+    /// 8-bit reference number (IE 0x00, 6-octet header).
+    EightBit(u8),
+    /// 16-bit reference number (IE 0x08, 7-octet header).
+    SixteenBit(u16),
+}
+
+impl Default for ConcatReference {
+    fn default() -> Self {
+        ConcatReference::EightBit(0)
+    }
+}
+
+/// Form of GSM 7-bit part payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeptetFormat {
+    /// Packed septets, preceded by the fill bits needed after the header.
+    #[default]
+    Packed,
+    /// One septet per byte, as returned by [`encode`](crate::encode).
+    Unpacked,
+}
+
+/// Options for [`split_into_segments`].
+#[derive(Debug, Clone, Default)]
+pub struct SplitOptions {
+    /// Encoding to use, or `None` to pick the cheapest with [`choose_encoding`].
+    pub encoding: Option<SmsEncoding>,
+    /// Concatenation reference number.
+    pub reference: ConcatReference,
+    /// Whether GSM 7-bit payloads are packed or unpacked.
+    pub septet_format: SeptetFormat,
+    /// Keep extended grapheme clusters (such as emoji with modifiers, or a
+    /// base letter with combining marks) within one part where possible.
+    #[cfg(feature = "graphemes")]
+    pub keep_graphemes: bool,
+}
+
+/// One part of a split message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmsPart {
+    /// Encoding of the payload.
+    pub encoding: SmsEncoding,
+    /// User Data Header including the UDHL octet, or empty if the part needs none.
+    pub udh: Vec<u8>,
+    /// Encoded text following the header.
+    pub payload: Vec<u8>,
+    /// TP-UDL for this part: septets for GSM 7-bit (header and fill bits
+    /// included), octets for UCS-2.
+    pub user_data_length: usize,
+    /// Byte range of the part's characters in the source text.
+    pub text: Range<usize>,
+}
+
+impl SmsPart {
+    /// The complete TP-UD: header followed by payload.
+    pub fn user_data(&self) -> Vec<u8> {
+        let mut user_data = Vec::with_capacity(self.udh.len() + self.payload.len());
+        user_data.extend_from_slice(&self.udh);
+        user_data.extend_from_slice(&self.payload);
+        user_data
+    }
+}
+
+/// Split text into SMS parts with ready-made concatenation headers.
+///
+/// A text that fits in one SMS yields a single part without a concatenation
+/// IE. Parts never break an escape sequence or a surrogate pair.
+///
+/// # Arguments
+///
+/// * `content` - The text to send
+/// * `options` - Encoding, reference number and payload format
+///
+/// # Returns
+///
+/// The parts in sending order.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::UnsupportedCharacter` if an explicit GSM 7-bit encoding
+/// cannot represent the text, and `Gsm7Error::TooManySegments` if the text
+/// needs more than 255 parts.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{ConcatReference, SplitOptions, split_into_segments};
+///
+/// let options = SplitOptions {
+///     reference: ConcatReference::EightBit(0x2A),
+///     ..Default::default()
+/// };
+/// let parts = split_into_segments(&"x".repeat(200), &options)?;
+/// assert_eq!(parts.len(), 2);
+/// assert_eq!(parts[0].udh, [0x05, 0x00, 0x03, 0x2A, 0x02, 0x01]);
+/// assert_eq!(parts[0].user_data_length, 7 + 153);
+/// assert_eq!(parts[1].udh, [0x05, 0x00, 0x03, 0x2A, 0x02, 0x02]);
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+pub fn split_into_segments(content: &str, options: &SplitOptions) -> Result<Vec<SmsPart>> {
+    let encoding = options
+        .encoding
+        .unwrap_or_else(|| choose_encoding(content).encoding);
+
+    let single_udh_len = part_header(encoding, None).len();
+    let multi_udh_len = part_header(encoding, Some((options.reference, 1, 1))).len();
+
+    let costs = atoms(content, encoding, options)?;
+    let layout = layout_costs(
+        content.len(),
+        &costs,
+        encoding,
+        encoding.units_for_header(single_udh_len),
+        encoding.units_for_header(multi_udh_len),
+    );
+    if layout.segment_count > usize::from(u8::MAX) {
+        return Err(Gsm7Error::TooManySegments {
+            count: layout.segment_count,
+        });
+    }
+
+    let total = layout.segment_count as u8;
+    layout
+        .segments
+        .into_iter()
+        .enumerate()
+        .map(|(index, segment)| {
+            let concatenation = (total > 1).then_some((options.reference, total, index as u8 + 1));
+            let header = part_header(encoding, concatenation);
+            let udh = header.to_bytes();
+
+            let text = &content[segment.text.clone()];
+            let (payload, user_data_length) = match encoding.gsm7_config() {
+                Some(config) => {
+                    let septets = encode_with_config(
+                        text,
+                        &Gsm7Config {
+                            strict: true,
+                            ..config
+                        },
+                    )?;
+                    let fill_bits = header.fill_bits();
+                    let length = header.septets() + septets.len();
+                    let payload = match options.septet_format {
+                        SeptetFormat::Packed => pack_septets_with_fill(&septets, fill_bits)?,
+                        SeptetFormat::Unpacked => septets,
+                    };
+                    (payload, length)
+                }
+                None => {
+                    let payload = encode_ucs2(text)?;
+                    let length = udh.len() + payload.len();
+                    (payload, length)
+                }
+            };
+
+            Ok(SmsPart {
+                encoding,
+                udh,
+                payload,
+                user_data_length,
+                text: segment.text,
+            })
+        })
+        .collect()
+}
+
+/// User Data Header of a part: the concatenation IE, if any, then the shift IEs.
+fn part_header(
+    encoding: SmsEncoding,
+    concatenation: Option<(ConcatReference, u8, u8)>,
+) -> UserDataHeader {
+    let mut elements = Vec::new();
+    if let Some((reference, total, sequence)) = concatenation {
+        elements.push(InformationElement::Concatenation {
+            reference,
+            total,
+            sequence,
+        });
+    }
+    if let SmsEncoding::Gsm7 {
+        locking_shift,
+        single_shift,
+    } = encoding
+    {
+        elements.extend(locking_shift.map(InformationElement::LockingShift));
+        elements.extend(single_shift.map(InformationElement::SingleShift));
+    }
+    UserDataHeader::new(elements)
+}
+
+/// Indivisible pieces of text as byte offset and unit cost.
+#[cfg(not(feature = "graphemes"))]
+fn atoms(
+    content: &str,
+    encoding: SmsEncoding,
+    _options: &SplitOptions,
+) -> Result<Vec<(usize, usize)>> {
+    char_costs(content, encoding)
+}
+
+/// Indivisible pieces of text as byte offset and unit cost.
+///
+/// With `keep_graphemes`, characters are merged into their grapheme cluster.
+/// A cluster too large for a whole part is left as separate characters.
+#[cfg(feature = "graphemes")]
+fn atoms(
+    content: &str,
+    encoding: SmsEncoding,
+    options: &SplitOptions,
+) -> Result<Vec<(usize, usize)>> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let chars = char_costs(content, encoding)?;
+    if !options.keep_graphemes {
+        return Ok(chars);
+    }
+
+    let limit =
+        encoding.units_for_header(part_header(encoding, Some((options.reference, 1, 1))).len());
+    let mut atoms = Vec::with_capacity(chars.len());
+    let mut chars = chars.into_iter().peekable();
+
+    for (start, grapheme) in content.grapheme_indices(true) {
+        let end = start + grapheme.len();
+        let mut cluster = Vec::new();
+        while let Some(&(offset, cost)) = chars.peek() {
+            if offset >= end {
+                break;
+            }
+            cluster.push((offset, cost));
+            chars.next();
+        }
+
+        let cost: usize = cluster.iter().map(|&(_, cost)| cost).sum();
+        if cost <= limit {
+            atoms.push((start, cost));
+        } else {
+            atoms.extend(cluster);
+        }
+    }
+
+    Ok(atoms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        NationalLanguage, decode_ucs2, decode_with_config, fill_bits_for_udh,
+        unpack_septets_with_fill,
+    };
+
+    fn reassemble(parts: &[SmsPart]) -> String {
+        parts
+            .iter()
+            .map(|part| match part.encoding.gsm7_config() {
+                Some(config) => {
+                    let fill_bits = fill_bits_for_udh(part.udh.len());
+                    let header_septets = (part.udh.len() * 8 + usize::from(fill_bits)) / 7;
+                    let septets = unpack_septets_with_fill(
+                        &part.payload,
+                        part.user_data_length - header_septets,
+                        fill_bits,
+                    )
+                    .unwrap();
+                    decode_with_config(&septets, &config).unwrap()
+                }
+                None => decode_ucs2(&part.payload).unwrap(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_single_part_has_no_header() {
+        let parts = split_into_segments("Hello", &SplitOptions::default()).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].udh.is_empty());
+        assert_eq!(parts[0].user_data_length, 5);
+        assert_eq!(parts[0].payload, crate::pack_septets(b"Hello"));
+    }
+
+    #[test]
+    fn test_escape_never_split() {
+        // 152 plain characters then euro signs: the first euro would straddle 153
+        let mut text = "a".repeat(152);
+        text.push_str(&"€".repeat(10));
+        let options = SplitOptions {
+            encoding: Some(SmsEncoding::GSM7_DEFAULT),
+            septet_format: SeptetFormat::Unpacked,
+            ..Default::default()
+        };
+        let parts = split_into_segments(&text, &options).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].payload.len(), 152);
+        assert_ne!(parts[0].payload.last(), Some(&0x1B));
+        assert_eq!(parts[1].payload[0], 0x1B);
+        let septets: Vec<u8> = parts.iter().flat_map(|part| part.payload.clone()).collect();
+        assert_eq!(crate::decode(&septets).unwrap(), text);
+    }
+
+    #[test]
+    fn test_sixteen_bit_reference() {
+        let options = SplitOptions {
+            reference: ConcatReference::SixteenBit(0xBEEF),
+            ..Default::default()
+        };
+        let text = "y".repeat(400);
+        let parts = split_into_segments(&text, &options).unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[2].udh, [0x06, 0x08, 0x04, 0xBE, 0xEF, 0x03, 0x03]);
+        // 7-octet header: 152 septets per part, no fill bits
+        assert_eq!(parts[0].text, 0..152);
+        assert_eq!(parts[0].user_data_length, 8 + 152);
+        assert_eq!(parts[0].user_data().len(), 140);
+        assert_eq!(reassemble(&parts), text);
+    }
+
+    #[test]
+    fn test_ucs2_parts_keep_surrogate_pairs() {
+        let text = format!("{}{}", "Ж".repeat(66), "🦀".repeat(40));
+        let parts = split_into_segments(&text, &SplitOptions::default()).unwrap();
+        assert!(parts.iter().all(|part| part.encoding == SmsEncoding::Ucs2));
+        assert_eq!(parts.len(), 3);
+        // 66 + one crab would be 68 units: the pair moves to the next part whole
+        assert_eq!(parts[0].payload.len(), 66 * 2);
+        assert!(parts.iter().all(|part| part.user_data().len() <= 140));
+        assert_eq!(reassemble(&parts), text);
+    }
+
+    #[test]
+    fn test_national_shift_ies() {
+        let text = "ğışç".repeat(50);
+        let parts = split_into_segments(&text, &SplitOptions::default()).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(
+            parts[0].udh,
+            [0x08, 0x00, 0x03, 0x00, 0x02, 0x01, 0x25, 0x01, 0x01]
+        );
+        // 9-octet header: 149 septets per part after 5 fill bits
+        assert_eq!(parts[0].user_data_length, 11 + 149);
+        assert_eq!(parts[0].user_data().len(), 140);
+        assert_eq!(reassemble(&parts), text);
+    }
+
+    #[test]
+    fn test_unsupported_character_with_explicit_encoding() {
+        let options = SplitOptions {
+            encoding: Some(SmsEncoding::GSM7_DEFAULT),
+            ..Default::default()
+        };
+        assert!(matches!(
+            split_into_segments("Ж", &options),
+            Err(Gsm7Error::UnsupportedCharacter {
+                character: 'Ж', ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_too_many_segments() {
+        let text = "z".repeat(153 * 256);
+        let options = SplitOptions {
+            encoding: Some(SmsEncoding::GSM7_DEFAULT),
+            ..Default::default()
+        };
+        assert_eq!(
+            split_into_segments(&text, &options),
+            Err(Gsm7Error::TooManySegments { count: 256 })
+        );
+    }
+
+    #[test]
+    fn test_user_data_length_matches_packed_payload() {
+        let text = "Concatenated {messages} cost € ".repeat(12);
+        let parts = split_into_segments(&text, &SplitOptions::default()).unwrap();
+        for part in &parts {
+            let octets = (part.user_data_length * 7).div_ceil(8);
+            assert_eq!(part.user_data().len(), octets);
+        }
+        assert_eq!(reassemble(&parts), text);
+    }
+
+    #[cfg(feature = "graphemes")]
+    #[test]
+    fn test_grapheme_clusters_kept_together() {
+        // Family emoji: several code points joined with ZWJ, 11 UTF-16 units
+        let family = "👨\u{200D}👩\u{200D}👧\u{200D}👦";
+        let text = format!("{}{}", "Ж".repeat(60), family);
+        let options = SplitOptions {
+            keep_graphemes: true,
+            ..Default::default()
+        };
+        let parts = split_into_segments(&text, &options).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(&text[parts[1].text.clone()], family);
+
+        let parts = split_into_segments(&text, &SplitOptions::default()).unwrap();
+        assert_ne!(&text[parts[1].text.clone()], family);
+    }
+
+    #[test]
+    fn test_shift_languages_are_reported() {
+        let options = SplitOptions {
+            encoding: Some(SmsEncoding::Gsm7 {
+                locking_shift: None,
+                single_shift: Some(NationalLanguage::Spanish),
+            }),
+            ..Default::default()
+        };
+        let parts = split_into_segments("ú", &options).unwrap();
+        assert_eq!(parts[0].udh, [0x03, 0x24, 0x01, 0x02]);
+    }
+}
@@ -0,0 +1,596 @@
+//! Operations 51, 52 and 53: the 5x short message fields.
+
+use super::{expect_fields, invalid_field};
+use crate::pdu::SmsAddress;
+use crate::{
+    CharacterSet, DataCodingScheme, Gsm7Config, Result, decode_ucs2, decode_with_config,
+    encode_ucs2, encode_with_config, hex, unpack_septets,
+};
+
+/// OTOA of an alphanumeric OAdC.
+const OTOA_ALPHANUMERIC: &str = "5039";
+
+/// XSer service type of a GSM User Data Header.
+const XSER_UDH: u8 = 0x01;
+/// XSer service type of a GSM data coding scheme.
+const XSER_DCS: u8 = 0x02;
+
+/// The message of a 5x operation: its MT, NB and NMsg, AMsg or TMsg fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MessageContent {
+    /// No message (MT empty), as in most 53 notifications.
+    #[default]
+    None,
+    /// MT 2: numeric message, digits only.
+    Numeric(String),
+    /// MT 3: alphanumeric message, sent as IRA hex: each GSM 7-bit code as
+    /// two hex digits, extension characters after an escape code.
+    Alphanumeric(String),
+    /// MT 4: transparent data with its length in bits (NB).
+    Transparent { bits: usize, data: Vec<u8> },
+}
+
+/// One XSer extra service: a type, a length and the data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraService {
+    /// The service type, such as 0x01 (GSM UDH) or 0x02 (GSM DCS).
+    pub service_type: u8,
+    /// The service data.
+    pub data: Vec<u8>,
+}
+
+/// The fields of operations 51, 52 and 53.
+///
+/// Fields are named after the UCP abbreviations and kept as sent, empty when
+/// absent, except OAdC, the message and XSer, which are decoded.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::ucp::{MessageContent, ShortMessage};
+///
+/// let mut submit = ShortMessage::new("0612345678", "ALPHA@NUM");
+/// assert_eq!(submit.otoa, "5039");
+/// submit.set_text("Grüße")?;
+/// assert_eq!(submit.message, MessageContent::Alphanumeric("Grüße".to_string()));
+///
+/// let fields = submit.fields()?;
+/// assert_eq!(fields[1], ("OAdC", "10412614190438AB4D".to_string()));
+/// assert_eq!(fields[20], ("AMsg", "47727E1E65".to_string()));
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShortMessage {
+    /// AdC: the recipient (51) or the original recipient (52, 53).
+    pub adc: String,
+    /// OAdC: the originator; the sender name itself when `otoa` is 5039.
+    pub oadc: String,
+    /// AC: authentication code.
+    pub ac: String,
+    /// NRq: notification request, `1` to request one.
+    pub nrq: String,
+    /// NAdC: notification address.
+    pub nadc: String,
+    /// NT: notification type.
+    pub nt: String,
+    /// NPID: notification PID value.
+    pub npid: String,
+    /// LRq: last resort address request.
+    pub lrq: String,
+    /// LRAd: last resort address.
+    pub lrad: String,
+    /// LPID: last resort address PID value.
+    pub lpid: String,
+    /// DD: deferred delivery requested.
+    pub dd: String,
+    /// DDT: deferred delivery time, DDMMYYHHmm.
+    pub ddt: String,
+    /// VP: validity period, DDMMYYHHmm.
+    pub vp: String,
+    /// RPID: replace PID.
+    pub rpid: String,
+    /// SCTS: service centre time stamp, DDMMYYHHmmss.
+    pub scts: String,
+    /// Dst: delivery status of a 53 notification.
+    pub dst: String,
+    /// Rsn: reason code of a 53 notification.
+    pub rsn: String,
+    /// DSCTS: delivery time stamp of a 53 notification.
+    pub dscts: String,
+    /// MT, NB and NMsg, AMsg or TMsg.
+    pub message: MessageContent,
+    /// MMS: more messages to send.
+    pub mms: String,
+    /// PR: priority request.
+    pub pr: String,
+    /// DCs: data coding scheme, deprecated in favour of XSer.
+    pub dcs: String,
+    /// MCLs: message class.
+    pub mcls: String,
+    /// RPI: reply path.
+    pub rpi: String,
+    /// CPg: reserved.
+    pub cpg: String,
+    /// RPLy: reserved.
+    pub rply: String,
+    /// OTOA: originator type of address, `5039` for alphanumeric.
+    pub otoa: String,
+    /// HPLMN: home PLMN address.
+    pub hplmn: String,
+    /// XSer: extra services.
+    pub xser: Vec<ExtraService>,
+    /// RES4: reserved.
+    pub res4: String,
+    /// RES5: reserved.
+    pub res5: String,
+}
+
+impl ShortMessage {
+    /// A message from `oadc` to `adc` with every other field empty; OTOA is
+    /// set to 5039 when `oadc` is not a number.
+    pub fn new(adc: &str, oadc: &str) -> Self {
+        let numeric = oadc.chars().all(|ch| ch.is_ascii_digit());
+        Self {
+            adc: adc.to_string(),
+            oadc: oadc.to_string(),
+            otoa: if numeric {
+                String::new()
+            } else {
+                OTOA_ALPHANUMERIC.to_string()
+            },
+            ..Self::default()
+        }
+    }
+
+    /// A message with `text`, as [`set_text`](Self::set_text) stores it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`set_text`](Self::set_text).
+    pub fn from_text(adc: &str, oadc: &str, text: &str) -> Result<Self> {
+        let mut message = Self::new(adc, oadc);
+        message.set_text(text)?;
+        Ok(message)
+    }
+
+    /// Store `text` as an alphanumeric message if the GSM 7-bit default
+    /// alphabet holds it, as UCS-2 transparent data with XSer DCS 0x08
+    /// otherwise.
+    ///
+    /// A previous XSer UDH and DCS are removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::UnsupportedUcs2Character` as
+    /// [`encode_ucs2`](crate::encode_ucs2) does.
+    pub fn set_text(&mut self, text: &str) -> Result<()> {
+        self.xser
+            .retain(|service| !matches!(service.service_type, XSER_UDH | XSER_DCS));
+        if encode_with_config(text, &Gsm7Config::strict()).is_ok() {
+            self.message = MessageContent::Alphanumeric(text.to_string());
+        } else {
+            let data = encode_ucs2(text)?;
+            self.message = MessageContent::Transparent {
+                bits: data.len() * 8,
+                data,
+            };
+            self.xser.push(ExtraService {
+                service_type: XSER_DCS,
+                data: vec![0x08],
+            });
+        }
+        Ok(())
+    }
+
+    /// The message text.
+    ///
+    /// Transparent data is decoded by the XSer DCS: UCS-2, or packed GSM
+    /// 7-bit with NB / 7 septets.
+    ///
+    /// # Returns
+    ///
+    /// The text, or `None` for no message and for binary data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidUtf16` or `Gsm7Error::MalformedData` for
+    /// malformed UCS-2.
+    pub fn text(&self) -> Result<Option<String>> {
+        match &self.message {
+            MessageContent::None => Ok(None),
+            MessageContent::Numeric(text) | MessageContent::Alphanumeric(text) => {
+                Ok(Some(text.clone()))
+            }
+            MessageContent::Transparent { bits, data } => {
+                let character_set = self
+                    .data_coding()
+                    .and_then(|dcs| DataCodingScheme::from_octet(dcs).character_set());
+                match character_set {
+                    Some(CharacterSet::Ucs2) => decode_ucs2(data).map(Some),
+                    Some(CharacterSet::Gsm7) => {
+                        let septets = unpack_septets(data, bits / 7);
+                        decode_with_config(&septets, &Gsm7Config::default()).map(Some)
+                    }
+                    Some(CharacterSet::EightBit) | None => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// The data coding scheme in XSer type 0x02.
+    pub fn data_coding(&self) -> Option<u8> {
+        self.extra_service(XSER_DCS)
+            .and_then(|data| data.first().copied())
+    }
+
+    /// The User Data Header in XSer type 0x01, without its length octet.
+    pub fn udh(&self) -> Option<&[u8]> {
+        self.extra_service(XSER_UDH)
+    }
+
+    fn extra_service(&self, service_type: u8) -> Option<&[u8]> {
+        self.xser
+            .iter()
+            .find(|service| service.service_type == service_type)
+            .map(|service| service.data.as_slice())
+    }
+
+    /// The 33 data fields as sent, with their UCP names.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidAddress` for an alphanumeric OAdC that is
+    /// empty or longer than 11 septets, `Gsm7Error::UnsupportedCharacter` for
+    /// alphanumeric text outside the GSM 7-bit alphabet, and
+    /// `Gsm7Error::InvalidUcpField` for a numeric message with other
+    /// characters, NB beyond the transparent data, or an XSer service longer
+    /// than 255 octets.
+    pub fn fields(&self) -> Result<Vec<(&'static str, String)>> {
+        let oadc = if self.otoa == OTOA_ALPHANUMERIC {
+            let bytes = SmsAddress::alphanumeric(&self.oadc)?.to_bytes()?;
+            // Length in semi-octets, then the packed septets
+            format!("{:02X}{}", bytes[0], hex::encode(&bytes[2..]))
+        } else {
+            self.oadc.clone()
+        };
+        let (mt, nb, msg_name, msg) = match &self.message {
+            MessageContent::None => ("", String::new(), "Msg", String::new()),
+            MessageContent::Numeric(digits) => {
+                if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid_field("NMsg", format!("'{digits}' is not numeric")));
+                }
+                ("2", String::new(), "NMsg", digits.clone())
+            }
+            MessageContent::Alphanumeric(text) => {
+                let septets = encode_with_config(text, &Gsm7Config::strict())?;
+                ("3", String::new(), "AMsg", hex::encode(&septets))
+            }
+            MessageContent::Transparent { bits, data } => {
+                if *bits > data.len() * 8 {
+                    return Err(invalid_field(
+                        "NB",
+                        format!("{bits} bits in {} octets", data.len()),
+                    ));
+                }
+                ("4", bits.to_string(), "TMsg", hex::encode(data))
+            }
+        };
+        let mut xser = String::new();
+        for service in &self.xser {
+            let length = u8::try_from(service.data.len()).map_err(|_| {
+                invalid_field(
+                    "XSer",
+                    format!("{} octets of service data", service.data.len()),
+                )
+            })?;
+            xser.push_str(&hex::encode(&[service.service_type, length]));
+            xser.push_str(&hex::encode(&service.data));
+        }
+
+        Ok(vec![
+            ("AdC", self.adc.clone()),
+            ("OAdC", oadc),
+            ("AC", self.ac.clone()),
+            ("NRq", self.nrq.clone()),
+            ("NAdC", self.nadc.clone()),
+            ("NT", self.nt.clone()),
+            ("NPID", self.npid.clone()),
+            ("LRq", self.lrq.clone()),
+            ("LRAd", self.lrad.clone()),
+            ("LPID", self.lpid.clone()),
+            ("DD", self.dd.clone()),
+            ("DDT", self.ddt.clone()),
+            ("VP", self.vp.clone()),
+            ("RPID", self.rpid.clone()),
+            ("SCTS", self.scts.clone()),
+            ("Dst", self.dst.clone()),
+            ("Rsn", self.rsn.clone()),
+            ("DSCTS", self.dscts.clone()),
+            ("MT", mt.to_string()),
+            ("NB", nb),
+            (msg_name, msg),
+            ("MMS", self.mms.clone()),
+            ("PR", self.pr.clone()),
+            ("DCs", self.dcs.clone()),
+            ("MCLs", self.mcls.clone()),
+            ("RPI", self.rpi.clone()),
+            ("CPg", self.cpg.clone()),
+            ("RPLy", self.rply.clone()),
+            ("OTOA", self.otoa.clone()),
+            ("HPLMN", self.hplmn.clone()),
+            ("XSer", xser),
+            ("RES4", self.res4.clone()),
+            ("RES5", self.res5.clone()),
+        ])
+    }
+
+    pub(super) fn read(fields: &[&str]) -> Result<Self> {
+        let [
+            adc,
+            oadc,
+            ac,
+            nrq,
+            nadc,
+            nt,
+            npid,
+            lrq,
+            lrad,
+            lpid,
+            dd,
+            ddt,
+            vp,
+            rpid,
+            scts,
+            dst,
+            rsn,
+            dscts,
+            mt,
+            nb,
+            msg,
+            mms,
+            pr,
+            dcs,
+            mcls,
+            rpi,
+            cpg,
+            rply,
+            otoa,
+            hplmn,
+            xser,
+            res4,
+            res5,
+        ] = expect_fields::<33>(fields)?;
+
+        let oadc = if otoa == OTOA_ALPHANUMERIC && !oadc.is_empty() {
+            read_alphanumeric_oadc(oadc)?
+        } else {
+            oadc.to_string()
+        };
+        let hex_field = |field: &'static str, value: &str| {
+            hex::decode(value).map_err(|err| invalid_field(field, err.to_string()))
+        };
+        let message = match mt {
+            "" if msg.is_empty() => MessageContent::None,
+            "2" => MessageContent::Numeric(msg.to_string()),
+            "3" => {
+                let septets: Vec<u8> = hex_field("AMsg", msg)?
+                    .iter()
+                    .map(|&octet| octet & 0x7F)
+                    .collect();
+                MessageContent::Alphanumeric(decode_with_config(&septets, &Gsm7Config::default())?)
+            }
+            "4" => {
+                let data = hex_field("TMsg", msg)?;
+                let bits = match nb {
+                    "" => data.len() * 8,
+                    nb => nb
+                        .parse()
+                        .ok()
+                        .filter(|&bits| bits <= data.len() * 8)
+                        .ok_or_else(|| {
+                            invalid_field("NB", format!("'{nb}' for {} octets", data.len()))
+                        })?,
+                };
+                MessageContent::Transparent { bits, data }
+            }
+            _ => return Err(invalid_field("MT", format!("unsupported type '{mt}'"))),
+        };
+
+        let mut services = Vec::new();
+        let mut rest = &hex_field("XSer", xser)?[..];
+        while let [service_type, length, data @ ..] = rest {
+            let length = usize::from(*length);
+            if data.len() < length {
+                return Err(invalid_field(
+                    "XSer",
+                    format!("service 0x{service_type:02X} is truncated"),
+                ));
+            }
+            services.push(ExtraService {
+                service_type: *service_type,
+                data: data[..length].to_vec(),
+            });
+            rest = &data[length..];
+        }
+        if !rest.is_empty() {
+            return Err(invalid_field("XSer", "trailing octet".to_string()));
+        }
+
+        let text = |value: &str| value.to_string();
+        Ok(Self {
+            adc: text(adc),
+            oadc,
+            ac: text(ac),
+            nrq: text(nrq),
+            nadc: text(nadc),
+            nt: text(nt),
+            npid: text(npid),
+            lrq: text(lrq),
+            lrad: text(lrad),
+            lpid: text(lpid),
+            dd: text(dd),
+            ddt: text(ddt),
+            vp: text(vp),
+            rpid: text(rpid),
+            scts: text(scts),
+            dst: text(dst),
+            rsn: text(rsn),
+            dscts: text(dscts),
+            message,
+            mms: text(mms),
+            pr: text(pr),
+            dcs: text(dcs),
+            mcls: text(mcls),
+            rpi: text(rpi),
+            cpg: text(cpg),
+            rply: text(rply),
+            otoa: text(otoa),
+            hplmn: text(hplmn),
+            xser: services,
+            res4: text(res4),
+            res5: text(res5),
+        })
+    }
+}
+
+/// Decode an alphanumeric OAdC: two hex digits giving the number of
+/// semi-octets used, then the packed septets in hex.
+fn read_alphanumeric_oadc(oadc: &str) -> Result<String> {
+    let invalid = |reason: String| invalid_field("OAdC", reason);
+    let octets = hex::decode(oadc).map_err(|err| invalid(err.to_string()))?;
+    let [length, packed @ ..] = &octets[..] else {
+        return Err(invalid("missing length".to_string()));
+    };
+    let length = usize::from(*length);
+    if packed.len() < length.div_ceil(2) {
+        return Err(invalid(format!(
+            "{length} semi-octets, but {} octets follow",
+            packed.len()
+        )));
+    }
+    let septets = unpack_septets(packed, length * 4 / 7);
+    decode_with_config(&septets, &Gsm7Config::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Gsm7Error;
+    use crate::ucp::{UcpBody, UcpMessage};
+
+    fn roundtrip(body: UcpBody) -> UcpBody {
+        let bytes = UcpMessage::new(12, body).to_bytes().unwrap();
+        UcpMessage::from_bytes(&bytes).unwrap().body
+    }
+
+    #[test]
+    fn test_alphanumeric_oadc() {
+        assert_eq!(
+            read_alphanumeric_oadc("10412614190438AB4D").unwrap(),
+            "ALPHA@NUM"
+        );
+        for name in ["A", "Shop", "ABCDEFG", "Eleven Char", "{Promo}"] {
+            let submit = ShortMessage::from_text("31612345678", name, "hi").unwrap();
+            let UcpBody::DeliverShortMessage(deliver) =
+                roundtrip(UcpBody::DeliverShortMessage(submit.clone()))
+            else {
+                panic!("not a 52");
+            };
+            assert_eq!(deliver, submit);
+        }
+        assert!(matches!(
+            ShortMessage::new("1", "Twelve Chars").fields(),
+            Err(Gsm7Error::InvalidAddress { .. })
+        ));
+        assert!(read_alphanumeric_oadc("14C1").is_err());
+
+        let numeric = ShortMessage::new("1", "0612345678");
+        assert_eq!(numeric.otoa, "");
+        assert_eq!(numeric.fields().unwrap()[1].1, "0612345678");
+    }
+
+    #[test]
+    fn test_amsg_uses_gsm_codes() {
+        let submit = ShortMessage::from_text("0612345678", "1234", "@£$ {€}").unwrap();
+        let fields = submit.fields().unwrap();
+        assert_eq!(fields[18], ("MT", "3".to_string()));
+        assert_eq!(fields[20].1, "000102201B281B651B29");
+
+        let mut fields: Vec<&str> = fields.iter().map(|(_, value)| value.as_str()).collect();
+        fields[20] = "48656C6C6F";
+        let read = ShortMessage::read(&fields).unwrap();
+        assert_eq!(read.text().unwrap().as_deref(), Some("Hello"));
+        fields[20] = "48656C6C6";
+        assert!(matches!(
+            ShortMessage::read(&fields),
+            Err(Gsm7Error::InvalidUcpField { field: "AMsg", .. })
+        ));
+        assert!(ShortMessage::read(&fields[..32]).is_err());
+    }
+
+    #[test]
+    fn test_ucs2_transparent() {
+        let mut submit = ShortMessage::from_text("0612345678", "Bank", "Привет").unwrap();
+        assert_eq!(submit.data_coding(), Some(0x08));
+        assert_eq!(
+            submit.message,
+            MessageContent::Transparent {
+                bits: 96,
+                data: encode_ucs2("Привет").unwrap(),
+            }
+        );
+        let fields = submit.fields().unwrap();
+        assert_eq!(fields[19].1, "96");
+        assert_eq!(fields[30].1, "020108");
+
+        submit.xser.insert(
+            0,
+            ExtraService {
+                service_type: XSER_UDH,
+                data: vec![0x00, 0x03, 0x2A, 0x02, 0x01],
+            },
+        );
+        let UcpBody::SubmitShortMessage(read) =
+            roundtrip(UcpBody::SubmitShortMessage(submit.clone()))
+        else {
+            panic!("not a 51");
+        };
+        assert_eq!(read.udh(), Some(&[0x00, 0x03, 0x2A, 0x02, 0x01][..]));
+        assert_eq!(read.text().unwrap().as_deref(), Some("Привет"));
+
+        submit.set_text("plain").unwrap();
+        assert!(submit.xser.is_empty());
+        assert_eq!(submit.data_coding(), None);
+    }
+
+    #[test]
+    fn test_notification_and_numeric() {
+        let mut notification = ShortMessage::new("0612345678", "4411");
+        notification.dst = "0".to_string();
+        notification.rsn = "000".to_string();
+        notification.scts = "020398142345".to_string();
+        notification.dscts = "020398142346".to_string();
+        notification.message = MessageContent::Alphanumeric("Message delivered".to_string());
+        assert_eq!(
+            roundtrip(UcpBody::DeliverNotification(notification.clone())),
+            UcpBody::DeliverNotification(notification)
+        );
+
+        let mut numeric = ShortMessage::new("0612345678", "4411");
+        numeric.message = MessageContent::Numeric("1234".to_string());
+        assert_eq!(
+            roundtrip(UcpBody::SubmitShortMessage(numeric.clone())),
+            UcpBody::SubmitShortMessage(numeric.clone())
+        );
+        numeric.message = MessageContent::Numeric("12a".to_string());
+        assert!(numeric.fields().is_err());
+        numeric.message = MessageContent::Transparent {
+            bits: 17,
+            data: vec![0xFF, 0xFF],
+        };
+        assert!(matches!(
+            numeric.fields(),
+            Err(Gsm7Error::InvalidUcpField { field: "NB", .. })
+        ));
+    }
+}
@@ -0,0 +1,600 @@
+//! UCP/EMI protocol messages.
+//!
+//! Covers the operations a large account exchanges with an SMSC: 31 alert,
+//! 51 submit short message, 52 deliver short message, 53 deliver
+//! notification and 60 session management, with their positive and negative
+//! results. A message is framed as `STX TRN/LEN/O|R/OT/fields/CHK ETX`, and
+//! its checksum is verified when it is parsed. Alphanumeric message text
+//! (AMsg) and alphanumeric originators (OAdC with OTOA 5039) go through the
+//! crate's GSM 7-bit tables and septet packing.
+//!
+//! # Example
+//!
+//! ```rust
+//! use gsm7_alt::ucp::{ShortMessage, UcpBody, UcpMessage};
+//!
+//! let submit = ShortMessage::from_text("447700900123", "Shop", "Your code is 1234 €")?;
+//! let bytes = UcpMessage::new(1, UcpBody::SubmitShortMessage(submit)).to_bytes()?;
+//! assert!(bytes.starts_with(b"\x0201/00"));
+//!
+//! let message = UcpMessage::from_bytes(&bytes)?;
+//! let UcpBody::SubmitShortMessage(submit) = message.body else { unreachable!() };
+//! assert_eq!(submit.oadc, "Shop");
+//! assert_eq!(submit.text()?.as_deref(), Some("Your code is 1234 €"));
+//! # Ok::<(), gsm7_alt::Gsm7Error>(())
+//! ```
+
+mod message;
+mod session;
+
+pub use message::{ExtraService, MessageContent, ShortMessage};
+pub use session::{Alert, SessionManagement};
+
+use crate::{Gsm7Error, Result};
+
+/// Start of a framed message.
+pub const STX: u8 = 0x02;
+/// End of a framed message.
+pub const ETX: u8 = 0x03;
+
+/// Error code: checksum error.
+pub const EC_CHECKSUM_ERROR: u8 = 1;
+/// Error code: syntax error.
+pub const EC_SYNTAX_ERROR: u8 = 2;
+/// Error code: operation not supported by the system.
+pub const EC_OPERATION_NOT_SUPPORTED: u8 = 3;
+/// Error code: operation not allowed.
+pub const EC_OPERATION_NOT_ALLOWED: u8 = 4;
+/// Error code: AdC invalid.
+pub const EC_ADC_INVALID: u8 = 6;
+/// Error code: authentication failure.
+pub const EC_AUTHENTICATION_FAILURE: u8 = 7;
+
+/// Largest value of the five-digit LEN field.
+const MAX_LENGTH: usize = 99_999;
+
+/// Octets of `TRN/LEN/O/OT/` before the data fields.
+const HEADER_CHARACTERS: usize = 14;
+
+/// Octets of the checksum after the data fields.
+const CHECKSUM_CHARACTERS: usize = 2;
+
+/// An operation type (OT).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// 31: alert, asking the SMSC to deliver pending messages.
+    Alert = 31,
+    /// 51: submit short message.
+    SubmitShortMessage = 51,
+    /// 52: deliver short message.
+    DeliverShortMessage = 52,
+    /// 53: deliver notification.
+    DeliverNotification = 53,
+    /// 60: session management (login).
+    SessionManagement = 60,
+}
+
+impl Operation {
+    /// The OT value.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// The operation with OT value `code`, if supported.
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            31 => Some(Operation::Alert),
+            51 => Some(Operation::SubmitShortMessage),
+            52 => Some(Operation::DeliverShortMessage),
+            53 => Some(Operation::DeliverNotification),
+            60 => Some(Operation::SessionManagement),
+            _ => None,
+        }
+    }
+
+    /// Whether the positive result carries MVP: only the 5x operations do.
+    fn result_has_mvp(self) -> bool {
+        (50..60).contains(&self.code())
+    }
+}
+
+/// One UCP message: the transaction reference number and the body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UcpMessage {
+    /// TRN, 0–99, echoed in the result.
+    pub trn: u8,
+    /// The operation or result and its fields.
+    pub body: UcpBody,
+}
+
+/// An operation and its fields, or a result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UcpBody {
+    /// Operation 31.
+    Alert(Alert),
+    /// Operation 51.
+    SubmitShortMessage(ShortMessage),
+    /// Operation 52.
+    DeliverShortMessage(ShortMessage),
+    /// Operation 53.
+    DeliverNotification(ShortMessage),
+    /// Operation 60.
+    SessionManagement(SessionManagement),
+    /// A positive result.
+    Ack(UcpAck),
+    /// A negative result.
+    Nack(UcpNack),
+}
+
+/// A positive result (`A`): `A/MVP/SM` for the 5x operations, `A/SM` for
+/// 31 and 60.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UcpAck {
+    /// The operation answered.
+    pub operation: Operation,
+    /// MVP: the modified validity period, `Some` for a 5x operation and
+    /// `None` for 31 and 60, whose results have no such field.
+    pub mvp: Option<String>,
+    /// SM: system message, for 51 usually `AdC:SCTS`.
+    pub system_message: String,
+}
+
+impl UcpAck {
+    /// A positive result for `operation`, with an empty MVP for a 5x
+    /// operation.
+    pub fn new(operation: Operation, system_message: &str) -> Self {
+        Self {
+            operation,
+            mvp: operation.result_has_mvp().then(String::new),
+            system_message: system_message.to_string(),
+        }
+    }
+}
+
+/// A negative result (`N`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UcpNack {
+    /// The operation answered.
+    pub operation: Operation,
+    /// EC: the error code, such as [`EC_SYNTAX_ERROR`].
+    pub error_code: u8,
+    /// SM: system message.
+    pub system_message: String,
+}
+
+impl UcpNack {
+    /// A negative result for `operation`.
+    pub fn new(operation: Operation, error_code: u8, system_message: &str) -> Self {
+        Self {
+            operation,
+            error_code,
+            system_message: system_message.to_string(),
+        }
+    }
+}
+
+impl UcpBody {
+    /// The operation this body is, or answers.
+    pub fn operation(&self) -> Operation {
+        match self {
+            UcpBody::Alert(_) => Operation::Alert,
+            UcpBody::SubmitShortMessage(_) => Operation::SubmitShortMessage,
+            UcpBody::DeliverShortMessage(_) => Operation::DeliverShortMessage,
+            UcpBody::DeliverNotification(_) => Operation::DeliverNotification,
+            UcpBody::SessionManagement(_) => Operation::SessionManagement,
+            UcpBody::Ack(ack) => ack.operation,
+            UcpBody::Nack(nack) => nack.operation,
+        }
+    }
+
+    /// Whether this is a result rather than an operation.
+    pub fn is_result(&self) -> bool {
+        matches!(self, UcpBody::Ack(_) | UcpBody::Nack(_))
+    }
+
+    fn read(operation: Operation, is_result: bool, fields: &[&str]) -> Result<Self> {
+        if is_result {
+            return match fields.first().copied() {
+                Some("A") if operation.result_has_mvp() => {
+                    let [_, mvp, system_message] = expect_fields::<3>(fields)?;
+                    Ok(UcpBody::Ack(UcpAck {
+                        operation,
+                        mvp: Some(mvp.to_string()),
+                        system_message: system_message.to_string(),
+                    }))
+                }
+                Some("A") => {
+                    let [_, system_message] = expect_fields::<2>(fields)?;
+                    Ok(UcpBody::Ack(UcpAck {
+                        operation,
+                        mvp: None,
+                        system_message: system_message.to_string(),
+                    }))
+                }
+                Some("N") => {
+                    let [_, error_code, system_message] = expect_fields::<3>(fields)?;
+                    Ok(UcpBody::Nack(UcpNack {
+                        operation,
+                        error_code: error_code.parse().map_err(|_| {
+                            invalid_field("EC", format!("'{error_code}' is not a number"))
+                        })?,
+                        system_message: system_message.to_string(),
+                    }))
+                }
+                ack => Err(invalid_field(
+                    "ACK",
+                    format!("'{}' is neither A nor N", ack.unwrap_or_default()),
+                )),
+            };
+        }
+        Ok(match operation {
+            Operation::Alert => UcpBody::Alert(Alert::read(fields)?),
+            Operation::SubmitShortMessage => {
+                UcpBody::SubmitShortMessage(ShortMessage::read(fields)?)
+            }
+            Operation::DeliverShortMessage => {
+                UcpBody::DeliverShortMessage(ShortMessage::read(fields)?)
+            }
+            Operation::DeliverNotification => {
+                UcpBody::DeliverNotification(ShortMessage::read(fields)?)
+            }
+            Operation::SessionManagement => {
+                UcpBody::SessionManagement(SessionManagement::read(fields)?)
+            }
+        })
+    }
+
+    fn fields(&self) -> Result<Vec<(&'static str, String)>> {
+        match self {
+            UcpBody::Alert(alert) => Ok(alert.fields()),
+            UcpBody::SubmitShortMessage(message)
+            | UcpBody::DeliverShortMessage(message)
+            | UcpBody::DeliverNotification(message) => message.fields(),
+            UcpBody::SessionManagement(session) => session.fields(),
+            UcpBody::Ack(ack) => {
+                let mut fields = vec![("ACK", "A".to_string())];
+                match (&ack.mvp, ack.operation.result_has_mvp()) {
+                    (mvp, true) => fields.push(("MVP", mvp.clone().unwrap_or_default())),
+                    (Some(_), false) => {
+                        return Err(invalid_field(
+                            "MVP",
+                            format!(
+                                "is not a field of the result of {:02}",
+                                ack.operation.code()
+                            ),
+                        ));
+                    }
+                    (None, false) => {}
+                }
+                fields.push(("SM", ack.system_message.clone()));
+                Ok(fields)
+            }
+            UcpBody::Nack(nack) => Ok(vec![
+                ("NACK", "N".to_string()),
+                ("EC", format!("{:02}", nack.error_code)),
+                ("SM", nack.system_message.clone()),
+            ]),
+        }
+    }
+}
+
+impl UcpMessage {
+    /// A message with transaction reference number `trn`.
+    pub fn new(trn: u8, body: UcpBody) -> Self {
+        Self { trn, body }
+    }
+
+    /// The length of the first framed message in `bytes`, for framing
+    /// messages on a stream.
+    ///
+    /// # Returns
+    ///
+    /// The length up to and including ETX, or `None` if `bytes` does not
+    /// start with STX or holds no ETX yet.
+    pub fn frame_length(bytes: &[u8]) -> Option<usize> {
+        if bytes.first() != Some(&STX) {
+            return None;
+        }
+        bytes
+            .iter()
+            .position(|&octet| octet == ETX)
+            .map(|end| end + 1)
+    }
+
+    /// Parse one message and verify its length and checksum.
+    ///
+    /// AMsg and alphanumeric OAdC values are decoded with the default
+    /// [`Gsm7Config`](crate::Gsm7Config).
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Exactly one message, with or without STX and ETX
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidUcpField` for a non-ASCII message, a LEN
+    /// that does not match, a wrong checksum, an unsupported operation, the
+    /// wrong number of fields for the operation, or a field that cannot be
+    /// decoded.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let inner = match bytes {
+            [STX, inner @ .., ETX] => inner,
+            _ => bytes,
+        };
+        let text = std::str::from_utf8(inner)
+            .ok()
+            .filter(|text| text.is_ascii())
+            .ok_or_else(|| invalid_field("message", "contains non-ASCII octets".to_string()))?;
+
+        let split = text.len().checked_sub(CHECKSUM_CHARACTERS).ok_or_else(|| {
+            invalid_field("message", format!("{} characters is too short", text.len()))
+        })?;
+        let (checked, received) = text.split_at(split);
+        let received = u8::from_str_radix(received, 16)
+            .ok()
+            .filter(|_| received.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| invalid_field("CHK", format!("'{received}' is not two hex digits")))?;
+        let computed = checksum(checked.as_bytes());
+        if received != computed {
+            return Err(invalid_field(
+                "CHK",
+                format!("{received:02X} does not match the computed {computed:02X}"),
+            ));
+        }
+
+        let Some(checked) = checked.strip_suffix('/') else {
+            return Err(invalid_field("CHK", "is not preceded by '/'".to_string()));
+        };
+        let fields: Vec<&str> = checked.split('/').collect();
+        let [trn, length, direction, operation, data @ ..] = &fields[..] else {
+            return Err(invalid_field(
+                "header",
+                format!("{} fields, expected TRN/LEN/O|R/OT", fields.len()),
+            ));
+        };
+
+        let trn = parse_number(trn, "TRN", 2)?;
+        let length = parse_number(length, "LEN", 5)?;
+        if length != text.len() {
+            return Err(invalid_field(
+                "LEN",
+                format!("{length}, but the message has {} characters", text.len()),
+            ));
+        }
+        let is_result = match *direction {
+            "O" => false,
+            "R" => true,
+            _ => {
+                return Err(invalid_field(
+                    "O/R",
+                    format!("'{direction}' is neither O nor R"),
+                ));
+            }
+        };
+        let code = parse_number(operation, "OT", 2)?;
+        let operation = u8::try_from(code)
+            .ok()
+            .and_then(Operation::from_code)
+            .ok_or_else(|| invalid_field("OT", format!("unsupported operation {code:02}")))?;
+
+        Ok(Self {
+            trn: trn as u8,
+            body: UcpBody::read(operation, is_result, data)?,
+        })
+    }
+
+    /// Serialize the message between STX and ETX, with LEN and the checksum
+    /// filled in.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidUcpField` for a TRN above 99, a field
+    /// holding `/` or a non-printable or non-ASCII character, a message longer
+    /// than LEN can count, or text that cannot be encoded; see
+    /// [`ShortMessage`] and [`SessionManagement`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        if self.trn > 99 {
+            return Err(invalid_field("TRN", format!("{} is above 99", self.trn)));
+        }
+        let fields = self.body.fields()?;
+        let mut data = String::new();
+        for (name, value) in &fields {
+            if let Some(ch) = value
+                .chars()
+                .find(|&ch| ch == '/' || !(ch.is_ascii_graphic() || ch == ' '))
+            {
+                return Err(invalid_field(name, format!("'{value}' contains {ch:?}")));
+            }
+            data.push_str(value);
+            data.push('/');
+        }
+
+        let length = HEADER_CHARACTERS + data.len() + CHECKSUM_CHARACTERS;
+        if length > MAX_LENGTH {
+            return Err(invalid_field(
+                "LEN",
+                format!("{length} characters, more than {MAX_LENGTH}"),
+            ));
+        }
+        let direction = if self.body.is_result() { 'R' } else { 'O' };
+        let checked = format!(
+            "{:02}/{:05}/{}/{:02}/{}",
+            self.trn,
+            length,
+            direction,
+            self.body.operation().code(),
+            data
+        );
+
+        let mut bytes = Vec::with_capacity(length + 2);
+        bytes.push(STX);
+        bytes.extend_from_slice(checked.as_bytes());
+        bytes.extend_from_slice(format!("{:02X}", checksum(checked.as_bytes())).as_bytes());
+        bytes.push(ETX);
+        Ok(bytes)
+    }
+}
+
+/// The UCP checksum: the sum of the octets from TRN up to and including the
+/// `/` before CHK, modulo 256.
+pub fn checksum(octets: &[u8]) -> u8 {
+    octets
+        .iter()
+        .fold(0u8, |sum, &octet| sum.wrapping_add(octet))
+}
+
+pub(crate) fn invalid_field(field: &'static str, reason: String) -> Gsm7Error {
+    Gsm7Error::InvalidUcpField { field, reason }
+}
+
+/// The data fields of an operation, checking that there are `N`.
+fn expect_fields<'a, const N: usize>(fields: &[&'a str]) -> Result<[&'a str; N]> {
+    fields.try_into().map_err(|_| {
+        invalid_field(
+            "fields",
+            format!("{} data fields, expected {N}", fields.len()),
+        )
+    })
+}
+
+/// A decimal field of exactly `digits` digits.
+fn parse_number(value: &str, field: &'static str, digits: usize) -> Result<usize> {
+    if value.len() != digits || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid_field(
+            field,
+            format!("'{value}' is not {digits} digits"),
+        ));
+    }
+    Ok(value.parse().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_roundtrip() {
+        let ack = UcpMessage::new(
+            0,
+            UcpBody::Ack(UcpAck::new(
+                Operation::SubmitShortMessage,
+                "012345:090996101010",
+            )),
+        );
+        let bytes = ack.to_bytes().unwrap();
+        let text = std::str::from_utf8(&bytes[1..bytes.len() - 1]).unwrap();
+        assert!(text.starts_with("00/00039/R/51/A//012345:090996101010/"));
+        let sum: u32 = text.bytes().take(text.len() - 2).map(u32::from).sum();
+        assert_eq!(&text[text.len() - 2..], format!("{:02X}", sum % 256));
+        assert_eq!(UcpMessage::from_bytes(&bytes).unwrap(), ack);
+
+        let nack = UcpMessage::new(
+            0,
+            UcpBody::Nack(UcpNack::new(
+                Operation::SessionManagement,
+                EC_AUTHENTICATION_FAILURE,
+                "bad login",
+            )),
+        );
+        let bytes = nack.to_bytes().unwrap();
+        assert!(bytes.starts_with(b"\x0200/00031/R/60/N/07/bad login/"));
+        assert_eq!(UcpMessage::frame_length(&bytes), Some(bytes.len()));
+        assert_eq!(UcpMessage::from_bytes(&bytes).unwrap(), nack);
+        // STX and ETX are optional, and the checksum may be lower case
+        let mut inner = bytes[1..bytes.len() - 1].to_vec();
+        let chk = inner.len() - 2;
+        inner[chk..].make_ascii_lowercase();
+        assert_eq!(UcpMessage::from_bytes(&inner).unwrap(), nack);
+    }
+
+    #[test]
+    fn test_checksum_and_length_errors() {
+        let mut bytes = UcpMessage::new(1, UcpBody::Ack(UcpAck::new(Operation::Alert, "")))
+            .to_bytes()
+            .unwrap();
+        let chk = bytes.len() - 2;
+        bytes[chk] ^= 0x01;
+        assert!(matches!(
+            UcpMessage::from_bytes(&bytes),
+            Err(Gsm7Error::InvalidUcpField { field: "CHK", .. })
+        ));
+
+        let text = "01/00099/R/31/A//";
+        let framed = format!("{text}{:02X}", checksum(text.as_bytes()));
+        assert!(matches!(
+            UcpMessage::from_bytes(framed.as_bytes()),
+            Err(Gsm7Error::InvalidUcpField { field: "LEN", .. })
+        ));
+
+        let text = "01/00020/O/99/x///";
+        let framed = format!("{text}{:02X}", checksum(text.as_bytes()));
+        assert!(matches!(
+            UcpMessage::from_bytes(framed.as_bytes()),
+            Err(Gsm7Error::InvalidUcpField { field: "OT", .. })
+        ));
+        assert!(UcpMessage::from_bytes(b"\x02\x03").is_err());
+        assert_eq!(UcpMessage::frame_length(b"\x0201/000"), None);
+        assert_eq!(UcpMessage::frame_length(b"01/00"), None);
+    }
+
+    #[test]
+    fn test_invalid_fields_on_write() {
+        let nack = |system_message: &str| {
+            UcpMessage::new(
+                1,
+                UcpBody::Nack(UcpNack::new(
+                    Operation::Alert,
+                    EC_SYNTAX_ERROR,
+                    system_message,
+                )),
+            )
+            .to_bytes()
+        };
+        assert!(nack("ok").is_ok());
+        assert!(matches!(
+            nack("a/b"),
+            Err(Gsm7Error::InvalidUcpField { field: "SM", .. })
+        ));
+        assert!(nack("é").is_err());
+        assert!(
+            UcpMessage::new(100, UcpBody::Ack(UcpAck::new(Operation::Alert, "")))
+                .to_bytes()
+                .is_err()
+        );
+        let mut ack = UcpAck::new(Operation::SessionManagement, "");
+        ack.mvp = Some(String::new());
+        assert!(matches!(
+            UcpMessage::new(1, UcpBody::Ack(ack)).to_bytes(),
+            Err(Gsm7Error::InvalidUcpField { field: "MVP", .. })
+        ));
+    }
+
+    #[test]
+    fn test_alert_and_session_results_have_no_mvp() {
+        // The positive results of 31 and 60 from the specification
+        for (frame, operation) in [
+            (&b"00/00019/R/60/A//6D"[..], Operation::SessionManagement),
+            (&b"00/00019/R/31/A//6B"[..], Operation::Alert),
+        ] {
+            let message = UcpMessage::from_bytes(frame).unwrap();
+            let ack = UcpAck::new(operation, "");
+            assert_eq!(ack.mvp, None);
+            assert_eq!(message, UcpMessage::new(0, UcpBody::Ack(ack)));
+            assert_eq!(&message.to_bytes().unwrap()[1..20], frame);
+        }
+        assert!(matches!(
+            UcpMessage::from_bytes(b"00/00020/R/60/A///94"),
+            Err(Gsm7Error::InvalidUcpField {
+                field: "fields",
+                ..
+            })
+        ));
+
+        let text = "01/00022/R/31/N/02//";
+        let nack = UcpMessage::from_bytes(format!("{text}04").as_bytes()).unwrap();
+        assert_eq!(
+            nack.body,
+            UcpBody::Nack(UcpNack::new(Operation::Alert, EC_SYNTAX_ERROR, ""))
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Operations 31 (alert) and 60 (session management).
+
+use super::{expect_fields, invalid_field};
+use crate::{Result, hex};
+
+/// The fields of operation 31, alert.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Alert {
+    /// AdC: the address to alert.
+    pub adc: String,
+    /// PID: `0539` for a PC over TCP/IP, `0100` for a mobile station.
+    pub pid: String,
+}
+
+impl Alert {
+    /// An alert for `adc` with `pid`.
+    pub fn new(adc: &str, pid: &str) -> Self {
+        Self {
+            adc: adc.to_string(),
+            pid: pid.to_string(),
+        }
+    }
+
+    /// The two data fields as sent, with their UCP names.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![("AdC", self.adc.clone()), ("PID", self.pid.clone())]
+    }
+
+    pub(super) fn read(fields: &[&str]) -> Result<Self> {
+        let [adc, pid] = expect_fields::<2>(fields)?;
+        Ok(Self::new(adc, pid))
+    }
+}
+
+/// The fields of operation 60, session management.
+///
+/// PWD and NPWD are sent as IRA hex: each ASCII character as two hex digits.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::ucp::SessionManagement;
+///
+/// let login = SessionManagement::login("0612345678", "secret");
+/// let fields = login.fields()?;
+/// assert_eq!(fields[4], ("PWD", "736563726574".to_string()));
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionManagement {
+    /// OAdC: the large account address.
+    pub oadc: String,
+    /// OTON: originator type of number, `6` for an abbreviated number.
+    pub oton: String,
+    /// ONPI: originator numbering plan, `5` for a private plan.
+    pub onpi: String,
+    /// STYP: subtype, `1` to open a session.
+    pub styp: String,
+    /// PWD: the password, decoded.
+    pub pwd: String,
+    /// NPWD: a new password, decoded.
+    pub npwd: String,
+    /// VERS: protocol version, `0100`.
+    pub vers: String,
+    /// LAdC: address for a VSMSC list.
+    pub ladc: String,
+    /// LTON: type of number of LAdC.
+    pub lton: String,
+    /// LNPI: numbering plan of LAdC.
+    pub lnpi: String,
+    /// OPID: originator protocol identifier.
+    pub opid: String,
+    /// RES1: reserved.
+    pub res1: String,
+}
+
+impl SessionManagement {
+    /// An open-session request for the large account `oadc`: an abbreviated
+    /// number in a private plan, protocol version 0100.
+    pub fn login(oadc: &str, password: &str) -> Self {
+        Self {
+            oadc: oadc.to_string(),
+            oton: "6".to_string(),
+            onpi: "5".to_string(),
+            styp: "1".to_string(),
+            pwd: password.to_string(),
+            vers: "0100".to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// The 12 data fields as sent, with their UCP names.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidUcpField` for a password with non-ASCII
+    /// characters.
+    pub fn fields(&self) -> Result<Vec<(&'static str, String)>> {
+        Ok(vec![
+            ("OAdC", self.oadc.clone()),
+            ("OTON", self.oton.clone()),
+            ("ONPI", self.onpi.clone()),
+            ("STYP", self.styp.clone()),
+            ("PWD", encode_ira("PWD", &self.pwd)?),
+            ("NPWD", encode_ira("NPWD", &self.npwd)?),
+            ("VERS", self.vers.clone()),
+            ("LAdC", self.ladc.clone()),
+            ("LTON", self.lton.clone()),
+            ("LNPI", self.lnpi.clone()),
+            ("OPID", self.opid.clone()),
+            ("RES1", self.res1.clone()),
+        ])
+    }
+
+    pub(super) fn read(fields: &[&str]) -> Result<Self> {
+        let [
+            oadc,
+            oton,
+            onpi,
+            styp,
+            pwd,
+            npwd,
+            vers,
+            ladc,
+            lton,
+            lnpi,
+            opid,
+            res1,
+        ] = expect_fields::<12>(fields)?;
+        let text = |value: &str| value.to_string();
+        Ok(Self {
+            oadc: text(oadc),
+            oton: text(oton),
+            onpi: text(onpi),
+            styp: text(styp),
+            pwd: decode_ira("PWD", pwd)?,
+            npwd: decode_ira("NPWD", npwd)?,
+            vers: text(vers),
+            ladc: text(ladc),
+            lton: text(lton),
+            lnpi: text(lnpi),
+            opid: text(opid),
+            res1: text(res1),
+        })
+    }
+}
+
+fn encode_ira(field: &'static str, value: &str) -> Result<String> {
+    if !value.is_ascii() {
+        return Err(invalid_field(
+            field,
+            "contains non-ASCII characters".to_string(),
+        ));
+    }
+    Ok(hex::encode(value.as_bytes()))
+}
+
+fn decode_ira(field: &'static str, value: &str) -> Result<String> {
+    let octets = hex::decode(value).map_err(|err| invalid_field(field, err.to_string()))?;
+    if !octets.is_ascii() {
+        return Err(invalid_field(
+            field,
+            "contains non-ASCII characters".to_string(),
+        ));
+    }
+    Ok(octets.iter().map(|&octet| char::from(octet)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Gsm7Error;
+    use crate::ucp::{UcpBody, UcpMessage};
+
+    #[test]
+    fn test_login_roundtrip() {
+        let login = SessionManagement::login("0612345678", "pa55_word");
+        let bytes = UcpMessage::new(1, UcpBody::SessionManagement(login.clone()))
+            .to_bytes()
+            .unwrap();
+        assert!(bytes.starts_with(b"\x0201/00063/O/60/0612345678/6/5/1/70613535"));
+        let message = UcpMessage::from_bytes(&bytes).unwrap();
+        assert_eq!(message.body, UcpBody::SessionManagement(login));
+
+        assert!(matches!(
+            SessionManagement::login("1", "pässword").fields(),
+            Err(Gsm7Error::InvalidUcpField { field: "PWD", .. })
+        ));
+        assert!(decode_ira("PWD", "E4").is_err());
+    }
+
+    #[test]
+    fn test_alert() {
+        let alert = UcpBody::Alert(Alert::new("0612345678", "0539"));
+        let bytes = UcpMessage::new(2, alert.clone()).to_bytes().unwrap();
+        assert!(bytes.starts_with(b"\x0202/00032/O/31/0612345678/0539/"));
+        assert_eq!(UcpMessage::from_bytes(&bytes).unwrap().body, alert);
+    }
+}
@@ -0,0 +1,219 @@
+//! UCS-2 / UTF-16BE encoding and decoding.
+//!
+//! Text that does not fit the GSM 7-bit alphabet is sent as UCS-2: two octets
+//! per character, big-endian. UCS-2 proper only covers the Basic Multilingual
+//! Plane; most handsets also accept UTF-16 surrogate pairs for characters
+//! beyond it, such as emoji.
+
+use crate::{Gsm7Error, Result};
+
+/// How characters outside the Basic Multilingual Plane are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ucs2Mode {
+    /// Strict UCS-2: characters outside the BMP, and surrogate code units in
+    /// received data, are errors.
+    Strict,
+    /// UTF-16BE: characters outside the BMP are encoded as surrogate pairs.
+    #[default]
+    Utf16,
+}
+
+/// Encode a string as UTF-16BE.
+///
+/// Characters outside the Basic Multilingual Plane become surrogate pairs.
+///
+/// # Arguments
+///
+/// * `content` - The string to encode
+///
+/// # Returns
+///
+/// A `Vec<u8>` with two octets per UTF-16 code unit.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{decode_ucs2, encode_ucs2};
+///
+/// let encoded = encode_ucs2("Привет 🦀")?;
+/// assert_eq!(encoded.len(), 7 * 2 + 4);
+/// assert_eq!(decode_ucs2(&encoded)?, "Привет 🦀");
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+pub fn encode_ucs2(content: &str) -> Result<Vec<u8>> {
+    encode_ucs2_with_mode(content, Ucs2Mode::default())
+}
+
+/// Encode a string as UCS-2 or UTF-16BE.
+///
+/// # Arguments
+///
+/// * `content` - The string to encode
+/// * `mode` - Whether characters outside the BMP are rejected or encoded as surrogate pairs
+///
+/// # Returns
+///
+/// A `Vec<u8>` with two octets per UTF-16 code unit.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::UnsupportedUcs2Character` in strict mode if the input
+/// contains a character outside the Basic Multilingual Plane.
+pub fn encode_ucs2_with_mode(content: &str, mode: Ucs2Mode) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(content.len() * 2);
+    let mut units = [0u16; 2];
+
+    for ch in content.chars() {
+        let encoded = ch.encode_utf16(&mut units);
+        if mode == Ucs2Mode::Strict && encoded.len() > 1 {
+            return Err(Gsm7Error::UnsupportedUcs2Character {
+                character: ch,
+                code: ch as u32,
+            });
+        }
+        for unit in encoded {
+            bytes.extend_from_slice(&unit.to_be_bytes());
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Decode UTF-16BE octets to a string.
+///
+/// # Arguments
+///
+/// * `data` - The encoded octets
+///
+/// # Returns
+///
+/// A `String` containing the decoded text.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::MalformedData` if `data` has an odd length, and
+/// `Gsm7Error::InvalidUtf16` if it contains an unpaired surrogate.
+pub fn decode_ucs2(data: &[u8]) -> Result<String> {
+    decode_ucs2_with_mode(data, Ucs2Mode::default())
+}
+
+/// Decode UCS-2 or UTF-16BE octets to a string.
+///
+/// # Arguments
+///
+/// * `data` - The encoded octets
+/// * `mode` - Whether surrogate pairs are accepted
+///
+/// # Returns
+///
+/// A `String` containing the decoded text.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::MalformedData` if `data` has an odd length, and
+/// `Gsm7Error::InvalidUtf16` if it contains an unpaired surrogate, or any
+/// surrogate in strict mode.
+pub fn decode_ucs2_with_mode(data: &[u8], mode: Ucs2Mode) -> Result<String> {
+    if !data.len().is_multiple_of(2) {
+        return Err(Gsm7Error::MalformedData {
+            reason: format!("UCS-2 data has odd length {}", data.len()),
+        });
+    }
+
+    let units = data
+        .chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+    let mut result = String::with_capacity(data.len() / 2);
+    let mut offset = 0;
+
+    for decoded in char::decode_utf16(units) {
+        let unit = match decoded {
+            Ok(ch) if mode == Ucs2Mode::Strict && ch.len_utf16() > 1 => {
+                u16::from_be_bytes([data[offset], data[offset + 1]])
+            }
+            Ok(ch) => {
+                result.push(ch);
+                offset += ch.len_utf16() * 2;
+                continue;
+            }
+            Err(err) => err.unpaired_surrogate(),
+        };
+        return Err(Gsm7Error::InvalidUtf16 { offset, unit });
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_bmp() {
+        assert_eq!(
+            encode_ucs2("Aé€").unwrap(),
+            [0x00, 0x41, 0x00, 0xE9, 0x20, 0xAC]
+        );
+        assert_eq!(
+            encode_ucs2_with_mode("Aé€", Ucs2Mode::Strict).unwrap(),
+            [0x00, 0x41, 0x00, 0xE9, 0x20, 0xAC]
+        );
+    }
+
+    #[test]
+    fn test_surrogate_pairs() {
+        let encoded = encode_ucs2("🦀").unwrap();
+        assert_eq!(encoded, [0xD8, 0x3E, 0xDD, 0x80]);
+        assert_eq!(decode_ucs2(&encoded).unwrap(), "🦀");
+    }
+
+    #[test]
+    fn test_strict_rejects_outside_bmp() {
+        assert_eq!(
+            encode_ucs2_with_mode("a🦀", Ucs2Mode::Strict),
+            Err(Gsm7Error::UnsupportedUcs2Character {
+                character: '🦀',
+                code: 0x1F980,
+            })
+        );
+        assert_eq!(
+            decode_ucs2_with_mode(&[0x00, 0x61, 0xD8, 0x3E, 0xDD, 0x80], Ucs2Mode::Strict),
+            Err(Gsm7Error::InvalidUtf16 {
+                offset: 2,
+                unit: 0xD83E,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unpaired_surrogate() {
+        assert_eq!(
+            decode_ucs2(&[0x00, 0x61, 0xDD, 0x80, 0x00, 0x62]),
+            Err(Gsm7Error::InvalidUtf16 {
+                offset: 2,
+                unit: 0xDD80,
+            })
+        );
+        assert_eq!(
+            decode_ucs2(&[0xD8, 0x3E]),
+            Err(Gsm7Error::InvalidUtf16 {
+                offset: 0,
+                unit: 0xD83E,
+            })
+        );
+    }
+
+    #[test]
+    fn test_odd_length() {
+        assert!(matches!(
+            decode_ucs2(&[0x00, 0x41, 0x00]),
+            Err(Gsm7Error::MalformedData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(encode_ucs2("").unwrap().is_empty());
+        assert_eq!(decode_ucs2(&[]).unwrap(), "");
+    }
+}
@@ -0,0 +1,495 @@
+//! TP-UDH User Data Header (3GPP TS 23.040 §9.2.3.24).
+//!
+//! The header is a length octet (UDHL) followed by information elements, each
+//! an identifier, a length and data. It sits in front of the text, and for
+//! GSM 7-bit the text starts on the next septet boundary after it.
+
+use crate::{
+    ConcatReference, Gsm7Error, IndicationType, NationalLanguage, Result, fill_bits_for_udh,
+};
+
+/// IE identifier: concatenated short messages, 8-bit reference number.
+pub(crate) const IEI_CONCAT_8BIT: u8 = 0x00;
+/// IE identifier: special SMS message indication.
+pub(crate) const IEI_SPECIAL_INDICATION: u8 = 0x01;
+/// IE identifier: application port addressing, 8-bit ports.
+pub(crate) const IEI_PORTS_8BIT: u8 = 0x04;
+/// IE identifier: application port addressing, 16-bit ports.
+pub(crate) const IEI_PORTS_16BIT: u8 = 0x05;
+/// IE identifier: concatenated short messages, 16-bit reference number.
+pub(crate) const IEI_CONCAT_16BIT: u8 = 0x08;
+/// IE identifier: EMS text formatting.
+pub(crate) const IEI_TEXT_FORMATTING: u8 = 0x0A;
+/// IE identifier: national language single shift.
+pub(crate) const IEI_SINGLE_SHIFT: u8 = 0x24;
+/// IE identifier: national language locking shift.
+pub(crate) const IEI_LOCKING_SHIFT: u8 = 0x25;
+
+/// One information element of a User Data Header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InformationElement {
+    /// Concatenated short message (IE 0x00 or 0x08, by reference width).
+    Concatenation {
+        /// Reference number shared by all parts.
+        reference: ConcatReference,
+        /// Number of parts.
+        total: u8,
+        /// This part's number, from 1.
+        sequence: u8,
+    },
+    /// Application port addressing with 8-bit ports (IE 0x04).
+    Ports8 {
+        /// Destination port.
+        destination: u8,
+        /// Originator port.
+        source: u8,
+    },
+    /// Application port addressing with 16-bit ports (IE 0x05), e.g. WAP push on 2948.
+    Ports16 {
+        /// Destination port.
+        destination: u16,
+        /// Originator port.
+        source: u16,
+    },
+    /// Special SMS message indication (IE 0x01): waiting message counts.
+    SpecialMessageIndication {
+        /// Store the message rather than discard it after updating the indication.
+        store: bool,
+        /// Basic indication type.
+        indication: IndicationType,
+        /// Extended message indication type (0 for none), 0–7.
+        extended_type: u8,
+        /// Multiple subscriber profile, 0–3.
+        profile: u8,
+        /// Number of waiting messages.
+        count: u8,
+    },
+    /// National language single shift table (IE 0x24).
+    SingleShift(NationalLanguage),
+    /// National language locking shift table (IE 0x25).
+    LockingShift(NationalLanguage),
+    /// EMS text formatting (IE 0x0A).
+    TextFormatting {
+        /// First character the formatting applies to.
+        start: u8,
+        /// Number of characters.
+        length: u8,
+        /// Formatting mode: alignment, font size, style bits.
+        mode: u8,
+        /// Foreground and background colour, if given.
+        colour: Option<u8>,
+    },
+    /// Any other IE, or a known IE whose data has the wrong length.
+    Unknown {
+        /// IE identifier.
+        identifier: u8,
+        /// IE data.
+        data: Vec<u8>,
+    },
+}
+
+impl InformationElement {
+    fn parse(identifier: u8, data: &[u8]) -> Self {
+        Self::parse_known(identifier, data).unwrap_or_else(|| InformationElement::Unknown {
+            identifier,
+            data: data.to_vec(),
+        })
+    }
+
+    fn parse_known(identifier: u8, data: &[u8]) -> Option<Self> {
+        Some(match (identifier, data) {
+            (IEI_CONCAT_8BIT, &[reference, total, sequence]) => InformationElement::Concatenation {
+                reference: ConcatReference::EightBit(reference),
+                total,
+                sequence,
+            },
+            (IEI_CONCAT_16BIT, &[high, low, total, sequence]) => {
+                InformationElement::Concatenation {
+                    reference: ConcatReference::SixteenBit(u16::from_be_bytes([high, low])),
+                    total,
+                    sequence,
+                }
+            }
+            (IEI_PORTS_8BIT, &[destination, source]) => InformationElement::Ports8 {
+                destination,
+                source,
+            },
+            (IEI_PORTS_16BIT, &[dest_high, dest_low, src_high, src_low]) => {
+                InformationElement::Ports16 {
+                    destination: u16::from_be_bytes([dest_high, dest_low]),
+                    source: u16::from_be_bytes([src_high, src_low]),
+                }
+            }
+            (IEI_SPECIAL_INDICATION, &[kind, count]) => {
+                InformationElement::SpecialMessageIndication {
+                    store: kind & 0x80 != 0,
+                    indication: IndicationType::from_bits(kind),
+                    extended_type: (kind >> 2) & 0b111,
+                    profile: (kind >> 5) & 0b11,
+                    count,
+                }
+            }
+            (IEI_SINGLE_SHIFT, &[id]) => {
+                InformationElement::SingleShift(NationalLanguage::from_id(id)?)
+            }
+            (IEI_LOCKING_SHIFT, &[id]) => {
+                InformationElement::LockingShift(NationalLanguage::from_id(id)?)
+            }
+            (IEI_TEXT_FORMATTING, &[start, length, mode]) => InformationElement::TextFormatting {
+                start,
+                length,
+                mode,
+                colour: None,
+            },
+            (IEI_TEXT_FORMATTING, &[start, length, mode, colour]) => {
+                InformationElement::TextFormatting {
+                    start,
+                    length,
+                    mode,
+                    colour: Some(colour),
+                }
+            }
+            _ => return None,
+        })
+    }
+
+    /// Append identifier, length and data to `bytes`.
+    fn write(&self, bytes: &mut Vec<u8>) {
+        let (identifier, data): (u8, Vec<u8>) = match self {
+            InformationElement::Concatenation {
+                reference: ConcatReference::EightBit(reference),
+                total,
+                sequence,
+            } => (IEI_CONCAT_8BIT, vec![*reference, *total, *sequence]),
+            InformationElement::Concatenation {
+                reference: ConcatReference::SixteenBit(reference),
+                total,
+                sequence,
+            } => {
+                let [high, low] = reference.to_be_bytes();
+                (IEI_CONCAT_16BIT, vec![high, low, *total, *sequence])
+            }
+            InformationElement::Ports8 {
+                destination,
+                source,
+            } => (IEI_PORTS_8BIT, vec![*destination, *source]),
+            InformationElement::Ports16 {
+                destination,
+                source,
+            } => {
+                let mut data = destination.to_be_bytes().to_vec();
+                data.extend_from_slice(&source.to_be_bytes());
+                (IEI_PORTS_16BIT, data)
+            }
+            InformationElement::SpecialMessageIndication {
+                store,
+                indication,
+                extended_type,
+                profile,
+                count,
+            } => {
+                let kind = u8::from(*store) << 7
+                    | (profile & 0b11) << 5
+                    | (extended_type & 0b111) << 2
+                    | *indication as u8;
+                (IEI_SPECIAL_INDICATION, vec![kind, *count])
+            }
+            InformationElement::SingleShift(language) => (IEI_SINGLE_SHIFT, vec![language.id()]),
+            InformationElement::LockingShift(language) => (IEI_LOCKING_SHIFT, vec![language.id()]),
+            InformationElement::TextFormatting {
+                start,
+                length,
+                mode,
+                colour,
+            } => {
+                let mut data = vec![*start, *length, *mode];
+                data.extend(colour);
+                (IEI_TEXT_FORMATTING, data)
+            }
+            InformationElement::Unknown { identifier, data } => (*identifier, data.clone()),
+        };
+        bytes.push(identifier);
+        bytes.push(data.len() as u8);
+        bytes.extend(data);
+    }
+}
+
+/// A parsed User Data Header.
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{ConcatReference, InformationElement, NationalLanguage, UserDataHeader};
+///
+/// let udh = UserDataHeader::parse(&[0x08, 0x00, 0x03, 0x2A, 0x02, 0x01, 0x25, 0x01, 0x01])?;
+/// assert_eq!(
+///     udh.concatenation(),
+///     Some((ConcatReference::EightBit(0x2A), 2, 1))
+/// );
+/// assert_eq!(udh.locking_shift(), Some(NationalLanguage::Turkish));
+/// assert_eq!(udh.fill_bits(), 5);
+/// assert_eq!(udh.to_bytes()[0], 0x08);
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UserDataHeader {
+    /// The information elements, in order.
+    pub elements: Vec<InformationElement>,
+}
+
+impl UserDataHeader {
+    /// Create a header from information elements.
+    pub fn new(elements: Vec<InformationElement>) -> Self {
+        Self { elements }
+    }
+
+    /// Parse a header starting with its UDHL octet.
+    ///
+    /// Bytes after the header (the text) are ignored. Known IEs with a data
+    /// length the specification does not allow are kept as
+    /// [`InformationElement::Unknown`].
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The user data, starting with the UDHL octet
+    ///
+    /// # Returns
+    ///
+    /// The header with its IEs in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Gsm7Error::InvalidPduField` with the offset in `data` if the
+    /// UDHL or an IE length runs past the end of the header.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let invalid = |offset: usize, reason: &str| Gsm7Error::InvalidPduField {
+            field: "TP-UDH",
+            offset,
+            reason: reason.to_string(),
+        };
+
+        let (&udhl, rest) = data
+            .split_first()
+            .ok_or_else(|| invalid(0, "missing UDHL"))?;
+        let mut ies = rest
+            .get(..usize::from(udhl))
+            .ok_or_else(|| invalid(0, "UDHL longer than the user data"))?;
+
+        let mut elements = Vec::new();
+        let mut offset = 1;
+        while !ies.is_empty() {
+            let [identifier, length, rest @ ..] = ies else {
+                return Err(invalid(offset, "IE header runs past the UDH"));
+            };
+            let data = rest
+                .get(..usize::from(*length))
+                .ok_or_else(|| invalid(offset, "IE data runs past the UDH"))?;
+            elements.push(InformationElement::parse(*identifier, data));
+            ies = &rest[data.len()..];
+            offset += 2 + data.len();
+        }
+
+        Ok(Self { elements })
+    }
+
+    /// Serialize the header, UDHL first. An empty header serializes to no bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0];
+        for element in &self.elements {
+            element.write(&mut bytes);
+        }
+        if bytes.len() == 1 {
+            return Vec::new();
+        }
+        bytes[0] = (bytes.len() - 1) as u8;
+        bytes
+    }
+
+    /// Length in octets including the UDHL octet, or 0 for an empty header.
+    pub fn len(&self) -> usize {
+        self.to_bytes().len()
+    }
+
+    /// Whether the header has no IEs.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Fill bits between the header and GSM 7-bit text.
+    pub fn fill_bits(&self) -> u8 {
+        fill_bits_for_udh(self.len())
+    }
+
+    /// Septets taken by the header and its fill bits in a GSM 7-bit TP-UDL.
+    pub fn septets(&self) -> usize {
+        (self.len() * 8 + usize::from(self.fill_bits())) / 7
+    }
+
+    /// Reference, total and sequence number of the concatenation IE, if any.
+    pub fn concatenation(&self) -> Option<(ConcatReference, u8, u8)> {
+        self.elements.iter().find_map(|element| match element {
+            InformationElement::Concatenation {
+                reference,
+                total,
+                sequence,
+            } => Some((*reference, *total, *sequence)),
+            _ => None,
+        })
+    }
+
+    /// Destination and source port, if the header has port addressing.
+    pub fn ports(&self) -> Option<(u16, u16)> {
+        self.elements.iter().find_map(|element| match *element {
+            InformationElement::Ports8 {
+                destination,
+                source,
+            } => Some((destination.into(), source.into())),
+            InformationElement::Ports16 {
+                destination,
+                source,
+            } => Some((destination, source)),
+            _ => None,
+        })
+    }
+
+    /// The national language locking shift table, if announced.
+    pub fn locking_shift(&self) -> Option<NationalLanguage> {
+        self.elements.iter().find_map(|element| match element {
+            InformationElement::LockingShift(language) => Some(*language),
+            _ => None,
+        })
+    }
+
+    /// The national language single shift table, if announced.
+    pub fn single_shift(&self) -> Option<NationalLanguage> {
+        self.elements.iter().find_map(|element| match element {
+            InformationElement::SingleShift(language) => Some(*language),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_all_elements() {
+        let udh = UserDataHeader::new(vec![
+            InformationElement::Concatenation {
+                reference: ConcatReference::SixteenBit(0xBEEF),
+                total: 3,
+                sequence: 2,
+            },
+            InformationElement::Ports16 {
+                destination: 2948,
+                source: 9200,
+            },
+            InformationElement::Ports8 {
+                destination: 0xF5,
+                source: 0x01,
+            },
+            InformationElement::SpecialMessageIndication {
+                store: true,
+                indication: IndicationType::Voicemail,
+                extended_type: 0,
+                profile: 1,
+                count: 4,
+            },
+            InformationElement::SingleShift(NationalLanguage::Spanish),
+            InformationElement::LockingShift(NationalLanguage::Portuguese),
+            InformationElement::TextFormatting {
+                start: 0,
+                length: 5,
+                mode: 0x10,
+                colour: Some(0x2F),
+            },
+            InformationElement::Unknown {
+                identifier: 0x70,
+                data: vec![1, 2],
+            },
+        ]);
+        let bytes = udh.to_bytes();
+        assert_eq!(
+            &bytes[..13],
+            [
+                0x24, 0x08, 0x04, 0xBE, 0xEF, 0x03, 0x02, 0x05, 0x04, 0x0B, 0x84, 0x23, 0xF0
+            ]
+        );
+        assert_eq!(usize::from(bytes[0]) + 1, bytes.len());
+        assert_eq!(UserDataHeader::parse(&bytes).unwrap(), udh);
+        assert_eq!(udh.ports(), Some((2948, 9200)));
+        assert_eq!(udh.single_shift(), Some(NationalLanguage::Spanish));
+    }
+
+    #[test]
+    fn test_special_message_indication_bits() {
+        let udh = UserDataHeader::parse(&[0x04, 0x01, 0x02, 0xA1, 0x03]).unwrap();
+        assert_eq!(
+            udh.elements,
+            [InformationElement::SpecialMessageIndication {
+                store: true,
+                indication: IndicationType::Fax,
+                extended_type: 0,
+                profile: 1,
+                count: 3,
+            }]
+        );
+        assert_eq!(udh.to_bytes(), [0x04, 0x01, 0x02, 0xA1, 0x03]);
+    }
+
+    #[test]
+    fn test_wrong_length_and_unknown_language_kept_raw() {
+        let bytes = [0x07, 0x00, 0x02, 0x2A, 0x02, 0x24, 0x01, 0x63];
+        let udh = UserDataHeader::parse(&bytes).unwrap();
+        assert_eq!(
+            udh.elements,
+            [
+                InformationElement::Unknown {
+                    identifier: 0x00,
+                    data: vec![0x2A, 0x02],
+                },
+                InformationElement::Unknown {
+                    identifier: 0x24,
+                    data: vec![0x63],
+                },
+            ]
+        );
+        assert_eq!(udh.concatenation(), None);
+        assert_eq!(udh.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_fill_bits_and_septets() {
+        let empty = UserDataHeader::default();
+        assert_eq!(empty.to_bytes(), Vec::<u8>::new());
+        assert_eq!((empty.fill_bits(), empty.septets()), (0, 0));
+
+        let concat = UserDataHeader::new(vec![InformationElement::Concatenation {
+            reference: ConcatReference::EightBit(1),
+            total: 2,
+            sequence: 1,
+        }]);
+        assert_eq!(
+            (concat.len(), concat.fill_bits(), concat.septets()),
+            (6, 1, 7)
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(UserDataHeader::parse(&[]).is_err());
+        assert!(matches!(
+            UserDataHeader::parse(&[0x05, 0x00, 0x03]),
+            Err(Gsm7Error::InvalidPduField { offset: 0, .. })
+        ));
+        assert!(matches!(
+            UserDataHeader::parse(&[0x04, 0x00, 0x03, 0x2A, 0x02]),
+            Err(Gsm7Error::InvalidPduField { offset: 1, .. })
+        ));
+        assert!(matches!(
+            UserDataHeader::parse(&[0x06, 0x70, 0x00, 0x00, 0x03, 0x2A, 0x02]),
+            Err(Gsm7Error::InvalidPduField { offset: 3, .. })
+        ));
+    }
+}
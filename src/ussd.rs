@@ -0,0 +1,247 @@
+//! USSD strings (3GPP TS 23.038 §6.1.2.3, TS 24.090).
+//!
+//! USSD strings use the cell broadcast data coding scheme, usually 0x0F
+//! (GSM 7-bit, language unspecified), and carry at most 160 octets: 182
+//! packed septets or 80 UCS-2 characters. Modems expose them over `AT+CUSD`
+//! as hex.
+
+use crate::cbs::{CbsDataCodingScheme, CbsLanguage};
+use crate::{
+    CharacterSet, Gsm7Config, Gsm7Error, Result, decode_ucs2, decode_with_config, encode_ucs2,
+    encode_with_config, hex, pack_septets, pack_septets_cr_padded, unpack_septets,
+    unpack_septets_cr_padded,
+};
+
+/// Most octets a USSD string can carry.
+pub const MAX_USSD_OCTETS: usize = 160;
+
+/// The usual USSD data coding scheme: GSM 7-bit, language unspecified (0x0F).
+pub const USSD_DCS: CbsDataCodingScheme = CbsDataCodingScheme::Language(CbsLanguage::Unspecified);
+
+/// Encode a USSD string as packed GSM 7-bit hex for DCS 0x0F.
+///
+/// See [`encode_ussd_with_dcs`].
+///
+/// # Example
+///
+/// ```rust
+/// use gsm7_alt::{USSD_DCS, decode_ussd, encode_ussd};
+///
+/// let hex = encode_ussd("*100#")?;
+/// assert_eq!(hex, "AA180C3602");
+/// // AT+CUSD=1,"AA180C3602",15
+/// assert_eq!(decode_ussd(&hex, USSD_DCS)?, "*100#");
+/// # Ok::<(), gsm7_alt::Gsm7Error>(())
+/// ```
+pub fn encode_ussd(text: &str) -> Result<String> {
+    encode_ussd_with_dcs(text, USSD_DCS)
+}
+
+/// Encode a USSD string as hex for the given data coding scheme.
+///
+/// GSM 7-bit text is packed with the `<CR>` padding rule, so a string ending
+/// in seven spare bits does not read as a trailing `@`. UCS-2 text is
+/// UTF-16BE. With DCS 0x11 the text must start with a two-letter language
+/// code and `<CR>`, as it does with 0x10; the code is sent packed in the
+/// first two octets.
+///
+/// # Arguments
+///
+/// * `text` - The string to send
+/// * `dcs` - The data coding scheme announced alongside the string
+///
+/// # Returns
+///
+/// The encoded string as uppercase hex.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::UnsupportedDataCoding` if `dcs` carries no text,
+/// `Gsm7Error::UnsupportedCharacter` if the text cannot be encoded, and
+/// `Gsm7Error::UssdTooLong` if it takes more than 160 octets.
+pub fn encode_ussd_with_dcs(text: &str, dcs: CbsDataCodingScheme) -> Result<String> {
+    let octets = match text_character_set(dcs)? {
+        CharacterSet::Ucs2 if dcs == CbsDataCodingScheme::LanguageInText(CharacterSet::Ucs2) => {
+            let (language, rest) =
+                split_language(text).ok_or_else(|| Gsm7Error::MalformedData {
+                    reason: "DCS 0x11 text must start with a language code and <CR>".to_string(),
+                })?;
+            let mut octets = pack_septets(&encode_with_config(language, &Gsm7Config::strict())?);
+            octets.extend(encode_ucs2(rest)?);
+            octets
+        }
+        CharacterSet::Ucs2 => encode_ucs2(text)?,
+        _ => pack_septets_cr_padded(&encode_with_config(text, &Gsm7Config::strict())?),
+    };
+    if octets.len() > MAX_USSD_OCTETS {
+        return Err(Gsm7Error::UssdTooLong {
+            octets: octets.len(),
+        });
+    }
+    Ok(hex::encode(&octets))
+}
+
+/// Decode a USSD string received as hex, such as the string in a `+CUSD`
+/// response.
+///
+/// GSM 7-bit strings are unpacked with `<CR>` padding removed. With DCS 0x11
+/// the packed language code is returned in front of the text followed by
+/// `<CR>`, as it arrives with 0x10. Use [`decode_ussd_lenient`] for senders
+/// that leave the seven spare bits zero instead of padding with `<CR>`.
+///
+/// # Arguments
+///
+/// * `hex` - The string as hex
+/// * `dcs` - The data coding scheme given with the string
+///
+/// # Returns
+///
+/// The decoded string. Unknown GSM 7-bit codes become the replacement character.
+///
+/// # Errors
+///
+/// Returns `Gsm7Error::InvalidHex` if `hex` is not a hex string,
+/// `Gsm7Error::UnsupportedDataCoding` if `dcs` carries no text, and
+/// `Gsm7Error::InvalidUtf16` for malformed UCS-2 text.
+pub fn decode_ussd(hex: &str, dcs: CbsDataCodingScheme) -> Result<String> {
+    decode_ussd_octets(&hex::decode(hex)?, dcs, false)
+}
+
+/// Decode a USSD string like [`decode_ussd`], for networks that skip the
+/// `<CR>` padding rule.
+///
+/// When the packed octets hold a whole number of eight septets and the last
+/// one is 0x00, it is taken for seven zero spare bits and dropped. A string
+/// that really ends in `@` at that position loses it, so use this only for
+/// senders known to be non-compliant.
+///
+/// # Errors
+///
+/// Returns the errors of [`decode_ussd`].
+pub fn decode_ussd_lenient(hex: &str, dcs: CbsDataCodingScheme) -> Result<String> {
+    decode_ussd_octets(&hex::decode(hex)?, dcs, true)
+}
+
+fn decode_ussd_octets(
+    octets: &[u8],
+    dcs: CbsDataCodingScheme,
+    drop_zero_spare_bits: bool,
+) -> Result<String> {
+    let config = Gsm7Config::default();
+    match text_character_set(dcs)? {
+        CharacterSet::Ucs2 if dcs == CbsDataCodingScheme::LanguageInText(CharacterSet::Ucs2) => {
+            let (language, text) = octets.split_at(octets.len().min(2));
+            let language = decode_with_config(&unpack_septets(language, 2), &config)?;
+            Ok(format!("{language}\r{}", decode_ucs2(text)?))
+        }
+        CharacterSet::Ucs2 => decode_ucs2(octets),
+        _ => {
+            let mut septets = unpack_septets_cr_padded(octets);
+            if drop_zero_spare_bits
+                && octets.len().is_multiple_of(7)
+                && septets.last() == Some(&0x00)
+            {
+                septets.pop();
+            }
+            decode_with_config(&septets, &config)
+        }
+    }
+}
+
+/// The text coding of a USSD data coding scheme.
+fn text_character_set(dcs: CbsDataCodingScheme) -> Result<CharacterSet> {
+    match dcs.character_set() {
+        Some(character_set @ (CharacterSet::Gsm7 | CharacterSet::Ucs2)) if !dcs.is_compressed() => {
+            Ok(character_set)
+        }
+        _ => Err(Gsm7Error::UnsupportedDataCoding {
+            dcs: dcs.to_octet(),
+        }),
+    }
+}
+
+/// Split `text` into its two-letter language code and the text after the `<CR>`.
+fn split_language(text: &str) -> Option<(&str, &str)> {
+    let (index, _) = text.char_indices().nth(2)?;
+    let (language, rest) = text.split_at(index);
+    Some((language, rest.strip_prefix('\r')?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gsm7_roundtrip() {
+        for text in ["*100#", "1234567", "Balance: 12.50 EUR\r", "Menu {1} €"] {
+            let hex = encode_ussd(text).unwrap();
+            assert_eq!(decode_ussd(&hex, USSD_DCS).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn test_trailing_spare_bits() {
+        // Seven septets padded with <CR> per the specification
+        assert_eq!(encode_ussd("1234567").unwrap(), "31D98C56B3DD1A");
+        // The same string from a sender that leaves the spare bits zero
+        assert_eq!(decode_ussd("31D98C56B3DD00", USSD_DCS).unwrap(), "1234567@");
+        assert_eq!(
+            decode_ussd_lenient("31D98C56B3DD00", USSD_DCS).unwrap(),
+            "1234567"
+        );
+    }
+
+    #[test]
+    fn test_trailing_at_sign_roundtrip() {
+        for text in ["ABCDEFG@", "ABCDEFGHIJKLMNO@"] {
+            let hex = encode_ussd(text).unwrap();
+            assert_eq!(decode_ussd(&hex, USSD_DCS).unwrap(), text);
+        }
+        assert_eq!(encode_ussd("ABCDEFG@").unwrap(), "41E19058341E01");
+    }
+
+    #[test]
+    fn test_length_limit() {
+        let hex = encode_ussd(&"a".repeat(182)).unwrap();
+        assert_eq!(hex.len(), 2 * MAX_USSD_OCTETS);
+        assert_eq!(
+            encode_ussd(&"a".repeat(183)),
+            Err(Gsm7Error::UssdTooLong { octets: 161 })
+        );
+        let ucs2 = CbsDataCodingScheme::from_octet(0x48);
+        assert!(encode_ussd_with_dcs(&"ж".repeat(80), ucs2).is_ok());
+        assert_eq!(
+            encode_ussd_with_dcs(&"ж".repeat(81), ucs2),
+            Err(Gsm7Error::UssdTooLong { octets: 162 })
+        );
+    }
+
+    #[test]
+    fn test_ucs2_variants() {
+        let ucs2 = CbsDataCodingScheme::from_octet(0x48);
+        let hex = encode_ussd_with_dcs("Баланс: 5₽", ucs2).unwrap();
+        assert!(hex.starts_with("0411"));
+        assert_eq!(decode_ussd(&hex, ucs2).unwrap(), "Баланс: 5₽");
+
+        let language_in_text = CbsDataCodingScheme::from_octet(0x11);
+        let hex = encode_ussd_with_dcs("ru\rБаланс", language_in_text).unwrap();
+        assert_eq!(hex[..4], hex::encode(&pack_septets(b"ru")));
+        assert_eq!(decode_ussd(&hex, language_in_text).unwrap(), "ru\rБаланс");
+        assert!(matches!(
+            encode_ussd_with_dcs("Баланс", language_in_text),
+            Err(Gsm7Error::MalformedData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_dcs() {
+        assert_eq!(
+            decode_ussd("AA", CbsDataCodingScheme::from_octet(0x44)),
+            Err(Gsm7Error::UnsupportedDataCoding { dcs: 0x44 })
+        );
+        assert!(matches!(
+            decode_ussd("A", USSD_DCS),
+            Err(Gsm7Error::InvalidHex { .. })
+        ));
+    }
+}